@@ -0,0 +1,322 @@
+use axum::http::Method;
+use common::{
+    ErrorCode, ErrorResponse, MessageResponse, NewQuestion, PatchOperation, Question,
+    QuestionEvent, QuestionEventKind,
+};
+use serde::Deserialize;
+use utoipa::openapi::path::{Operation, PathItem};
+use utoipa::openapi::Deprecated;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::request_routes::LEGACY_ALIASES;
+use crate::webhooks::{CreatedWebhook, DeliveryStatus, NewWebhook, Webhook, WebhookDelivery};
+
+/// Route serving the OpenAPI document.
+pub const OPENAPI_DOCUMENT_ROUTE: &str = "/openapi.json";
+
+/// Route serving the documentation UI.
+pub const DOCUMENTATION_UI_ROUTE: &str = "/docs";
+
+/// The body of `POST /api/v1/questions`: a single question or an array of questions.
+#[derive(Deserialize, ToSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+pub enum NewQuestions {
+    One(NewQuestion),
+    Many(Vec<NewQuestion>),
+}
+
+/// The merge patch body of `PATCH /api/v1/questions/{id}`: the fields to change, the missing
+/// ones are kept and `null` clears `type_of_content` or `type_of_question`.
+#[derive(Deserialize, ToSchema)]
+#[allow(dead_code)]
+pub struct QuestionChanges {
+    pub question_title: Option<String>,
+    pub type_of_content: Option<String>,
+    pub type_of_question: Option<Vec<String>>,
+}
+
+/// OpenAPI 3 description of the backend API, built from the handler annotations and the shared types.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Questions API",
+        description = "Store, search, update and delete questions. Errors are answered as `ErrorResponse`. \
+            Every client has a budget of reads and of writes: once used up, requests are answered \
+            `429` with `rate_limited` and a `Retry-After` header, and every answer carries `RateLimit-*` headers. \
+            The webhook routes require an admin API key in `X-API-Key`."
+    ),
+    paths(
+        crate::request_handlers::list_questions,
+        crate::request_handlers::add_questions,
+        crate::request_handlers::get_question_by_id,
+        crate::request_handlers::replace_question,
+        crate::request_handlers::update_question,
+        crate::request_handlers::delete_question,
+        crate::request_handlers::stream_question_events,
+        crate::webhooks::register_webhook,
+        crate::webhooks::list_webhooks,
+        crate::webhooks::delete_webhook,
+        crate::webhooks::list_webhook_deliveries,
+        crate::webhooks::list_dead_letters,
+        crate::webhooks::redeliver_dead_letter,
+    ),
+    components(schemas(
+        Question,
+        NewQuestion,
+        NewQuestions,
+        QuestionChanges,
+        PatchOperation,
+        QuestionEvent,
+        QuestionEventKind,
+        NewWebhook,
+        Webhook,
+        CreatedWebhook,
+        WebhookDelivery,
+        DeliveryStatus,
+        MessageResponse,
+        ErrorResponse,
+        ErrorCode
+    )),
+    tags(
+        (name = "questions", description = "Questions database"),
+        (name = "webhooks", description = "Question events posted to other tools")
+    )
+)]
+pub struct ApiDocumentation;
+
+impl ApiDocumentation {
+    /// The OpenAPI document, with every legacy route listed as a deprecated copy of its successor.
+    pub fn document() -> utoipa::openapi::OpenApi {
+        let mut document = Self::openapi();
+        for alias in &LEGACY_ALIASES {
+            let successor_path = alias.successor_route.replace(":id", "{id}");
+            let successor_operation = document
+                .paths
+                .paths
+                .get_mut(&successor_path)
+                .and_then(|path_item| path_operation(path_item, &alias.method).clone());
+            let Some(mut legacy_operation) = successor_operation else {
+                continue;
+            };
+            legacy_operation.deprecated = Some(Deprecated::True);
+            legacy_operation.operation_id = legacy_operation
+                .operation_id
+                .map(|operation_id| format!("legacy_{}", operation_id));
+            legacy_operation.description = Some(format!(
+                "Deprecated alias of `{} {}`.",
+                alias.method, successor_path
+            ));
+
+            let legacy_path = alias.legacy_route.replace(":id", "{id}");
+            let legacy_item = document.paths.paths.entry(legacy_path).or_default();
+            *path_operation(legacy_item, &alias.method) = Some(legacy_operation);
+        }
+        document
+    }
+}
+
+/// The operation of a path item for an HTTP method.
+///
+/// # Arguments
+/// * `path_item` - The path item of the OpenAPI document
+/// * `method` - One of the methods used by the API
+fn path_operation<'a>(path_item: &'a mut PathItem, method: &Method) -> &'a mut Option<Operation> {
+    match *method {
+        Method::POST => &mut path_item.post,
+        Method::PUT => &mut path_item.put,
+        Method::PATCH => &mut path_item.patch,
+        Method::DELETE => &mut path_item.delete,
+        _ => &mut path_item.get,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiDocumentation, DOCUMENTATION_UI_ROUTE, OPENAPI_DOCUMENT_ROUTE};
+    use crate::health_checks::health_routes;
+    use crate::metrics::{metrics_routes, BackendMetrics, METRICS_ROUTE};
+    use crate::question_events::QuestionEvents;
+    use crate::questions_database::DatabaseAvailability;
+    use crate::request_routes::setup_routes;
+    use crate::request_routes::LEGACY_ALIASES;
+    use axum::body::Body;
+    use axum::http::{header, Method, Request, StatusCode};
+    use common::route_paths::{HEALTH_ROUTE, READINESS_ROUTE};
+    use sqlx::postgres::PgPoolOptions;
+    use std::collections::{BTreeSet, HashSet};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    /// The sources declaring the route constants of the Backend.
+    const ROUTE_SOURCES: [&str; 3] = [
+        include_str!("../../common/src/route_paths.rs"),
+        include_str!("api_documentation.rs"),
+        include_str!("metrics.rs"),
+    ];
+
+    /// Operational routes that are deliberately left out of the OpenAPI document.
+    const UNDOCUMENTED_ROUTES: [&str; 5] = [
+        HEALTH_ROUTE,
+        READINESS_ROUTE,
+        METRICS_ROUTE,
+        OPENAPI_DOCUMENT_ROUTE,
+        DOCUMENTATION_UI_ROUTE,
+    ];
+
+    /// Every route declared as a `*_ROUTE` constant, in Axum syntax.
+    ///
+    /// The constants are read from their sources rather than listed here, so that a route
+    /// added with a new constant is checked without changing this test.
+    fn declared_routes() -> BTreeSet<&'static str> {
+        ROUTE_SOURCES
+            .iter()
+            .flat_map(|source| source.lines())
+            .filter_map(|line| line.trim().strip_prefix("pub const "))
+            .filter_map(|declaration| declaration.split_once("_ROUTE: &str = \""))
+            .filter_map(|(_, value)| value.strip_suffix("\";"))
+            .filter(|route| route.starts_with('/'))
+            .collect()
+    }
+
+    /// The documented methods of every documented path, in OpenAPI syntax.
+    fn documented_operations() -> Vec<(String, BTreeSet<String>)> {
+        let document = serde_json::to_value(ApiDocumentation::document())
+            .expect("The OpenAPI document serializes to JSON");
+        document["paths"]
+            .as_object()
+            .expect("The OpenAPI document has paths")
+            .iter()
+            .map(|(path, operations)| {
+                let methods = operations
+                    .as_object()
+                    .expect("A path item is an object")
+                    .keys()
+                    .filter(|key| !matches!(key.as_str(), "parameters" | "summary" | "description"))
+                    .map(|method| method.to_ascii_uppercase())
+                    .collect();
+                (path.clone(), methods)
+            })
+            .collect()
+    }
+
+    /// Asks the router which methods it accepts on a path, without running any handler.
+    ///
+    /// Axum answers `405 Method Not Allowed` with an `Allow` header when the path matches
+    /// but the method does not, and `404 Not Found` when no route matches the path.
+    async fn routed_methods(path: &str) -> Option<BTreeSet<String>> {
+        // The pool never connects, since no handler runs
+        let database_pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .expect("A lazy pool accepts any URL");
+        let database_pool = Arc::new(database_pool);
+        // Every router merged by `main`, without the layers that don't change the routes
+        let response = setup_routes(
+            database_pool.clone(),
+            Arc::new(QuestionEvents::new()),
+            Arc::new(HashSet::new()),
+        )
+        .merge(health_routes(
+            database_pool.clone(),
+            Arc::new(DatabaseAvailability::default()),
+        ))
+        .merge(metrics_routes(
+            Arc::new(BackendMetrics::new(1)),
+            database_pool,
+        ))
+        .oneshot(
+            Request::builder()
+                .method(Method::TRACE)
+                .uri(path)
+                .body(Body::empty())
+                .expect("The probe request is valid"),
+        )
+        .await
+        .expect("The router is infallible");
+        if response.status() == StatusCode::NOT_FOUND {
+            return None;
+        }
+        assert_eq!(
+            response.status(),
+            StatusCode::METHOD_NOT_ALLOWED,
+            "{}",
+            path
+        );
+        let allow_header = response
+            .headers()
+            .get(header::ALLOW)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        Some(
+            allow_header
+                .split(',')
+                .map(|method| method.trim().to_string())
+                // Axum answers HEAD for every GET route, which OpenAPI leaves implicit
+                .filter(|method| !method.is_empty() && method != "HEAD")
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn every_documented_operation_is_routed() {
+        for (documented_path, documented_methods) in documented_operations() {
+            let concrete_path = documented_path.replace("{id}", "1");
+            let routed_methods = routed_methods(&concrete_path)
+                .await
+                .unwrap_or_else(|| panic!("{} is documented but not routed", documented_path));
+            assert_eq!(
+                routed_methods, documented_methods,
+                "Methods of {} differ between the router and the OpenAPI document",
+                documented_path
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_routes_are_documented_as_deprecated() {
+        let document = serde_json::to_value(ApiDocumentation::document())
+            .expect("The OpenAPI document serializes to JSON");
+        for alias in &LEGACY_ALIASES {
+            let legacy_path = alias.legacy_route.replace(":id", "{id}");
+            let method = alias.method.as_str().to_ascii_lowercase();
+            assert_eq!(
+                document["paths"][&legacy_path][&method]["deprecated"],
+                serde_json::Value::Bool(true),
+                "{} {} is not documented as deprecated",
+                alias.method,
+                legacy_path
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn every_route_is_documented() {
+        let documented_paths: BTreeSet<String> = documented_operations()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        let declared_routes = declared_routes();
+        for route in UNDOCUMENTED_ROUTES {
+            assert!(declared_routes.contains(route), "{} is not declared", route);
+        }
+        for route in &declared_routes {
+            let concrete_path = route.replace(":id", "1");
+            assert!(
+                routed_methods(&concrete_path).await.is_some(),
+                "{} is declared but not routed",
+                route
+            );
+            let openapi_path = route.replace(":id", "{id}");
+            assert_eq!(
+                documented_paths.contains(&openapi_path),
+                !UNDOCUMENTED_ROUTES.contains(route),
+                "{} is routed but not documented, or documented but meant to be left out",
+                route
+            );
+        }
+        assert_eq!(
+            documented_paths.len() + UNDOCUMENTED_ROUTES.len(),
+            declared_routes.len()
+        );
+    }
+}
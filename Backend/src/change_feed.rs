@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use common::QuestionEvent;
+use sqlx::postgres::PgListener;
+use sqlx::{PgConnection, PgPool};
+
+use crate::configuration::BackendConfiguration;
+use crate::question_events::QuestionEvents;
+
+/// Channel of the notifications sent by the trigger of `questions_table`.
+pub const QUESTION_CHANGES_CHANNEL: &str = "question_changes";
+
+/// Function of the trigger, notifying `QUESTION_CHANGES_CHANNEL` of every added, updated or
+/// deleted question with a `QuestionEvent` as JSON payload, once the transaction commits.
+const NOTIFY_FUNCTION_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION notify_question_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('question_changes', json_build_object(
+        'kind', CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END,
+        'question_id', CASE TG_OP WHEN 'DELETE' THEN OLD.question_id ELSE NEW.question_id END
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql
+"#;
+
+/// Name of the trigger of `questions_table`.
+const NOTIFY_TRIGGER_NAME: &str = "questions_table_notify_change";
+
+/// Creates the trigger of `questions_table` calling the notify function.
+const NOTIFY_TRIGGER_SQL: &str = "CREATE TRIGGER questions_table_notify_change \
+     AFTER INSERT OR UPDATE OR DELETE ON questions_table \
+     FOR EACH ROW EXECUTE FUNCTION notify_question_change()";
+
+/// Creates a trigger of `questions_table`, unless one of the same name is already there.
+///
+/// # Arguments
+/// * `connection` - The connection of the installing transaction
+/// * `trigger_name` - The name of the trigger
+/// * `trigger_sql` - The statement creating the trigger
+///
+/// # Returns
+/// Whether the trigger was created.
+pub async fn create_trigger_unless_exists(
+    connection: &mut PgConnection,
+    trigger_name: &str,
+    trigger_sql: &str,
+) -> Result<bool, sqlx::Error> {
+    let trigger_exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM pg_trigger \
+         WHERE tgname = $1 AND tgrelid = 'questions_table'::regclass)",
+    )
+    .bind(trigger_name)
+    .fetch_one(&mut *connection)
+    .await?;
+    if !trigger_exists {
+        sqlx::query(trigger_sql).execute(&mut *connection).await?;
+    }
+    Ok(!trigger_exists)
+}
+
+/// Installs the function and the trigger publishing the changes of the questions, unless
+/// they are already there.
+///
+/// The installation is serialized with an advisory lock, so that Backend instances starting
+/// together don't race.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+async fn install_notify_trigger(database_pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut transaction = database_pool.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(NOTIFY_TRIGGER_NAME)
+        .execute(&mut *transaction)
+        .await?;
+    sqlx::query(NOTIFY_FUNCTION_SQL)
+        .execute(&mut *transaction)
+        .await?;
+    if create_trigger_unless_exists(&mut transaction, NOTIFY_TRIGGER_NAME, NOTIFY_TRIGGER_SQL)
+        .await?
+    {
+        tracing::info!(
+            trigger = NOTIFY_TRIGGER_NAME,
+            "Installed the trigger publishing the question changes"
+        );
+    }
+    transaction.commit().await
+}
+
+/// Listens to `QUESTION_CHANGES_CHANNEL` on a connection of the pool, after installing the
+/// trigger when possible.
+///
+/// The listener is set up first, so that no change committed after the trigger exists is missed.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+async fn start_listening(database_pool: &PgPool) -> Result<PgListener, sqlx::Error> {
+    let mut listener = PgListener::connect_with(database_pool).await?;
+    listener.listen(QUESTION_CHANGES_CHANNEL).await?;
+    if let Err(error) = install_notify_trigger(database_pool).await {
+        tracing::warn!(
+            %error,
+            "Couldn't install the trigger publishing the question changes, it must be installed by hand"
+        );
+    }
+    Ok(listener)
+}
+
+/// Publishes the changes of the questions notified by the database to every consumer of the
+/// question events, so that the changes made through any Backend instance, or straight in the
+/// database, reach the clients of every instance.
+///
+/// The listener keeps one connection of the pool, given back once the question events are
+/// closed as the server shuts down, so that the pool can be closed.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `question_events` - The broadcaster of the changes
+/// * `configuration` - The backend configuration holding the retry settings
+pub async fn feed_question_events(
+    database_pool: Arc<PgPool>,
+    question_events: Arc<QuestionEvents>,
+    configuration: BackendConfiguration,
+) {
+    tokio::select! {
+        _ = question_events.closed() => {}
+        _ = listen_for_changes(&database_pool, &question_events, &configuration) => {}
+    }
+}
+
+/// Publishes the notified changes until the pool is closed.
+///
+/// When the connection is lost, a resync is published, since the notifications sent meanwhile
+/// are gone, and the listener is set up again with the backoff of the database connection.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `question_events` - The broadcaster of the changes
+/// * `configuration` - The backend configuration holding the retry settings
+async fn listen_for_changes(
+    database_pool: &PgPool,
+    question_events: &QuestionEvents,
+    configuration: &BackendConfiguration,
+) {
+    let mut failed_attempts = 0;
+    loop {
+        let mut listener = match start_listening(database_pool).await {
+            Ok(listener) => listener,
+            Err(sqlx::Error::PoolClosed) => return,
+            Err(error) => {
+                failed_attempts += 1;
+                tracing::warn!(
+                    attempt = failed_attempts,
+                    %error,
+                    "Couldn't listen for the question changes, retrying"
+                );
+                tokio::time::sleep(configuration.database_connect_retry_delay(failed_attempts))
+                    .await;
+                continue;
+            }
+        };
+        if failed_attempts > 0 {
+            question_events.publish_resync();
+        }
+        failed_attempts = 0;
+        tracing::info!(
+            channel = QUESTION_CHANGES_CHANNEL,
+            "Listening for the question changes"
+        );
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    match serde_json::from_str::<QuestionEvent>(notification.payload()) {
+                        Ok(event) => question_events.publish(event),
+                        Err(error) => tracing::warn!(
+                            %error,
+                            payload = notification.payload(),
+                            "Ignoring an invalid question change notification"
+                        ),
+                    }
+                }
+                // The listener reconnects by itself on the next call
+                Ok(None) => {
+                    tracing::warn!(
+                        "Lost the connection listening for the question changes, reconnecting"
+                    );
+                    question_events.publish_resync();
+                }
+                Err(sqlx::Error::PoolClosed) => return,
+                Err(error) => {
+                    tracing::warn!(%error, "Listening for the question changes failed");
+                    question_events.publish_resync();
+                    break;
+                }
+            }
+        }
+    }
+}
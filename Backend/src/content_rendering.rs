@@ -0,0 +1,162 @@
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+
+/// Prefix that pulldown-cmark puts on the class of fenced code blocks (e.g. `language-rust`).
+const CODE_LANGUAGE_CLASS_PREFIX: &str = "language-";
+
+/// Renders the Markdown content of a question to sanitized HTML.
+///
+/// The Markdown is first converted to HTML, then every tag and attribute that is not
+/// on the ammonia allow list is removed, so scripts, event handlers and `javascript:`
+/// links never reach the browser. Fenced code blocks keep their `language-*` class
+/// so the Frontend can apply syntax highlighting styles, and task list items keep their
+/// checkbox, which is always a disabled `checkbox` input whatever the Markdown says.
+///
+/// # Arguments
+/// * `markdown_content` - The raw Markdown stored in `type_of_content`.
+///
+/// # Returns
+/// The sanitized HTML that is safe to insert into a page.
+pub fn render_markdown_content(markdown_content: &str) -> String {
+    // Enable the common GitHub style extensions (tables, strikethrough and task lists).
+    let mut markdown_options = Options::empty();
+    markdown_options.insert(Options::ENABLE_TABLES);
+    markdown_options.insert(Options::ENABLE_STRIKETHROUGH);
+    markdown_options.insert(Options::ENABLE_TASKLISTS);
+
+    // Convert the Markdown into unsanitized HTML.
+    let markdown_parser = Parser::new_ext(markdown_content, markdown_options);
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, markdown_parser);
+
+    // Remove anything that is not explicitly allowed from the rendered HTML.
+    Builder::default()
+        .add_tag_attributes("code", &["class"])
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["checked"])
+        .set_tag_attribute_value("input", "type", "checkbox")
+        .set_tag_attribute_value("input", "disabled", "")
+        .attribute_filter(|tag, attribute, value| match (tag, attribute) {
+            ("code", "class") => keep_language_classes(value).map(Into::into),
+            ("input", "checked") => Some("".into()),
+            _ => Some(value.into()),
+        })
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+/// Keeps only the `language-*` classes of a `<code>` element.
+///
+/// # Arguments
+/// * `class_value` - The value of the `class` attribute.
+///
+/// # Returns
+/// The filtered class list, or `None` when no code language class is left.
+fn keep_language_classes(class_value: &str) -> Option<String> {
+    let language_classes: Vec<&str> = class_value
+        .split_whitespace()
+        .filter(|class_name| {
+            class_name
+                .strip_prefix(CODE_LANGUAGE_CLASS_PREFIX)
+                .is_some_and(|language| {
+                    !language.is_empty()
+                        && language
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+'))
+                })
+        })
+        .collect();
+
+    if language_classes.is_empty() {
+        None
+    } else {
+        Some(language_classes.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_markdown_content;
+
+    #[test]
+    fn markdown_is_rendered_to_html() {
+        let html = render_markdown_content(
+            "# Title\n\n**bold** ~~gone~~\n\n| a | b |\n|---|---|\n| 1 | 2 |\n",
+        );
+        assert!(html.contains("<h1>Title</h1>"), "{}", html);
+        assert!(html.contains("<strong>bold</strong>"), "{}", html);
+        assert!(html.contains("<del>gone</del>"), "{}", html);
+        assert!(html.contains("<td>1</td>"), "{}", html);
+    }
+
+    #[test]
+    fn scripts_and_event_handlers_are_stripped() {
+        let html = render_markdown_content(
+            "<script>alert(1)</script>\n\n<img src=\"x\" onerror=\"alert(2)\">\n\n\
+             [link](javascript:alert(3)) <a href=\"#\" onclick=\"alert(4)\">a</a>",
+        );
+        for forbidden in ["<script", "alert(1)", "onerror", "javascript:", "onclick"] {
+            assert!(!html.contains(forbidden), "{} in {}", forbidden, html);
+        }
+        assert!(html.contains("<img src=\"x\""), "{}", html);
+    }
+
+    #[test]
+    fn code_blocks_keep_only_their_language_class() {
+        let html = render_markdown_content("```rust\nfn main() {}\n```\n");
+        assert!(html.contains("<code class=\"language-rust\">"), "{}", html);
+
+        let html = render_markdown_content(
+            "<pre><code class=\"evil language-c++ x\" onclick=\"alert(1)\">int</code></pre>",
+        );
+        assert!(html.contains("<code class=\"language-c++\">"), "{}", html);
+
+        let html = render_markdown_content("<code class=\"highlight language-\">x</code>");
+        assert!(html.contains("<code>x</code>"), "{}", html);
+    }
+
+    /// The attributes of every `<input>` of an HTML fragment, sorted, as they come in any order.
+    fn input_attributes(html: &str) -> Vec<Vec<&str>> {
+        html.split("<input ")
+            .skip(1)
+            .map(|input| {
+                let mut attributes: Vec<&str> = input
+                    .split('>')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_end_matches('/')
+                    .split_whitespace()
+                    .collect();
+                attributes.sort_unstable();
+                attributes
+            })
+            .collect()
+    }
+
+    #[test]
+    fn task_lists_keep_their_checkboxes() {
+        let html = render_markdown_content("- [ ] to do\n- [x] done\n");
+        assert_eq!(
+            input_attributes(&html),
+            [
+                vec!["disabled=\"\"", "type=\"checkbox\""],
+                vec!["checked=\"\"", "disabled=\"\"", "type=\"checkbox\""],
+            ],
+            "{}",
+            html
+        );
+    }
+
+    #[test]
+    fn raw_inputs_are_turned_into_disabled_checkboxes() {
+        let html = render_markdown_content(
+            "<input type=\"text\" name=\"password\" value=\"x\" onfocus=\"alert(1)\">",
+        );
+        assert_eq!(
+            input_attributes(&html),
+            [vec!["disabled=\"\"", "type=\"checkbox\""]],
+            "{}",
+            html
+        );
+    }
+}
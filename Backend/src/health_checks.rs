@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use common::route_paths::{HEALTH_ROUTE, READINESS_ROUTE};
+use common::{MessageResponse, ReadinessCheck, ReadinessReport};
+use sqlx::PgPool;
+
+use crate::questions_database::DatabaseAvailability;
+
+/// Longest time a readiness check may take before the database counts as unavailable,
+/// kept short so that probes get an answer before their own timeout.
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Sets up the liveness and readiness probes, which are left out of the OpenAPI document.
+///
+/// # Arguments
+/// * `database_pool` - The pool checked by the readiness probe
+/// * `database_availability` - Whether the database was reached since startup
+pub fn health_routes(
+    database_pool: Arc<PgPool>,
+    database_availability: Arc<DatabaseAvailability>,
+) -> Router {
+    Router::new()
+        .route(HEALTH_ROUTE, get(report_health))
+        .route(READINESS_ROUTE, get(report_readiness))
+        .with_state((database_pool, database_availability))
+}
+
+/// Answers as long as the process serves requests, without touching the database.
+async fn report_health() -> Json<MessageResponse> {
+    Json(MessageResponse {
+        message: "alive".to_string(),
+    })
+}
+
+/// Checks that the database answers and that its schema is usable.
+///
+/// While the Backend runs in degraded mode, because the database couldn't be reached at
+/// startup, the database isn't queried and the last connection error is reported instead.
+///
+/// # Returns
+/// `200 OK` with the report when every check passed, `503 Service Unavailable` otherwise.
+async fn report_readiness(
+    State((database_pool, database_availability)): State<(Arc<PgPool>, Arc<DatabaseAvailability>)>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    let checks = if !database_availability.is_connected() {
+        vec![ReadinessCheck::failed(
+            "database",
+            format!(
+                "Degraded mode, not connected since startup: {}",
+                database_availability
+                    .last_error()
+                    .unwrap_or_else(|| "no attempt finished yet".to_string())
+            ),
+        )]
+    } else {
+        match tokio::time::timeout(READINESS_CHECK_TIMEOUT, check_database(&database_pool)).await {
+            Ok(checks) => checks,
+            Err(_) => vec![ReadinessCheck::failed(
+                "database",
+                format!(
+                    "No answer within {} ms",
+                    READINESS_CHECK_TIMEOUT.as_millis()
+                ),
+            )],
+        }
+    };
+    let report = ReadinessReport::new(checks);
+    if !report.ready {
+        tracing::warn!(?report, "Backend is not ready");
+    }
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Runs a lightweight query, then checks the schema.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+///
+/// # Returns
+/// The `database` check, followed by the `migrations` check when the database answered.
+async fn check_database(database_pool: &PgPool) -> Vec<ReadinessCheck> {
+    if let Err(error) = sqlx::query("SELECT 1").execute(database_pool).await {
+        return vec![ReadinessCheck::failed("database", error.to_string())];
+    }
+    vec![
+        ReadinessCheck::passed("database"),
+        check_migrations(database_pool).await,
+    ]
+}
+
+/// Checks that the questions table exists and, when the schema is managed by sqlx
+/// migrations, that none of them failed.
+///
+/// The schema is currently created by hand as described in the README, so the
+/// `_sqlx_migrations` table is usually absent.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+async fn check_migrations(database_pool: &PgPool) -> ReadinessCheck {
+    let schema = sqlx::query_as::<_, (bool, bool)>(
+        "SELECT to_regclass('public.questions_table') IS NOT NULL, \
+         to_regclass('public._sqlx_migrations') IS NOT NULL",
+    )
+    .fetch_one(database_pool)
+    .await;
+    let has_migrations_table = match schema {
+        Ok((true, has_migrations_table)) => has_migrations_table,
+        Ok((false, _)) => {
+            return ReadinessCheck::failed("migrations", "The questions_table table is missing")
+        }
+        Err(error) => return ReadinessCheck::failed("migrations", error.to_string()),
+    };
+    if !has_migrations_table {
+        return ReadinessCheck::passed("migrations");
+    }
+
+    let failed_migration = sqlx::query_scalar::<_, i64>(
+        "SELECT version FROM _sqlx_migrations WHERE NOT success ORDER BY version LIMIT 1",
+    )
+    .fetch_optional(database_pool)
+    .await;
+    match failed_migration {
+        Ok(None) => ReadinessCheck::passed("migrations"),
+        Ok(Some(version)) => {
+            ReadinessCheck::failed("migrations", format!("Migration {} failed", version))
+        }
+        Err(error) => ReadinessCheck::failed("migrations", error.to_string()),
+    }
+}
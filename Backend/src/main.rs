@@ -1,3 +1,30 @@
+/// Describes the backend API as an OpenAPI document.
+mod api_documentation;
+
+/// Feeds the question events from the notifications of the database.
+mod change_feed;
+
+/// Loads the server settings from defaults, a TOML file, the environment and flags.
+mod configuration;
+
+/// Renders question Markdown content into sanitized HTML.
+mod content_rendering;
+
+/// Answers the liveness and readiness probes.
+mod health_checks;
+
+/// Collects the Prometheus metrics of the requests, the SQL statements and the database pool.
+mod metrics;
+
+/// Broadcasts the changes of the questions to the clients of the events route and the other consumers.
+mod question_events;
+
+/// Validates replacements and applies merge patches and JSON Patches to questions.
+mod question_patch;
+
+/// Limits the rate of requests of each client with token buckets.
+mod rate_limit;
+
 /// Handles the database interactions for questions.
 mod questions_database;
 
@@ -7,30 +34,243 @@ mod request_handlers;
 /// Sets up the routing for HTTP requests
 mod request_routes;
 
+/// Sets up the structured logs and the span of each request.
+mod request_tracing;
+
+/// Delivers the question events to the webhooks, with retries and a dead-letter list.
+mod webhook_delivery;
+
+/// Registers the webhooks and lists their deliveries.
+mod webhooks;
+
+/// Stops the server on SIGTERM or SIGINT after draining the requests being answered.
+mod shutdown;
+
+/// Serves HTTPS and redirects plain HTTP to it.
+mod tls_listener;
+
+use crate::change_feed::feed_question_events;
+use crate::configuration::{BackendArguments, BackendConfiguration};
+use crate::health_checks::health_routes;
+use crate::metrics::{metrics_routes, record_request_metrics, BackendMetrics, QueryMetricsLayer};
+use crate::question_events::QuestionEvents;
 use crate::questions_database::initialize_questions_database;
+use crate::rate_limit::{enforce_rate_limit, RateLimiter};
+use crate::request_tracing::{initialize_logging, with_request_tracing};
+use crate::shutdown::{
+    close_database_pool, serve_with_graceful_shutdown, track_in_flight_requests, InFlightRequests,
+};
+use crate::tls_listener::https_redirect_routes;
+use crate::webhook_delivery::dispatch_webhooks;
+use axum::extract::DefaultBodyLimit;
+use axum::http::StatusCode;
+use axum::middleware;
+use clap::Parser;
+use common::tls::ReloadingTlsConfig;
 use dotenv::dotenv;
 /// Imports routes from the `request_routes` module
-use request_routes::setup_routes;
+use request_routes::{compression_layer, cors_layer, setup_routes};
 use std::sync::Arc;
+use tower_http::timeout::TimeoutLayer;
 
 // Entry point for the application
-// Sets up TCP listener for the server, binds it to a specific address,
+// Sets up TCP listener for the server, binds it to the configured address,
 // and runs the server using Axum
 #[tokio::main]
 async fn main() {
     // Open a.env file to load the environment variables.
     dotenv().ok();
 
-    // Set up the pool of database connections.
-    let database_pool = Arc::new(initialize_questions_database().await);
+    // Resolve the configuration from the defaults, the config file, the environment and the flags.
+    let arguments = BackendArguments::parse();
+    if arguments.print_config {
+        print_configuration(&arguments);
+    }
+    let configuration = match BackendConfiguration::load(&arguments) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+
+    // Write structured logs, as configured, from now on, and collect metrics.
+    let metrics = Arc::new(BackendMetrics::new(configuration.database_max_connections));
+    initialize_logging(
+        configuration.log_format,
+        &configuration.log_filter,
+        QueryMetricsLayer::new(metrics.clone()),
+    );
+
+    // Set up the pool of database connections, waiting for the database or starting degraded.
+    let (database_pool, database_availability) =
+        match initialize_questions_database(&configuration).await {
+            Ok((database_pool, database_availability)) => {
+                (Arc::new(database_pool), database_availability)
+            }
+            Err(error) => {
+                tracing::error!(%error, "Couldn't start the server");
+                std::process::exit(1);
+            }
+        };
+
+    /* Publish the changes of the questions notified by the database, whichever instance made them */
+    let question_events = Arc::new(QuestionEvents::new());
+    tokio::spawn(feed_question_events(
+        database_pool.clone(),
+        question_events.clone(),
+        configuration.clone(),
+    ));
+
+    /* Deliver the question events to the registered webhooks */
+    tokio::spawn(dispatch_webhooks(
+        database_pool.clone(),
+        question_events.clone(),
+        configuration.clone(),
+    ));
 
-    /* Set up the routes for the server */
-    let routes = setup_routes(database_pool.clone());
-    println!("Server Has Started!");
+    /* Set up the routes for the server, limiting the body size and the rate of those of the API */
+    let webhook_admin_keys = Arc::new(configuration.webhook_admin_keys.iter().cloned().collect());
+    let mut api_routes = setup_routes(
+        database_pool.clone(),
+        question_events.clone(),
+        webhook_admin_keys,
+    )
+    .layer(DefaultBodyLimit::max(configuration.max_body_bytes));
+    if configuration.rate_limit_enabled {
+        let rate_limiter = Arc::new(RateLimiter::new(configuration.rate_limit_settings()));
+        api_routes = api_routes.layer(middleware::from_fn_with_state(
+            rate_limiter,
+            enforce_rate_limit,
+        ));
+    }
+    // Preflight requests are answered before reaching the rate limiter
+    if let Some(cors_layer) = cors_layer(&configuration.cors_allowed_origins) {
+        api_routes = api_routes.layer(cors_layer);
+    }
+    let mut routes = api_routes
+        .merge(health_routes(database_pool.clone(), database_availability))
+        .merge(metrics_routes(metrics.clone(), database_pool.clone()));
+    if configuration.compression_enabled {
+        routes = routes.layer(compression_layer(configuration.compression_min_bytes));
+    }
+    let in_flight_requests = Arc::new(InFlightRequests::default());
+    let routes = with_request_tracing(
+        routes
+            .layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                configuration.request_timeout(),
+            ))
+            .layer(middleware::from_fn_with_state(
+                metrics,
+                record_request_metrics,
+            ))
+            .layer(middleware::from_fn_with_state(
+                in_flight_requests.clone(),
+                track_in_flight_requests,
+            )),
+    );
 
-    /* Creates a TCP listener bound to address `0.0.0.0:1000  */
-    let tcp_listener = tokio::net::TcpListener::bind("0.0.0.0:1000").await.unwrap();
+    /* Load the certificate when serving HTTPS, reloading it whenever its files change */
+    let tls_config = match configuration.tls_files().map(ReloadingTlsConfig::load) {
+        Some(Ok(tls_config)) => Some(tls_config),
+        Some(Err(error)) => {
+            tracing::error!(%error, "Couldn't start the server");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    /* Creates a TCP listener bound to the configured address */
+    let tcp_listener = match tokio::net::TcpListener::bind(configuration.bind_address).await {
+        Ok(tcp_listener) => tcp_listener,
+        Err(error) => {
+            tracing::error!(
+                %error,
+                bind_address = %configuration.bind_address,
+                "Couldn't start the server"
+            );
+            std::process::exit(1);
+        }
+    };
+
+    /* Redirect plain HTTP to HTTPS when asked to */
+    if let Some(redirect_bind_address) = configuration.tls_redirect_bind_address {
+        let redirect_listener = match tokio::net::TcpListener::bind(redirect_bind_address).await {
+            Ok(redirect_listener) => redirect_listener,
+            Err(error) => {
+                tracing::error!(
+                    %error,
+                    %redirect_bind_address,
+                    "Couldn't start the HTTP to HTTPS redirect"
+                );
+                std::process::exit(1);
+            }
+        };
+        let redirect_routes = https_redirect_routes(configuration.bind_address.port());
+        tokio::spawn(async move {
+            if let Err(error) = axum::serve(redirect_listener, redirect_routes).await {
+                tracing::error!(%error, "HTTP to HTTPS redirect failed");
+            }
+        });
+        tracing::info!(%redirect_bind_address, "Redirecting HTTP to HTTPS");
+    }
+
+    tracing::info!(
+        bind_address = %configuration.bind_address,
+        tls = tls_config.is_some(),
+        "Server has started"
+    );
+
+    /* Serves incoming request using configured routes until SIGTERM or SIGINT */
+    let shutdown_summary = match serve_with_graceful_shutdown(
+        tcp_listener,
+        routes,
+        in_flight_requests,
+        configuration.shutdown_timeout(),
+        tls_config,
+        question_events,
+    )
+    .await
+    {
+        Ok(shutdown_summary) => shutdown_summary,
+        Err(error) => {
+            tracing::error!(%error, "Server failed");
+            std::process::exit(1);
+        }
+    };
+
+    /* Close the database connections once no request needs them */
+    let database_pool_closed = close_database_pool(&database_pool).await;
+    tracing::info!(
+        signal = shutdown_summary.signal,
+        drain_ms = shutdown_summary.drain_duration.as_millis() as u64,
+        served_requests = shutdown_summary.served_requests,
+        abandoned_requests = shutdown_summary.abandoned_requests,
+        database_pool_closed,
+        "Server has stopped"
+    );
+}
 
-    /* Serves incoming request using configured routes */
-    axum::serve(tcp_listener, routes).await.unwrap();
+/// Prints the merged configuration, even when it is invalid, then exits.
+///
+/// The validation errors are reported on the standard error, after the configuration,
+/// and make the process exit with status 2.
+///
+/// # Arguments
+/// * `arguments` - The parsed command line flags, which already include the environment variables.
+fn print_configuration(arguments: &BackendArguments) -> ! {
+    let configuration = match BackendConfiguration::resolve(arguments) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    print!("{}", configuration.to_printable_toml());
+    if let Err(error) = configuration.validate() {
+        eprintln!("{}", error);
+        std::process::exit(2);
+    }
+    std::process::exit(0);
 }
@@ -0,0 +1,265 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sqlx::PgPool;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Route serving the metrics in the Prometheus text format.
+pub const METRICS_ROUTE: &str = "/metrics";
+
+/// Target of the events sqlx logs for every SQL statement.
+pub const SQL_QUERY_TARGET: &str = "sqlx::query";
+
+/// Route label of the requests that match no route, so unknown paths can't add label values.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Buckets of the SQL statement durations in seconds, finer than the HTTP ones.
+const QUERY_DURATION_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// The Prometheus metrics of the Backend.
+pub struct BackendMetrics {
+    registry: Registry,
+    http_requests_total: IntCounterVec,
+    http_request_duration_seconds: HistogramVec,
+    database_query_duration_seconds: HistogramVec,
+    database_pool_connections: IntGaugeVec,
+}
+
+impl BackendMetrics {
+    /// Creates and registers every metric of the Backend.
+    ///
+    /// # Arguments
+    /// * `database_max_connections` - The size limit of the database pool, exported as a gauge
+    pub fn new(database_max_connections: u32) -> Self {
+        let registry = Registry::new();
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests answered"),
+            &["method", "route", "status"],
+        )
+        .expect("The metric is valid");
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to answer HTTP requests",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("The metric is valid");
+        let database_query_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "database_query_duration_seconds",
+                "Time taken by SQL statements",
+            )
+            .buckets(QUERY_DURATION_BUCKETS.to_vec()),
+            &["operation"],
+        )
+        .expect("The metric is valid");
+        let database_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "database_pool_connections",
+                "Connections of the database pool, by state",
+            ),
+            &["state"],
+        )
+        .expect("The metric is valid");
+        let database_pool_max_connections = IntGauge::new(
+            "database_pool_max_connections",
+            "Maximum number of connections of the database pool",
+        )
+        .expect("The metric is valid");
+        database_pool_max_connections.set(i64::from(database_max_connections));
+
+        for collector in [
+            Box::new(http_requests_total.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration_seconds.clone()),
+            Box::new(database_query_duration_seconds.clone()),
+            Box::new(database_pool_connections.clone()),
+            Box::new(database_pool_max_connections),
+        ] {
+            registry
+                .register(collector)
+                .expect("Every metric has a unique name");
+        }
+
+        BackendMetrics {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            database_query_duration_seconds,
+            database_pool_connections,
+        }
+    }
+
+    /// Counts an answered request and its latency.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method of the request
+    /// * `route` - The route template that matched, e.g. `/api/v1/questions/:id`
+    /// * `status` - The status of the answer
+    /// * `latency` - The time taken to answer
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+        self.http_requests_total.with_label_values(&labels).inc();
+        self.http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(latency.as_secs_f64());
+    }
+
+    /// Records the duration of a SQL statement.
+    ///
+    /// # Arguments
+    /// * `operation` - The SQL command, e.g. `SELECT`
+    /// * `elapsed_secs` - The duration of the statement in seconds
+    pub fn observe_query(&self, operation: &str, elapsed_secs: f64) {
+        self.database_query_duration_seconds
+            .with_label_values(&[operation])
+            .observe(elapsed_secs);
+    }
+
+    /// Renders every metric in the Prometheus text format, with the current database pool usage.
+    ///
+    /// # Arguments
+    /// * `database_pool` - The pool whose connections are counted
+    fn render(&self, database_pool: &PgPool) -> String {
+        let total_connections = i64::from(database_pool.size());
+        let idle_connections = database_pool.num_idle() as i64;
+        self.database_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle_connections);
+        self.database_pool_connections
+            .with_label_values(&["in_use"])
+            .set((total_connections - idle_connections).max(0));
+
+        let mut buffer = Vec::new();
+        if let Err(error) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!(%error, "Couldn't encode the metrics");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Sets up the route serving the metrics.
+///
+/// # Arguments
+/// * `metrics` - The metrics of the Backend
+/// * `database_pool` - The pool whose usage is exported
+///
+/// # Returns
+/// Axum {Router} serving `/metrics`
+pub fn metrics_routes(metrics: Arc<BackendMetrics>, database_pool: Arc<PgPool>) -> Router {
+    Router::new()
+        .route(METRICS_ROUTE, get(render_metrics))
+        .with_state((metrics, database_pool))
+}
+
+/// Answers the metrics in the Prometheus text format.
+async fn render_metrics(
+    State((metrics, database_pool)): State<(Arc<BackendMetrics>, Arc<PgPool>)>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics.render(&database_pool),
+    )
+}
+
+/// Middleware counting every request by method, route template and status, with its latency.
+///
+/// # Arguments
+/// * `metrics` - The metrics of the Backend
+/// * `request` - The request, whose matched route was set by the router
+/// * `next` - The rest of the middleware stack
+pub async fn record_request_metrics(
+    State(metrics): State<Arc<BackendMetrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched_path| matched_path.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let started_at = Instant::now();
+    let response = next.run(request).await;
+    metrics.observe_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        started_at.elapsed(),
+    );
+    response
+}
+
+/// Tracing layer recording the duration of the SQL statements logged by sqlx.
+pub struct QueryMetricsLayer {
+    metrics: Arc<BackendMetrics>,
+}
+
+impl QueryMetricsLayer {
+    /// Creates the layer.
+    ///
+    /// # Arguments
+    /// * `metrics` - The metrics the durations are recorded in
+    pub fn new(metrics: Arc<BackendMetrics>) -> Self {
+        QueryMetricsLayer { metrics }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for QueryMetricsLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        if event.metadata().target() != SQL_QUERY_TARGET {
+            return;
+        }
+        let mut query_fields = QueryFields::default();
+        event.record(&mut query_fields);
+        if let Some(elapsed_secs) = query_fields.elapsed_secs {
+            self.metrics
+                .observe_query(&query_fields.operation, elapsed_secs);
+        }
+    }
+}
+
+/// The fields of a sqlx statement event used by the metrics.
+#[derive(Default)]
+struct QueryFields {
+    /// The first word of the statement summary, e.g. `SELECT`.
+    operation: String,
+    elapsed_secs: Option<f64>,
+}
+
+impl Visit for QueryFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "summary" {
+            self.operation = value
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
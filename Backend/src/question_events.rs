@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::response::sse::Event;
+use common::QuestionEvent;
+use futures_util::stream::{self, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+
+/// Number of recent events kept to be replayed to the clients resuming with `Last-Event-ID`.
+const REPLAYED_EVENTS: usize = 256;
+
+/// Number of events a slow subscriber may fall behind before it is told to resynchronize.
+const SUBSCRIBER_CAPACITY: usize = 64;
+
+/// Name of the event telling a client that it missed events and must fetch the questions again.
+pub const RESYNC_EVENT: &str = "resync";
+
+/// A question event with the number it is streamed with.
+#[derive(Debug, Clone)]
+pub struct NumberedEvent {
+    /// The position of the event among those published by this run of the server.
+    pub id: u64,
+    /// The change, or `None` when changes may have been missed, e.g. while the connection to
+    /// the database was lost, so that the questions must be fetched again.
+    pub event: Option<QuestionEvent>,
+}
+
+/// The events kept for replay, and the ID of the next one.
+struct RecentEvents {
+    events: VecDeque<NumberedEvent>,
+    next_id: u64,
+}
+
+/// Broadcasts the changes of the questions, as received from the database, to the clients of
+/// the events route and to the other consumers inside the Backend.
+///
+/// Every instance numbers the events itself, so an event ID is the random ID of the run of the
+/// server followed by the number of the event, e.g. `3f9c2a7d1b6e4058-42`. A client resuming with
+/// the ID of another instance, or of an earlier run, is told to resynchronize rather than being
+/// replayed the events that happen to have the same numbers here.
+pub struct QuestionEvents {
+    run_id: Arc<str>,
+    recent_events: Mutex<RecentEvents>,
+    sender: broadcast::Sender<NumberedEvent>,
+    closed: watch::Sender<bool>,
+}
+
+/// The events received by one subscriber.
+pub struct Subscription {
+    /// The ID of the run of the server, prefixing the event IDs.
+    run_id: Arc<str>,
+    /// Whether events the subscriber needs were not kept, so it must fetch the questions again.
+    missed_events: bool,
+    /// The ID of the last event published when subscribing, then of the last event received.
+    latest_id: u64,
+    /// The kept events the subscriber missed since its `Last-Event-ID`.
+    replayed_events: VecDeque<NumberedEvent>,
+    /// The events published from now on.
+    receiver: broadcast::Receiver<NumberedEvent>,
+    /// Becomes `true` when the server shuts down.
+    closed: watch::Receiver<bool>,
+}
+
+impl QuestionEvents {
+    /// Creates the broadcaster, without any event yet.
+    pub fn new() -> Self {
+        QuestionEvents {
+            run_id: format!("{:016x}", rand::random::<u64>()).into(),
+            recent_events: Mutex::new(RecentEvents {
+                events: VecDeque::with_capacity(REPLAYED_EVENTS),
+                next_id: 1,
+            }),
+            sender: broadcast::channel(SUBSCRIBER_CAPACITY).0,
+            closed: watch::Sender::new(false),
+        }
+    }
+
+    /// Locks the recent events, recovering them if a holder panicked.
+    fn lock(&self) -> MutexGuard<'_, RecentEvents> {
+        self.recent_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Gives the event the next ID, keeps it for replay and sends it to every subscriber.
+    ///
+    /// # Arguments
+    /// * `event` - The change of a question
+    pub fn publish(&self, event: QuestionEvent) {
+        self.push(Some(event));
+    }
+
+    /// Tells every subscriber, present or resuming later, that changes may have been missed.
+    pub fn publish_resync(&self) {
+        self.push(None);
+    }
+
+    /// Numbers an event, keeps it for replay and sends it to every subscriber.
+    fn push(&self, event: Option<QuestionEvent>) {
+        let mut recent_events = self.lock();
+        let numbered_event = NumberedEvent {
+            id: recent_events.next_id,
+            event,
+        };
+        recent_events.next_id += 1;
+        if recent_events.events.len() == REPLAYED_EVENTS {
+            recent_events.events.pop_front();
+        }
+        recent_events.events.push_back(numbered_event.clone());
+        // Sent while locked, so that a subscriber subscribing meanwhile gets it exactly once
+        let _ = self.sender.send(numbered_event);
+    }
+
+    /// The number of an event streamed by this run of the server.
+    ///
+    /// # Arguments
+    /// * `event_id` - The ID of the event, as streamed
+    ///
+    /// # Returns
+    /// The number of the event, or `None` when it was streamed by another instance or run, or
+    /// is not an event ID.
+    fn event_number(&self, event_id: &str) -> Option<u64> {
+        let (run_id, number) = event_id.split_once('-')?;
+        if run_id != &*self.run_id {
+            return None;
+        }
+        number.parse().ok()
+    }
+
+    /// Subscribes to the events, with those missed since the last one received.
+    ///
+    /// # Arguments
+    /// * `last_event_id` - The `Last-Event-ID` of a reconnecting client
+    ///
+    /// # Returns
+    /// The missed events, or whether the subscriber must resynchronize, and the events to come.
+    pub fn subscribe(&self, last_event_id: Option<&str>) -> Subscription {
+        let last_event_id = last_event_id.map(|event_id| self.event_number(event_id));
+        let recent_events = self.lock();
+        let receiver = self.sender.subscribe();
+        let oldest_kept_id = recent_events
+            .events
+            .front()
+            .map_or(recent_events.next_id, |oldest_event| oldest_event.id);
+        let (missed_events, replayed_events) = match last_event_id {
+            None => (false, VecDeque::new()),
+            Some(Some(last_event_id))
+                if last_event_id < recent_events.next_id
+                    && last_event_id.saturating_add(1) >= oldest_kept_id =>
+            {
+                let replayed_events = recent_events
+                    .events
+                    .iter()
+                    .filter(|numbered_event| numbered_event.id > last_event_id)
+                    .cloned()
+                    .collect();
+                (false, replayed_events)
+            }
+            // Too old to replay, or from another instance or run of the server
+            Some(_) => (true, VecDeque::new()),
+        };
+        Subscription {
+            run_id: self.run_id.clone(),
+            missed_events,
+            latest_id: recent_events.next_id.saturating_sub(1),
+            replayed_events,
+            receiver,
+            closed: self.closed.subscribe(),
+        }
+    }
+
+    /// Ends every subscription, so that the event streams don't hold the server shutdown.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Waits until the broadcaster is closed, as the server shuts down.
+    pub async fn closed(&self) {
+        let _ = self.closed.subscribe().wait_for(|closed| *closed).await;
+    }
+}
+
+impl Subscription {
+    /// Waits for the next event: a resync if the subscriber missed too many, the replayed
+    /// events, then the events as they are published.
+    ///
+    /// # Returns
+    /// The event, or `None` once the server shuts down.
+    pub async fn next_event(&mut self) -> Option<NumberedEvent> {
+        if *self.closed.borrow() {
+            return None;
+        }
+        if self.missed_events {
+            self.missed_events = false;
+            return Some(NumberedEvent {
+                id: self.latest_id,
+                event: None,
+            });
+        }
+        if let Some(numbered_event) = self.replayed_events.pop_front() {
+            return Some(numbered_event);
+        }
+        tokio::select! {
+            biased;
+            _ = self.closed.wait_for(|closed| *closed) => None,
+            received = self.receiver.recv() => match received {
+                Ok(numbered_event) => {
+                    self.latest_id = numbered_event.id;
+                    Some(numbered_event)
+                }
+                // The subscriber fell too far behind and missed the oldest events
+                Err(RecvError::Lagged(_)) => Some(NumberedEvent {
+                    id: self.latest_id,
+                    event: None,
+                }),
+                Err(RecvError::Closed) => None,
+            },
+        }
+    }
+}
+
+/// The server-sent event of a question event, named after its kind, or the `resync` event
+/// telling a client to fetch the questions again.
+///
+/// # Arguments
+/// * `run_id` - The ID of the run of the server, prefixing the event ID
+/// * `numbered_event` - The event and its number
+fn sse_event(run_id: &str, numbered_event: &NumberedEvent) -> Event {
+    let event = Event::default().id(format!("{}-{}", run_id, numbered_event.id));
+    match numbered_event.event {
+        Some(question_event) => event
+            .event(question_event.kind.name())
+            .json_data(question_event)
+            .unwrap_or_else(|_| Event::default().comment("unserializable event")),
+        None => event.event(RESYNC_EVENT).data("{}"),
+    }
+}
+
+/// Turns a subscription into the stream of server-sent events of a client, until the server
+/// shuts down.
+///
+/// # Arguments
+/// * `subscription` - The subscription of the client
+pub fn event_stream(subscription: Subscription) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(subscription, |mut subscription| async move {
+        let numbered_event = subscription.next_event().await?;
+        Some((
+            Ok(sse_event(&subscription.run_id, &numbered_event)),
+            subscription,
+        ))
+    })
+}
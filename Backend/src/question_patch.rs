@@ -0,0 +1,505 @@
+use common::{NewQuestion, PatchOperation};
+use serde_json::{Map, Value};
+
+/// The fields of a question that a replacement or a patch can set.
+const QUESTION_FIELDS: [&str; 3] = ["question_title", "type_of_content", "type_of_question"];
+
+/// A patch of a question, in one of the supported formats.
+pub enum QuestionPatch {
+    /// An RFC 7396 JSON Merge Patch.
+    Merge(Value),
+    /// An RFC 6902 JSON Patch.
+    Json(Vec<PatchOperation>),
+}
+
+impl QuestionPatch {
+    /// Applies the patch to a question.
+    ///
+    /// # Arguments
+    /// * `question` - The stored fields of the question
+    ///
+    /// # Returns
+    /// The patched question, or a message describing why the patch can't be applied.
+    pub fn apply(&self, question: &NewQuestion) -> Result<NewQuestion, String> {
+        match self {
+            QuestionPatch::Merge(merge_patch) => apply_merge_patch(question, merge_patch),
+            QuestionPatch::Json(operations) => apply_json_patch(question, operations),
+        }
+    }
+}
+
+/// The JSON document of a question that patches are applied to.
+///
+/// # Arguments
+/// * `question` - The stored fields of the question
+fn question_document(question: &NewQuestion) -> Value {
+    serde_json::to_value(question).unwrap_or_default()
+}
+
+/// Validates a complete question document, as sent by a PUT or left by a patch.
+///
+/// Every field must be present with the right type, the title must not be blank,
+/// and unknown fields are refused instead of ignored.
+///
+/// # Arguments
+/// * `document` - The JSON document of the question
+///
+/// # Returns
+/// The question, or a message describing the first problem found.
+pub fn validate_question(document: &Value) -> Result<NewQuestion, String> {
+    let fields = document
+        .as_object()
+        .ok_or_else(|| "A question must be a JSON object".to_string())?;
+    if let Some(unknown_field) = fields
+        .keys()
+        .find(|field| !QUESTION_FIELDS.contains(&field.as_str()))
+    {
+        return Err(format!("Unknown field `{}`", unknown_field));
+    }
+
+    let question_title = match fields.get("question_title") {
+        Some(Value::String(question_title)) if !question_title.trim().is_empty() => question_title,
+        Some(Value::String(_)) => return Err("`question_title` must not be blank".to_string()),
+        Some(_) => return Err("`question_title` must be a string".to_string()),
+        None => return Err("`question_title` is required".to_string()),
+    };
+    let type_of_content = match fields.get("type_of_content") {
+        Some(Value::String(type_of_content)) => type_of_content,
+        Some(_) => return Err("`type_of_content` must be a string".to_string()),
+        None => return Err("`type_of_content` is required".to_string()),
+    };
+    let type_of_question = match fields.get("type_of_question") {
+        Some(Value::Array(types)) => types
+            .iter()
+            .map(|type_of_question| type_of_question.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>()
+            .ok_or_else(|| "`type_of_question` must be an array of strings".to_string())?,
+        Some(_) => return Err("`type_of_question` must be an array of strings".to_string()),
+        None => return Err("`type_of_question` is required".to_string()),
+    };
+
+    Ok(NewQuestion {
+        question_title: question_title.clone(),
+        type_of_content: type_of_content.clone(),
+        type_of_question,
+    })
+}
+
+/// Applies an RFC 7396 JSON Merge Patch to a question.
+///
+/// Members of the patch replace the fields of the question, and `null` members remove them,
+/// which clears `type_of_content` and `type_of_question`.
+///
+/// # Arguments
+/// * `question` - The stored fields of the question
+/// * `merge_patch` - The patch, which must be a JSON object
+///
+/// # Returns
+/// The patched question, or a message describing why the patch is invalid.
+fn apply_merge_patch(question: &NewQuestion, merge_patch: &Value) -> Result<NewQuestion, String> {
+    if !merge_patch.is_object() {
+        return Err("A merge patch must be a JSON object".to_string());
+    }
+    let mut document = question_document(question);
+    merge_into(&mut document, merge_patch);
+    validate_question(&with_cleared_fields(document))
+}
+
+/// Merges a patch into a document as described by RFC 7396.
+fn merge_into(document: &mut Value, merge_patch: &Value) {
+    let Value::Object(patch_members) = merge_patch else {
+        *document = merge_patch.clone();
+        return;
+    };
+    if !document.is_object() {
+        *document = Value::Object(Map::new());
+    }
+    if let Value::Object(document_members) = document {
+        for (name, patch_value) in patch_members {
+            if patch_value.is_null() {
+                document_members.remove(name);
+            } else {
+                merge_into(
+                    document_members.entry(name.clone()).or_insert(Value::Null),
+                    patch_value,
+                );
+            }
+        }
+    }
+}
+
+/// Applies an RFC 6902 JSON Patch to a question.
+///
+/// The operations are applied in order and the patch is all or nothing: the question
+/// is only changed when every operation succeeds and the result is a valid question.
+///
+/// # Arguments
+/// * `question` - The stored fields of the question
+/// * `operations` - The operations of the patch
+///
+/// # Returns
+/// The patched question, or a message naming the operation that failed.
+fn apply_json_patch(
+    question: &NewQuestion,
+    operations: &[PatchOperation],
+) -> Result<NewQuestion, String> {
+    let mut document = question_document(question);
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut document, operation)
+            .map_err(|message| format!("Operation {} failed: {}", index, message))?;
+    }
+    validate_question(&with_cleared_fields(document))
+}
+
+/// Applies one JSON Patch operation to a document.
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => {
+            let (parent, last_token) = parent_of(document, path)?;
+            match parent {
+                Value::Object(members) => {
+                    members.insert(last_token, value.clone());
+                }
+                Value::Array(items) => {
+                    let index = if last_token == "-" {
+                        items.len()
+                    } else {
+                        array_index(&last_token, items.len() + 1, path)?
+                    };
+                    items.insert(index, value.clone());
+                }
+                _ => return Err(format!("`{}` is not inside an object or an array", path)),
+            }
+        }
+        PatchOperation::Remove { path } => {
+            let (parent, last_token) = parent_of(document, path)?;
+            match parent {
+                Value::Object(members) => {
+                    members
+                        .remove(&last_token)
+                        .ok_or_else(|| format!("`{}` does not exist", path))?;
+                }
+                Value::Array(items) => {
+                    let index = array_index(&last_token, items.len(), path)?;
+                    items.remove(index);
+                }
+                _ => return Err(format!("`{}` does not exist", path)),
+            }
+        }
+        PatchOperation::Replace { path, value } => {
+            let target = document
+                .pointer_mut(path)
+                .filter(|_| !path.is_empty())
+                .ok_or_else(|| format!("`{}` does not exist", path))?;
+            *target = value.clone();
+        }
+        PatchOperation::Test { path, value } => {
+            let current_value = document
+                .pointer(path)
+                .ok_or_else(|| format!("`{}` does not exist", path))?;
+            if current_value != value {
+                return Err(format!("`{}` is not {}", path, value));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the container that a JSON Pointer points into, with its last reference token.
+///
+/// # Arguments
+/// * `document` - The document the pointer refers to
+/// * `path` - A JSON Pointer other than the whole document, e.g. `/type_of_question/-`
+fn parent_of<'a>(document: &'a mut Value, path: &str) -> Result<(&'a mut Value, String), String> {
+    let (parent_path, last_token) = path
+        .rsplit_once('/')
+        .ok_or_else(|| format!("`{}` is not a JSON Pointer to a field", path))?;
+    let parent = document
+        .pointer_mut(parent_path)
+        .ok_or_else(|| format!("`{}` does not exist", parent_path))?;
+    // Unescape the token as described by RFC 6901
+    Ok((parent, last_token.replace("~1", "/").replace("~0", "~")))
+}
+
+/// Parses an array index of a JSON Pointer, refusing leading zeros and indexes past the end.
+///
+/// # Arguments
+/// * `token` - The reference token
+/// * `upper_bound` - The first index that is out of range
+/// * `path` - The whole pointer, for the error message
+fn array_index(token: &str, upper_bound: usize, path: &str) -> Result<usize, String> {
+    let is_canonical = token == "0" || !token.starts_with('0');
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|index| is_canonical && *index < upper_bound)
+        .ok_or_else(|| format!("`{}` is not a valid index in `{}`", token, path))
+}
+
+/// Puts back, with their empty value, the fields that a patch may clear by removing them:
+/// `type_of_content` and `type_of_question`. The title stays required.
+fn with_cleared_fields(mut document: Value) -> Value {
+    if let Value::Object(fields) = &mut document {
+        fields
+            .entry("type_of_content")
+            .or_insert_with(|| Value::String(String::new()));
+        fields
+            .entry("type_of_question")
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_question, QuestionPatch};
+    use common::{NewQuestion, PatchOperation};
+    use serde_json::{json, Value};
+
+    /// The stored question that the patches are applied to.
+    fn stored_question() -> NewQuestion {
+        NewQuestion {
+            question_title: "What is Rust?".to_string(),
+            type_of_content: "text".to_string(),
+            type_of_question: vec!["basics".to_string(), "language".to_string()],
+        }
+    }
+
+    /// Applies a JSON Patch given as JSON to the stored question.
+    fn apply_json_patch(operations: Value) -> Result<NewQuestion, String> {
+        let operations: Vec<PatchOperation> =
+            serde_json::from_value(operations).expect("The operations are well formed");
+        QuestionPatch::Json(operations).apply(&stored_question())
+    }
+
+    /// Applies a JSON Merge Patch to the stored question.
+    fn apply_merge_patch(merge_patch: Value) -> Result<NewQuestion, String> {
+        QuestionPatch::Merge(merge_patch).apply(&stored_question())
+    }
+
+    #[test]
+    fn merge_patches_replace_the_given_fields() {
+        let question = apply_merge_patch(json!({"question_title": "What is Cargo?"})).unwrap();
+        assert_eq!(question.question_title, "What is Cargo?");
+        assert_eq!(question.type_of_content, "text");
+        assert_eq!(question.type_of_question, ["basics", "language"]);
+
+        let question = apply_merge_patch(json!({"type_of_question": ["tooling"]})).unwrap();
+        assert_eq!(question.type_of_question, ["tooling"]);
+    }
+
+    #[test]
+    fn merge_patch_nulls_clear_the_optional_fields() {
+        let question =
+            apply_merge_patch(json!({"type_of_content": null, "type_of_question": null})).unwrap();
+        assert_eq!(question.question_title, "What is Rust?");
+        assert_eq!(question.type_of_content, "");
+        assert!(question.type_of_question.is_empty());
+    }
+
+    #[test]
+    fn merge_patches_cannot_clear_the_title_or_add_fields() {
+        assert_eq!(
+            apply_merge_patch(json!({"question_title": null})).unwrap_err(),
+            "`question_title` is required"
+        );
+        assert_eq!(
+            apply_merge_patch(json!({"question_title": "  "})).unwrap_err(),
+            "`question_title` must not be blank"
+        );
+        assert_eq!(
+            apply_merge_patch(json!({"answer": "42"})).unwrap_err(),
+            "Unknown field `answer`"
+        );
+        assert_eq!(
+            apply_merge_patch(json!(["question_title"])).unwrap_err(),
+            "A merge patch must be a JSON object"
+        );
+    }
+
+    #[test]
+    fn json_patches_append_and_insert_types() {
+        let question = apply_json_patch(json!([
+            {"op": "add", "path": "/type_of_question/-", "value": "ownership"}
+        ]))
+        .unwrap();
+        assert_eq!(
+            question.type_of_question,
+            ["basics", "language", "ownership"]
+        );
+
+        let question = apply_json_patch(json!([
+            {"op": "add", "path": "/type_of_question/1", "value": "ownership"}
+        ]))
+        .unwrap();
+        assert_eq!(
+            question.type_of_question,
+            ["basics", "ownership", "language"]
+        );
+
+        let question = apply_json_patch(json!([
+            {"op": "add", "path": "/type_of_question/2", "value": "ownership"}
+        ]))
+        .unwrap();
+        assert_eq!(
+            question.type_of_question,
+            ["basics", "language", "ownership"]
+        );
+    }
+
+    #[test]
+    fn json_patches_refuse_indexes_out_of_range() {
+        assert_eq!(
+            apply_json_patch(json!([
+                {"op": "add", "path": "/type_of_question/3", "value": "ownership"}
+            ]))
+            .unwrap_err(),
+            "Operation 0 failed: `3` is not a valid index in `/type_of_question/3`"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "remove", "path": "/type_of_question/2"}])).unwrap_err(),
+            "Operation 0 failed: `2` is not a valid index in `/type_of_question/2`"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "remove", "path": "/type_of_question/01"}]))
+                .unwrap_err(),
+            "Operation 0 failed: `01` is not a valid index in `/type_of_question/01`"
+        );
+    }
+
+    #[test]
+    fn json_patches_remove_and_replace_values() {
+        let question = apply_json_patch(json!([
+            {"op": "remove", "path": "/type_of_question/0"},
+            {"op": "replace", "path": "/question_title", "value": "What is a crate?"},
+            {"op": "remove", "path": "/type_of_content"}
+        ]))
+        .unwrap();
+        assert_eq!(question.question_title, "What is a crate?");
+        assert_eq!(question.type_of_content, "");
+        assert_eq!(question.type_of_question, ["language"]);
+    }
+
+    #[test]
+    fn a_failing_test_operation_fails_the_whole_patch() {
+        let question = apply_json_patch(json!([
+            {"op": "test", "path": "/question_title", "value": "What is Rust?"},
+            {"op": "replace", "path": "/question_title", "value": "What is Cargo?"}
+        ]))
+        .unwrap();
+        assert_eq!(question.question_title, "What is Cargo?");
+
+        assert_eq!(
+            apply_json_patch(json!([
+                {"op": "replace", "path": "/question_title", "value": "What is Cargo?"},
+                {"op": "test", "path": "/type_of_content", "value": "video"}
+            ]))
+            .unwrap_err(),
+            "Operation 1 failed: `/type_of_content` is not \"video\""
+        );
+    }
+
+    #[test]
+    fn json_patches_refuse_unknown_paths() {
+        assert_eq!(
+            apply_json_patch(json!([{"op": "replace", "path": "/answer", "value": "42"}]))
+                .unwrap_err(),
+            "Operation 0 failed: `/answer` does not exist"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "remove", "path": "/answer"}])).unwrap_err(),
+            "Operation 0 failed: `/answer` does not exist"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "add", "path": "/answers/0", "value": "42"}]))
+                .unwrap_err(),
+            "Operation 0 failed: `/answers` does not exist"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "replace", "path": "", "value": {}}])).unwrap_err(),
+            "Operation 0 failed: `` does not exist"
+        );
+        assert_eq!(
+            apply_json_patch(json!([{"op": "add", "path": "/answer", "value": "42"}])).unwrap_err(),
+            "Unknown field `answer`"
+        );
+    }
+
+    #[test]
+    fn unknown_operations_and_members_are_refused() {
+        for operations in [
+            json!([{"op": "move", "from": "/question_title", "path": "/type_of_content"}]),
+            json!([{"op": "remove", "path": "/type_of_content", "value": "text"}]),
+            json!([{"op": "add", "path": "/type_of_question/-"}]),
+            json!([{"path": "/type_of_content"}]),
+        ] {
+            assert!(
+                serde_json::from_value::<Vec<PatchOperation>>(operations.clone()).is_err(),
+                "{} should be refused",
+                operations
+            );
+        }
+    }
+
+    #[test]
+    fn valid_questions_are_accepted() {
+        let question = validate_question(&json!({
+            "question_title": "What is Rust?",
+            "type_of_content": "",
+            "type_of_question": []
+        }))
+        .unwrap();
+        assert_eq!(question.question_title, "What is Rust?");
+    }
+
+    #[test]
+    fn invalid_questions_are_refused() {
+        let complete = json!({
+            "question_title": "What is Rust?",
+            "type_of_content": "text",
+            "type_of_question": ["basics"]
+        });
+        let with = |field: &str, value: Value| {
+            let mut document = complete.clone();
+            document[field] = value;
+            validate_question(&document).unwrap_err()
+        };
+        let without = |field: &str| {
+            let mut document = complete.clone();
+            document.as_object_mut().unwrap().remove(field);
+            validate_question(&document).unwrap_err()
+        };
+
+        assert_eq!(
+            validate_question(&json!("What is Rust?")).unwrap_err(),
+            "A question must be a JSON object"
+        );
+        assert_eq!(
+            with("question_title", json!(" \t")),
+            "`question_title` must not be blank"
+        );
+        assert_eq!(
+            with("question_title", json!(1)),
+            "`question_title` must be a string"
+        );
+        assert_eq!(without("question_title"), "`question_title` is required");
+        assert_eq!(
+            with("type_of_content", json!(["text"])),
+            "`type_of_content` must be a string"
+        );
+        assert_eq!(without("type_of_content"), "`type_of_content` is required");
+        assert_eq!(
+            with("type_of_question", json!([1])),
+            "`type_of_question` must be an array of strings"
+        );
+        assert_eq!(
+            with("type_of_question", json!("basics")),
+            "`type_of_question` must be an array of strings"
+        );
+        assert_eq!(
+            without("type_of_question"),
+            "`type_of_question` is required"
+        );
+        assert_eq!(with("question_id", json!(1)), "Unknown field `question_id`");
+    }
+}
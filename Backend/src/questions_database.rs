@@ -1,28 +1,256 @@
-use sqlx::{PgPool, Pool, Postgres};
-use std::env;
+use crate::configuration::BackendConfiguration;
+use log::LevelFilter;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{ConnectOptions, Connection, PgConnection, Pool, Postgres};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// Defines the structure and functionalities for managing questions
 pub mod questions_module {
 
-    use serde::{Deserialize, Serialize};
-    use sqlx::FromRow;
+    use crate::content_rendering::render_markdown_content;
 
-    /// Represents a single question in the database
-    /// Each question includes an ID, title, content type and a list of question categories
-    #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
-    pub struct QuestionStructure {
-        pub question_id: i32,
-        pub question_title: String,
-        pub type_of_content: String,
-        pub type_of_question: Vec<String>,
+    /// Represents a single question in the database.
+    /// The type is shared with the Frontend through the `common` crate.
+    pub use common::Question as QuestionStructure;
+
+    /// Adds the sanitized rendering of the Markdown content to a question.
+    pub trait RenderedContent {
+        /// Fills `type_of_content_html` with the sanitized HTML of the Markdown content.
+        fn with_rendered_content(self) -> Self;
+    }
+
+    impl RenderedContent for QuestionStructure {
+        fn with_rendered_content(mut self) -> Self {
+            self.type_of_content_html = render_markdown_content(&self.type_of_content);
+            self
+        }
+    }
+}
+
+/// Errors that prevent the Backend from starting with its database.
+#[derive(Debug)]
+pub enum DatabaseStartupError {
+    /// `database_url` couldn't be turned into connection options.
+    InvalidUrl(sqlx::Error),
+    /// PostgresSQL answered but refused the connection, e.g. wrong credentials or a missing
+    /// database, which retrying won't fix.
+    Rejected(sqlx::Error),
+    /// PostgresSQL couldn't be reached after every attempt, and the degraded start is disabled.
+    Unreachable {
+        attempts: u32,
+        last_error: sqlx::Error,
+    },
+}
+
+impl fmt::Display for DatabaseStartupError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DatabaseStartupError::InvalidUrl(error) => {
+                write!(formatter, "Couldn't parse the database url: {}", error)
+            }
+            DatabaseStartupError::Rejected(error) => {
+                write!(formatter, "The database refused the connection: {}", error)
+            }
+            DatabaseStartupError::Unreachable {
+                attempts,
+                last_error,
+            } => write!(
+                formatter,
+                "Couldn't connect to the database after {} attempts: {}",
+                attempts, last_error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DatabaseStartupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DatabaseStartupError::InvalidUrl(error) | DatabaseStartupError::Rejected(error) => {
+                Some(error)
+            }
+            DatabaseStartupError::Unreachable { last_error, .. } => Some(last_error),
+        }
+    }
+}
+
+/// Whether the database has been reached since the Backend started, shared with the
+/// readiness probe so that a degraded start reports not ready.
+#[derive(Debug, Default)]
+pub struct DatabaseAvailability {
+    connected: AtomicBool,
+    last_error: Mutex<Option<String>>,
+}
+
+impl DatabaseAvailability {
+    /// Whether a connection to the database succeeded since the Backend started.
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// The error of the last failed connection attempt, if any.
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .map(|last_error| last_error.clone())
+            .unwrap_or_default()
+    }
+
+    fn record_connected(&self) {
+        self.connected.store(true, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self, error: &sqlx::Error) {
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = Some(error.to_string());
+        }
     }
 }
 
-/// Creates the PostgresSQL database connection pool from scratch.
-pub async fn initialize_questions_database() -> Pool<Postgres> {
-    let question_database_url =
-        env::var("QUESTION_DATABASE_URL").expect("QUESTION_DATABASE_URL must be set in .env file");
-    PgPool::connect(&question_database_url)
-        .await
-        .expect("Couldn't connect to the database. Please check connection or database url")
+/// Creates the PostgresSQL database connection pool, once the database could be reached.
+///
+/// Connecting is attempted `database_connect_attempts` times with an exponential backoff.
+/// When every attempt failed and `database_degraded_start` is set, the pool is returned
+/// anyway: it connects on demand, and a background task keeps trying until the database
+/// answers, which the returned `DatabaseAvailability` reports to the readiness probe.
+///
+/// Every SQL statement is logged with its duration at the `debug` level of the `sqlx::query`
+/// target, and at the `warn` level when it takes longer than the slow query threshold.
+///
+/// # Arguments
+/// * `configuration` - The backend configuration holding the database URL and pool settings.
+///
+/// # Returns
+/// The pool with the availability of the database, or the reason the server can't start.
+pub async fn initialize_questions_database(
+    configuration: &BackendConfiguration,
+) -> Result<(Pool<Postgres>, Arc<DatabaseAvailability>), DatabaseStartupError> {
+    let connect_options = PgConnectOptions::from_str(&configuration.database_url)
+        .map_err(DatabaseStartupError::InvalidUrl)?
+        .log_statements(LevelFilter::Debug)
+        .log_slow_statements(LevelFilter::Warn, configuration.slow_query_threshold());
+    let database_pool = PgPoolOptions::new()
+        .max_connections(configuration.database_max_connections)
+        .min_connections(configuration.database_min_connections)
+        .acquire_timeout(configuration.database_acquire_timeout())
+        .idle_timeout(configuration.database_idle_timeout())
+        .connect_lazy_with(connect_options.clone());
+    let availability = Arc::new(DatabaseAvailability::default());
+
+    let mut failed_attempts = 0;
+    loop {
+        let error = match try_connect(&connect_options, configuration).await {
+            Ok(()) => {
+                availability.record_connected();
+                tracing::info!(attempts = failed_attempts + 1, "Connected to the database");
+                return Ok((database_pool, availability));
+            }
+            Err(error) if is_rejection(&error) => {
+                return Err(DatabaseStartupError::Rejected(error))
+            }
+            Err(error) => error,
+        };
+        availability.record_failure(&error);
+        failed_attempts += 1;
+
+        if failed_attempts >= configuration.database_connect_attempts {
+            if !configuration.database_degraded_start {
+                return Err(DatabaseStartupError::Unreachable {
+                    attempts: failed_attempts,
+                    last_error: error,
+                });
+            }
+            tracing::warn!(
+                attempts = failed_attempts,
+                %error,
+                "Starting in degraded mode, connecting to the database in the background"
+            );
+            tokio::spawn(connect_in_background(
+                connect_options,
+                configuration.clone(),
+                availability.clone(),
+                failed_attempts,
+            ));
+            return Ok((database_pool, availability));
+        }
+
+        let retry_delay = configuration.database_connect_retry_delay(failed_attempts);
+        tracing::warn!(
+            attempt = failed_attempts,
+            %error,
+            retry_delay_ms = retry_delay.as_millis() as u64,
+            "Couldn't connect to the database, retrying"
+        );
+        tokio::time::sleep(retry_delay).await;
+    }
+}
+
+/// Keeps trying to connect to the database after a degraded start, until it answers.
+///
+/// # Arguments
+/// * `connect_options` - The connection options of the pool
+/// * `configuration` - The backend configuration holding the retry settings
+/// * `availability` - Marked as connected once an attempt succeeds
+/// * `failed_attempts` - The attempts that already failed at startup
+async fn connect_in_background(
+    connect_options: PgConnectOptions,
+    configuration: BackendConfiguration,
+    availability: Arc<DatabaseAvailability>,
+    mut failed_attempts: u32,
+) {
+    loop {
+        tokio::time::sleep(configuration.database_connect_retry_delay(failed_attempts)).await;
+        match try_connect(&connect_options, &configuration).await {
+            Ok(()) => {
+                availability.record_connected();
+                tracing::info!(
+                    attempts = failed_attempts + 1,
+                    "Connected to the database, leaving degraded mode"
+                );
+                return;
+            }
+            Err(error) => {
+                availability.record_failure(&error);
+                failed_attempts += 1;
+                tracing::debug!(attempt = failed_attempts, %error, "Database still unreachable");
+            }
+        }
+    }
+}
+
+/// Opens and closes a single connection, bounded by the acquire timeout of the pool.
+///
+/// A single connection is used rather than the pool, which would keep retrying
+/// until its acquire timeout.
+async fn try_connect(
+    connect_options: &PgConnectOptions,
+    configuration: &BackendConfiguration,
+) -> Result<(), sqlx::Error> {
+    let connection = tokio::time::timeout(
+        configuration.database_acquire_timeout(),
+        PgConnection::connect_with(connect_options),
+    )
+    .await
+    .map_err(|_| {
+        sqlx::Error::Io(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "Timed out while connecting to the database",
+        ))
+    })??;
+    connection.close().await
+}
+
+/// Whether PostgresSQL refused the connection for a reason that retrying won't fix:
+/// invalid credentials (SQLSTATE class 28) or a missing database (3D000).
+fn is_rejection(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Database(database_error) => database_error
+            .code()
+            .is_some_and(|code| code.starts_with("28") || code == "3D000"),
+        _ => false,
+    }
 }
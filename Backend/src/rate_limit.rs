@@ -0,0 +1,559 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use common::{ErrorCode, API_KEY_HEADER};
+
+use crate::request_handlers::error_response;
+
+/// Header listing the addresses a request went through, the client first.
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// How often the buckets that refilled completely are forgotten, bounding the memory used.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A token bucket budget: `burst` requests at once, refilled at `per_minute` requests a minute.
+#[derive(Debug, Clone, Copy)]
+pub struct Budget {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl Budget {
+    /// Tokens added to the bucket every second.
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.per_minute) / 60.0
+    }
+
+    /// Time for an empty bucket to refill completely, the window of the `RateLimit-Policy`.
+    fn window(&self) -> Duration {
+        Duration::from_secs_f64(f64::from(self.burst) / self.refill_rate())
+    }
+}
+
+/// Settings of the rate limiter, taken from the configuration.
+#[derive(Debug, Clone)]
+pub struct RateLimitSettings {
+    /// Budget of the GET, HEAD and OPTIONS requests of each client.
+    pub read: Budget,
+    /// Budget of the requests that change questions.
+    pub write: Budget,
+    /// Proxies, such as the Frontend, whose `X-Forwarded-For` header names the client.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// API keys that get their own budget instead of the one of their address.
+    pub api_keys: HashSet<String>,
+}
+
+/// Who a budget belongs to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    /// A known API key sent in `X-API-Key`.
+    ApiKey(String),
+    /// The address of the client, behind the trusted proxies.
+    Address(IpAddr),
+}
+
+impl fmt::Display for ClientKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Only the start of the key, so that the logs don't leak it
+            ClientKey::ApiKey(api_key) => write!(
+                formatter,
+                "key:{}…",
+                api_key.chars().take(4).collect::<String>()
+            ),
+            ClientKey::Address(address) => write!(formatter, "{}", address),
+        }
+    }
+}
+
+/// Whether a request reads or changes questions, each kind having its own budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    /// The kind of access of a request method.
+    fn of(method: &Method) -> Self {
+        if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            Access::Read
+        } else {
+            Access::Write
+        }
+    }
+}
+
+/// The tokens left to a client for one kind of access.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// The outcome of taking a token, with what the `RateLimit-*` headers report.
+struct Decision {
+    allowed: bool,
+    budget: Budget,
+    remaining: u32,
+    /// Time until the bucket is full again.
+    reset_after: Duration,
+    /// Time until the next token, when the request was refused.
+    retry_after: Duration,
+}
+
+/// Token bucket rate limiter keyed by API key or client address, with separate budgets
+/// for reads and writes.
+pub struct RateLimiter {
+    settings: RateLimitSettings,
+    buckets: Mutex<HashMap<(ClientKey, Access), TokenBucket>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter where every client starts with full buckets.
+    ///
+    /// # Arguments
+    /// * `settings` - The budgets, trusted proxies and known API keys
+    pub fn new(settings: RateLimitSettings) -> Self {
+        RateLimiter {
+            settings,
+            buckets: Mutex::new(HashMap::new()),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Finds who the request counts against: its known API key, or else the address of the
+    /// client, read from `X-Forwarded-For` when the request came through trusted proxies.
+    ///
+    /// # Arguments
+    /// * `headers` - The headers of the request
+    /// * `peer_address` - The address the connection came from, when known
+    fn client_key(&self, headers: &HeaderMap, peer_address: Option<IpAddr>) -> ClientKey {
+        if let Some(api_key) = headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|api_key| self.settings.api_keys.contains(*api_key))
+        {
+            return ClientKey::ApiKey(api_key.to_string());
+        }
+
+        let Some(peer_address) = peer_address else {
+            return ClientKey::Address(IpAddr::from([0, 0, 0, 0]));
+        };
+        // Walk the chain from the closest hop, skipping the trusted proxies: the first
+        // address that isn't one of them is the client, as the ones before it can be forged
+        let forwarded_addresses = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|address| address.trim().parse::<IpAddr>());
+        let mut client_address = peer_address;
+        for forwarded_address in forwarded_addresses.rev() {
+            if !self.settings.trusted_proxies.contains(&client_address) {
+                break;
+            }
+            match forwarded_address {
+                Ok(forwarded_address) => client_address = forwarded_address,
+                Err(_) => break,
+            }
+        }
+        ClientKey::Address(client_address)
+    }
+
+    /// The budget of a kind of access.
+    fn budget(&self, access: Access) -> Budget {
+        match access {
+            Access::Read => self.settings.read,
+            Access::Write => self.settings.write,
+        }
+    }
+
+    /// Takes a token from the bucket of a client, refilling it for the time elapsed.
+    ///
+    /// # Arguments
+    /// * `client_key` - Who the request counts against
+    /// * `access` - Whether the request reads or changes questions
+    /// * `now` - The time the request arrived
+    fn take_token(&self, client_key: ClientKey, access: Access, now: Instant) -> Decision {
+        let budget = self.budget(access);
+        let capacity = f64::from(budget.burst);
+        let refill_rate = budget.refill_rate();
+        self.sweep_full_buckets(now);
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bucket = buckets.entry((client_key, access)).or_insert(TokenBucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_rate).min(capacity);
+        bucket.updated_at = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+        Decision {
+            allowed,
+            budget,
+            remaining: bucket.tokens.floor() as u32,
+            reset_after: Duration::from_secs_f64((capacity - bucket.tokens) / refill_rate),
+            retry_after: Duration::from_secs_f64((1.0 - bucket.tokens).max(0.0) / refill_rate),
+        }
+    }
+
+    /// Forgets the buckets that refilled completely, which behave like new ones.
+    fn sweep_full_buckets(&self, now: Instant) {
+        let Ok(mut last_sweep) = self.last_sweep.try_lock() else {
+            return;
+        };
+        if now.duration_since(*last_sweep) < SWEEP_INTERVAL {
+            return;
+        }
+        *last_sweep = now;
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        buckets.retain(|(_, access), bucket| {
+            now.duration_since(bucket.updated_at) < self.budget(*access).window()
+        });
+    }
+}
+
+/// Middleware refusing the requests of clients that used up their budget with
+/// `429 Too Many Requests` and `Retry-After`, and adding the `RateLimit-*` headers
+/// to every answer.
+///
+/// # Arguments
+/// * `rate_limiter` - The shared limiter
+/// * `request` - The incoming request, with the address of the connection in its extensions
+/// * `next` - The rest of the middleware stack and the handler
+pub async fn enforce_rate_limit(
+    State(rate_limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer_address = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip());
+    let client_key = rate_limiter.client_key(request.headers(), peer_address);
+    let access = Access::of(request.method());
+    let decision = rate_limiter.take_token(client_key.clone(), access, Instant::now());
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::debug!(client = %client_key, ?access, "Rate limit exceeded");
+        let mut response = error_response(ErrorCode::RateLimited, "Too many requests, retry later");
+        insert_header(
+            response.headers_mut(),
+            "retry-after",
+            ceil_secs(decision.retry_after),
+        );
+        response
+    };
+
+    let headers = response.headers_mut();
+    insert_header(headers, "ratelimit-limit", u64::from(decision.budget.burst));
+    insert_header(
+        headers,
+        "ratelimit-remaining",
+        u64::from(decision.remaining),
+    );
+    insert_header(headers, "ratelimit-reset", ceil_secs(decision.reset_after));
+    if let Ok(policy) = HeaderValue::from_str(&format!(
+        "{};w={}",
+        decision.budget.burst,
+        ceil_secs(decision.budget.window())
+    )) {
+        headers.insert(HeaderName::from_static("ratelimit-policy"), policy);
+    }
+    response
+}
+
+/// Rounds a duration up to whole seconds, as the rate limit headers expect.
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs_f64().ceil() as u64
+}
+
+/// Sets a header with a numeric value.
+fn insert_header(headers: &mut HeaderMap, name: &'static str, value: u64) {
+    headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        enforce_rate_limit, Access, Budget, ClientKey, RateLimitSettings, RateLimiter,
+        FORWARDED_FOR_HEADER,
+    };
+    use axum::body::Body;
+    use axum::extract::{ConnectInfo, Request};
+    use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
+    use axum::middleware;
+    use axum::routing::get;
+    use axum::Router;
+    use common::API_KEY_HEADER;
+    use std::collections::HashSet;
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tower::ServiceExt;
+
+    const CLIENT: [u8; 4] = [203, 0, 113, 7];
+    const FRONTEND: [u8; 4] = [10, 0, 0, 2];
+    const LOAD_BALANCER: [u8; 4] = [10, 0, 0, 1];
+
+    /// A limiter allowing 3 reads at once refilled at one a second, and 1 write at once
+    /// refilled at one every 10 seconds, behind the Frontend and a load balancer.
+    fn test_limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitSettings {
+            read: Budget {
+                per_minute: 60,
+                burst: 3,
+            },
+            write: Budget {
+                per_minute: 6,
+                burst: 1,
+            },
+            trusted_proxies: vec![IpAddr::from(FRONTEND), IpAddr::from(LOAD_BALANCER)],
+            api_keys: HashSet::from(["a-known-api-key".to_string()]),
+        })
+    }
+
+    /// Headers carrying the given `X-Forwarded-For` values, one header line each.
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(FORWARDED_FOR_HEADER, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    /// The address key of an IPv4 address.
+    fn address(octets: [u8; 4]) -> ClientKey {
+        ClientKey::Address(IpAddr::from(octets))
+    }
+
+    #[test]
+    fn buckets_refill_at_the_budget_rate() {
+        let rate_limiter = test_limiter();
+        let start = Instant::now();
+        let take = |elapsed_ms| {
+            rate_limiter.take_token(
+                address(CLIENT),
+                Access::Read,
+                start + Duration::from_millis(elapsed_ms),
+            )
+        };
+
+        for remaining in [2, 1, 0] {
+            let decision = take(0);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+        }
+        let refused = take(0);
+        assert!(!refused.allowed);
+        assert_eq!(refused.retry_after, Duration::from_secs(1));
+        assert_eq!(refused.reset_after, Duration::from_secs(3));
+
+        let refused = take(500);
+        assert!(!refused.allowed);
+        assert_eq!(refused.retry_after, Duration::from_millis(500));
+
+        let allowed = take(1000);
+        assert!(allowed.allowed);
+        assert_eq!(allowed.remaining, 0);
+
+        // A bucket never holds more than its burst
+        let allowed = take(60_000);
+        assert!(allowed.allowed);
+        assert_eq!(allowed.remaining, 2);
+        assert_eq!(allowed.reset_after, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reads_and_writes_have_separate_budgets() {
+        let rate_limiter = test_limiter();
+        let now = Instant::now();
+        assert!(
+            rate_limiter
+                .take_token(address(CLIENT), Access::Write, now)
+                .allowed
+        );
+        let refused = rate_limiter.take_token(address(CLIENT), Access::Write, now);
+        assert!(!refused.allowed);
+        assert_eq!(refused.retry_after, Duration::from_secs(10));
+        assert!(
+            rate_limiter
+                .take_token(address(CLIENT), Access::Read, now)
+                .allowed
+        );
+        // Other clients have their own buckets
+        assert!(
+            rate_limiter
+                .take_token(address(FRONTEND), Access::Write, now)
+                .allowed
+        );
+
+        assert_eq!(Access::of(&Method::GET), Access::Read);
+        assert_eq!(Access::of(&Method::HEAD), Access::Read);
+        assert_eq!(Access::of(&Method::OPTIONS), Access::Read);
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            assert_eq!(Access::of(&method), Access::Write);
+        }
+    }
+
+    #[test]
+    fn known_api_keys_get_their_own_budget() {
+        let rate_limiter = test_limiter();
+        let mut headers = HeaderMap::new();
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("a-known-api-key"));
+        assert_eq!(
+            rate_limiter.client_key(&headers, Some(IpAddr::from(CLIENT))),
+            ClientKey::ApiKey("a-known-api-key".to_string())
+        );
+
+        headers.insert(
+            API_KEY_HEADER,
+            HeaderValue::from_static("an-unknown-api-key"),
+        );
+        assert_eq!(
+            rate_limiter.client_key(&headers, Some(IpAddr::from(CLIENT))),
+            address(CLIENT)
+        );
+        assert_eq!(
+            ClientKey::ApiKey("a-known-api-key".to_string()).to_string(),
+            "key:a-kn…"
+        );
+    }
+
+    #[test]
+    fn forwarded_addresses_are_only_read_from_trusted_proxies() {
+        let rate_limiter = test_limiter();
+        let headers = forwarded_for(&["198.51.100.1"]);
+        assert_eq!(
+            rate_limiter.client_key(&headers, Some(IpAddr::from(CLIENT))),
+            address(CLIENT)
+        );
+        assert_eq!(
+            rate_limiter.client_key(&headers, Some(IpAddr::from(FRONTEND))),
+            address([198, 51, 100, 1])
+        );
+        assert_eq!(
+            rate_limiter.client_key(&HeaderMap::new(), Some(IpAddr::from(FRONTEND))),
+            address(FRONTEND)
+        );
+        assert_eq!(
+            rate_limiter.client_key(&headers, None),
+            address([0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn spoofed_forwarded_addresses_are_ignored() {
+        let rate_limiter = test_limiter();
+        // The client wrote the first address itself, the Frontend appended the real one
+        assert_eq!(
+            rate_limiter.client_key(
+                &forwarded_for(&["192.0.2.66, 203.0.113.7"]),
+                Some(IpAddr::from(FRONTEND))
+            ),
+            address(CLIENT)
+        );
+        // A client naming a trusted proxy doesn't get further than its own address
+        assert_eq!(
+            rate_limiter.client_key(
+                &forwarded_for(&["192.0.2.66, 10.0.0.1, 203.0.113.7"]),
+                Some(IpAddr::from(FRONTEND))
+            ),
+            address(CLIENT)
+        );
+    }
+
+    #[test]
+    fn forwarded_addresses_are_walked_through_every_trusted_proxy() {
+        let rate_limiter = test_limiter();
+        assert_eq!(
+            rate_limiter.client_key(
+                &forwarded_for(&["192.0.2.66, 203.0.113.7", "10.0.0.2"]),
+                Some(IpAddr::from(LOAD_BALANCER))
+            ),
+            address(CLIENT)
+        );
+        assert_eq!(
+            rate_limiter.client_key(
+                &forwarded_for(&["2001:db8::7, 10.0.0.2"]),
+                Some(IpAddr::from(LOAD_BALANCER))
+            ),
+            ClientKey::Address("2001:db8::7".parse().unwrap())
+        );
+        // An address that can't be parsed stops the walk at the last trusted proxy
+        assert_eq!(
+            rate_limiter.client_key(
+                &forwarded_for(&["203.0.113.7, garbage"]),
+                Some(IpAddr::from(FRONTEND))
+            ),
+            address(FRONTEND)
+        );
+    }
+
+    /// Sends a request through the middleware, from the client address.
+    async fn limited_request(routes: &Router, method: Method) -> axum::response::Response {
+        let mut request = Request::builder()
+            .method(method)
+            .uri("/questions")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((CLIENT, 40000))));
+        routes.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn answers_carry_the_rate_limit_headers() {
+        let routes = Router::new()
+            .route("/questions", get(|| async { "[]" }).post(|| async { "{}" }))
+            .layer(middleware::from_fn_with_state(
+                Arc::new(test_limiter()),
+                enforce_rate_limit,
+            ));
+        let header = |response: &axum::response::Response, name: &str| {
+            response.headers()[name].to_str().unwrap().to_string()
+        };
+
+        let response = limited_request(&routes, Method::GET).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, "ratelimit-limit"), "3");
+        assert_eq!(header(&response, "ratelimit-remaining"), "2");
+        assert_eq!(header(&response, "ratelimit-reset"), "1");
+        assert_eq!(header(&response, "ratelimit-policy"), "3;w=3");
+        assert!(!response.headers().contains_key("retry-after"));
+
+        let response = limited_request(&routes, Method::POST).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, "ratelimit-policy"), "1;w=10");
+        let response = limited_request(&routes, Method::POST).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(header(&response, "retry-after"), "10");
+        assert_eq!(header(&response, "ratelimit-remaining"), "0");
+        assert_eq!(header(&response, "ratelimit-reset"), "10");
+    }
+}
@@ -1,31 +1,97 @@
-use crate::questions_database::questions_module::QuestionStructure;
+use crate::api_documentation::{NewQuestions, QuestionChanges};
+use crate::question_events::{event_stream, QuestionEvents};
+use crate::question_patch::{validate_question, QuestionPatch};
+use crate::questions_database::questions_module::{QuestionStructure, RenderedContent};
 use axum::{
-    extract::{Json, Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    body::Bytes,
+    extract::{
+        rejection::{BytesRejection, JsonRejection},
+        Json, Path, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
-use serde_json::{json, Value};
+use common::{
+    ErrorCode, ErrorResponse, ListParameters, MessageResponse, NewQuestion, PatchOperation,
+    QuestionEvent, SearchParameters, JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE,
+};
+use serde_json::Value;
 use sqlx::PgPool;
 use std::sync::Arc;
 
+/// Builds a JSON error answer whose HTTP status matches the error code.
+///
+/// # Arguments
+/// * `code` - The machine readable kind of the error
+/// * `message` - The human readable description of the error
+pub(crate) fn error_response(code: ErrorCode, message: &str) -> Response {
+    let status =
+        StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, Json(ErrorResponse::new(code, message))).into_response()
+}
+
+/// Builds the JSON error answer of a body that couldn't be read: `413` with `payload_too_large`
+/// when it exceeds the body limit, `400` with `invalid_input` otherwise.
+///
+/// # Arguments
+/// * `status` - The status Axum gives to the rejection
+/// * `message` - The description of the rejection
+pub(crate) fn body_rejection_response(status: StatusCode, message: &str) -> Response {
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        error_response(ErrorCode::PayloadTooLarge, message)
+    } else {
+        error_response(ErrorCode::InvalidInput, message)
+    }
+}
+
+/// Builds a JSON success answer with the given status.
+///
+/// # Arguments
+/// * `status` - The HTTP status of the answer
+/// * `message` - The success message
+fn message_response(status: StatusCode, message: &str) -> Response {
+    let success_message = MessageResponse {
+        message: message.to_string(),
+    };
+    (status, Json(success_message)).into_response()
+}
+
 /// Retrieves every question from the database.
 ///
 /// # Arguments
 /// * `database_pool` - A state where the database pool is located
 ///
 /// # Returns
-/// A list of every query in the database contained in a JSON answer
-pub async fn fetch_all_questions(State(database_pool): State<Arc<PgPool>>) -> impl IntoResponse {
+/// A list of every query in the database contained in a JSON answer, including the rendered HTML
+/// content, or an error message when the database failed
+pub async fn fetch_all_questions(State(database_pool): State<Arc<PgPool>>) -> Response {
     // Attempt to fetch all questions from the database
-    let all_questions = sqlx::query_as::<_, QuestionStructure>(
+    let query_result = sqlx::query_as::<_, QuestionStructure>(
         "SELECT * FROM questions_table ORDER BY question_id",
     )
     .fetch_all(&*database_pool)
-    .await
-    .expect("Error retrieving questions from the database");
+    .await;
+
+    match query_result {
+        Ok(all_questions) => {
+            // Render the Markdown content of every question into sanitized HTML.
+            let all_questions: Vec<QuestionStructure> = all_questions
+                .into_iter()
+                .map(QuestionStructure::with_rendered_content)
+                .collect();
 
-    // Respond with the list of all questions in JSON format
-    Json(all_questions)
+            // Respond with the list of all questions in JSON format
+            (StatusCode::OK, Json(all_questions)).into_response()
+        }
+        Err(error) => {
+            // The database may be down, e.g. while the server runs in degraded mode
+            tracing::error!(%error, "Couldn't retrieve the questions from the database");
+            error_response(ErrorCode::InternalError, "Internal server error")
+        }
+    }
 }
 
 /// Retrieves a question from the database using its ID.
@@ -36,6 +102,17 @@ pub async fn fetch_all_questions(State(database_pool): State<Arc<PgPool>>) -> im
 ///
 /// # Returns
 /// If the query is found, a JSON answer with it, or if it is not, an error message
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions/{id}",
+    tag = "questions",
+    params(("id" = i32, Path, description = "ID of the question")),
+    responses(
+        (status = 200, description = "The question", body = QuestionStructure),
+        (status = 404, description = "No question has this ID", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
 pub async fn get_question_by_id(
     Path(q_id): Path<i32>,
     State(database_pool): State<Arc<PgPool>>,
@@ -49,17 +126,111 @@ pub async fn get_question_by_id(
     .await;
 
     if let Ok(Some(question)) = query_result {
-        // If found, reply with the specifics of the question.
-        (StatusCode::OK, Json(question)).into_response()
+        // If found, reply with the specifics of the question and its rendered content.
+        (StatusCode::OK, Json(question.with_rendered_content())).into_response()
     } else if let Ok(None) = query_result {
         // If you cannot find the question ID, respond with an error.
-        let error_message =
-            json!({"error":"Question with this specific if not found or it doesn't exists!"});
-        (StatusCode::NOT_FOUND, Json(error_message)).into_response()
+        error_response(
+            ErrorCode::QuestionNotFound,
+            "Question with this specific if not found or it doesn't exists!",
+        )
     } else {
         // If there was an internal server error during retrieval, respond with an error.
-        let error_message = json!({"error": "Internal server error"});
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_message)).into_response()
+        error_response(ErrorCode::InternalError, "Internal server error")
+    }
+}
+
+/// Searches the questions whose title, content or types contain the text, or whose ID equals it.
+///
+/// # Arguments
+/// * `search_parameters` - The `q` query string parameter
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A JSON list of matching questions, or an error message
+pub async fn search_questions(
+    Query(search_parameters): Query<SearchParameters>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> impl IntoResponse {
+    find_matching_questions(&database_pool, &search_parameters.q).await
+}
+
+/// Lists every question, or only those matching the `q` query string parameter.
+///
+/// # Arguments
+/// * `list_parameters` - The optional `q` query string parameter
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A JSON list of questions ordered by ID, or an error message
+#[utoipa::path(
+    get,
+    path = "/api/v1/questions",
+    tag = "questions",
+    params(ListParameters),
+    responses(
+        (status = 200, description = "The questions, ordered by ID", body = [QuestionStructure]),
+        (status = 400, description = "The search text is empty", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn list_questions(
+    Query(list_parameters): Query<ListParameters>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> Response {
+    match list_parameters.q {
+        Some(search_text) => find_matching_questions(&database_pool, &search_text).await,
+        None => fetch_all_questions(State(database_pool)).await,
+    }
+}
+
+/// Finds the questions whose title, content or types contain the text, or whose ID equals it.
+///
+/// # Arguments
+/// * `database_pool` - A common reference to the connection pool for PostgresSQL.
+/// * `search_text` - The text to look for
+///
+/// # Returns
+/// A JSON list of matching questions, or an error message
+async fn find_matching_questions(database_pool: &PgPool, search_text: &str) -> Response {
+    let search_text = search_text.trim();
+    if search_text.is_empty() {
+        return error_response(ErrorCode::InvalidInput, "Search text must not be empty");
+    }
+
+    // Escape the LIKE wildcards so the text is matched literally.
+    let like_pattern = format!(
+        "%{}%",
+        search_text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+
+    // Attempt to fetch the matching questions from the database
+    let query_result = sqlx::query_as::<_, QuestionStructure>(
+        "SELECT * FROM questions_table \
+         WHERE question_title ILIKE $1 \
+            OR type_of_content ILIKE $1 \
+            OR EXISTS (SELECT 1 FROM unnest(type_of_question) AS question_type WHERE question_type ILIKE $1) \
+            OR question_id::text = $2 \
+         ORDER BY question_id",
+    )
+    .bind(&like_pattern)
+    .bind(search_text)
+    .fetch_all(database_pool)
+    .await;
+
+    if let Ok(matching_questions) = query_result {
+        // Render the Markdown content of every match into sanitized HTML.
+        let matching_questions: Vec<QuestionStructure> = matching_questions
+            .into_iter()
+            .map(QuestionStructure::with_rendered_content)
+            .collect();
+        (StatusCode::OK, Json(matching_questions)).into_response()
+    } else {
+        // If there was an internal server error during the search, respond with an error.
+        error_response(ErrorCode::InternalError, "Internal server error")
     }
 }
 
@@ -71,6 +242,17 @@ pub async fn get_question_by_id(
 ///
 /// # Returns
 /// When a question is removed, a success message appears; if the question cannot be located, an error message appears.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/questions/{id}",
+    tag = "questions",
+    params(("id" = i32, Path, description = "ID of the question")),
+    responses(
+        (status = 200, description = "The question was deleted", body = MessageResponse),
+        (status = 404, description = "No question has this ID", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
 pub async fn delete_question(
     Path(q_id): Path<i32>,
     State(database_pool): State<Arc<PgPool>>,
@@ -85,18 +267,17 @@ pub async fn delete_question(
         // Verify whether any rows were impacted—that is, whether the question was located and removed.
         if question_deleted.rows_affected() > 0 {
             // If the question was eliminated, successfully respond to it.
-            let success_message = json!({"message":"Question deleted successfully"});
-            (StatusCode::OK, Json(success_message)).into_response()
+            message_response(StatusCode::OK, "Question deleted successfully")
         } else {
             // If you cannot find the question ID, respond with an error.
-            let error_message =
-                json!({"error":"Question with this specific if not found or it doesn't exists!"});
-            (StatusCode::NOT_FOUND, Json(error_message)).into_response()
+            error_response(
+                ErrorCode::QuestionNotFound,
+                "Question with this specific if not found or it doesn't exists!",
+            )
         }
     } else {
         // If there was an internal server error during deletion, respond with an error.
-        let error_message = json!({"error": "Internal server error"});
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_message)).into_response()
+        error_response(ErrorCode::InternalError, "Internal server error")
     }
 }
 
@@ -105,39 +286,58 @@ pub async fn delete_question(
 ///
 /// # Arguments
 /// * `State(database_pool)` - A shared reference to the connection pool of PostgresSQL databases.
-/// * `Json(input)` - The JSON payload with the additional question or questions.
+/// * `input` - The JSON payload with the additional question or questions, or the reason it couldn't be read.
 ///
 /// # Returns
 /// * `StatusCode::CREATED` accompanied, if the questions are successfully added, by a success message.
 /// * `StatusCode::BAD_REQUEST` when the input format is deemed invalid, in addition to an error message.
+/// * `StatusCode::PAYLOAD_TOO_LARGE` when the payload exceeds the body size limit.
 /// * If there are problems with the database insertion, provide appropriate error answers.
+#[utoipa::path(
+    post,
+    path = "/api/v1/questions",
+    tag = "questions",
+    request_body = NewQuestions,
+    responses(
+        (status = 201, description = "The questions were added", body = MessageResponse),
+        (status = 400, description = "A question is missing fields", body = ErrorResponse),
+        (status = 413, description = "The body exceeds the size limit", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
 pub async fn add_questions(
     State(database_pool): State<Arc<PgPool>>,
-    Json(input): Json<Value>,
+    input: Result<Json<Value>, JsonRejection>,
 ) -> impl IntoResponse {
+    let input = match input {
+        Ok(Json(input)) => input,
+        Err(rejection) => {
+            return body_rejection_response(rejection.status(), &rejection.body_text())
+        }
+    };
     // Verify whether the input is a series of questions.
     if let Some(questions) = input.as_array() {
         // Repeat for every question in the array, then add it to the database.
         for question in questions {
             if let Err(error) = insert_question(&database_pool, question).await {
-                return error.into_response();
+                return error;
             }
         }
         // Successfully respond if all the questions are included.
-        let success_message = json!({"message": "All questions added successfully"});
-        (StatusCode::CREATED, Json(success_message)).into_response()
+        message_response(StatusCode::CREATED, "All questions added successfully")
     } else if input.is_object() {
         // Respond to a single input question
         if let Err(error) = insert_question(&database_pool, &input).await {
-            return error.into_response();
+            return error;
         }
         // Send a success message in response to a single query.
-        let success_message = json!({"message": "Question added successfully"});
-        (StatusCode::CREATED, Json(success_message)).into_response()
+        message_response(StatusCode::CREATED, "Question added successfully")
     } else {
         // If the question is added, successfully respond to it.
-        let error_message = json!({"error": "Invalid input format. Expected a single object or an array of objects."});
-        (StatusCode::BAD_REQUEST, Json(error_message)).into_response()
+        error_response(
+            ErrorCode::InvalidInput,
+            "Invalid input format. Expected a single object or an array of objects.",
+        )
     }
 }
 
@@ -150,11 +350,8 @@ pub async fn add_questions(
 ///
 /// # Returns
 /// * `Ok(())` whether the question is correctly inserted.
-/// * If there are problems with validation or insertion, a JSON error message and a tuple with {StatusCode` are contained in `Err`.
-async fn insert_question(
-    database_pool: &Arc<PgPool>,
-    question: &Value,
-) -> Result<(), (StatusCode, Json<Value>)> {
+/// * If there are problems with validation or insertion, the JSON error answer is contained in `Err`.
+async fn insert_question(database_pool: &Arc<PgPool>, question: &Value) -> Result<(), Response> {
     // Extract and verify the fields that are necessary from the inquiry.
     let question_title = question
         .get("question_title")
@@ -188,99 +385,276 @@ async fn insert_question(
 
         // Respond to any insertion errors.
         if insert_result.is_err() {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": "Internal server error during insertion of question"})),
+            return Err(error_response(
+                ErrorCode::InternalError,
+                "Internal server error during insertion of question",
             ));
         }
         Ok(())
     } else {
         // If any mandatory fields are missing or incorrect, respond with an error.
-        Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({"error": "Invalid input for a question. Must be missing inputs"})),
+        Err(error_response(
+            ErrorCode::InvalidInput,
+            "Invalid input for a question. Must be missing inputs",
         ))
     }
 }
 
-/// Updates a question in the database.
+/// Replaces every field of a question in the database.
+///
+/// The body must be a complete question: every field present with the right type, a title
+/// that is not blank, and no unknown fields.
+///
+/// # Arguments
+/// * `q_id` - The question's ID to replace
+/// * `database_pool` - A state that contains the database connection pool
+/// * `replacement` - The complete new question, or the reason it isn't valid JSON
+///
+/// # Returns
+/// A success message, or an error message if the body is invalid, the question does not exist or the update fails.
+#[utoipa::path(
+    put,
+    path = "/api/v1/questions/{id}",
+    tag = "questions",
+    params(("id" = i32, Path, description = "ID of the question")),
+    request_body = NewQuestion,
+    responses(
+        (status = 200, description = "The question was replaced", body = MessageResponse),
+        (status = 400, description = "The body is not a complete, valid question", body = ErrorResponse),
+        (status = 404, description = "No question has this ID", body = ErrorResponse),
+        (status = 413, description = "The body exceeds the size limit", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn replace_question(
+    Path(q_id): Path<i32>,
+    State(database_pool): State<Arc<PgPool>>,
+    replacement: Result<Json<Value>, JsonRejection>,
+) -> impl IntoResponse {
+    let replacement = match replacement {
+        Ok(Json(replacement)) => replacement,
+        Err(rejection) => {
+            return body_rejection_response(rejection.status(), &rejection.body_text())
+        }
+    };
+    let replacement = match validate_question(&replacement) {
+        Ok(replacement) => replacement,
+        Err(message) => return error_response(ErrorCode::InvalidInput, &message),
+    };
+
+    let replace_result = sqlx::query(
+        "UPDATE questions_table SET question_title = $1, type_of_content = $2, type_of_question = $3 WHERE question_id = $4",
+    )
+    .bind(&replacement.question_title)
+    .bind(&replacement.type_of_content)
+    .bind(&replacement.type_of_question)
+    .bind(q_id)
+    .execute(&*database_pool)
+    .await;
+
+    match replace_result {
+        Ok(replaced) if replaced.rows_affected() > 0 => {
+            message_response(StatusCode::OK, "Question replaced successfully")
+        }
+        Ok(_) => error_response(
+            ErrorCode::QuestionNotFound,
+            "Question with this specific ID not found or it doesn't exist!",
+        ),
+        Err(_) => error_response(
+            ErrorCode::InternalError,
+            "Internal server error during replacement of question",
+        ),
+    }
+}
+
+/// Reads the patch of a PATCH request according to its `Content-Type`.
+///
+/// `application/merge-patch+json` is an RFC 7396 JSON Merge Patch and `application/json-patch+json`
+/// an RFC 6902 JSON Patch. Plain `application/json`, or no `Content-Type`, is read as a merge patch
+/// so that existing clients keep working.
+///
+/// # Arguments
+/// * `headers` - The headers of the request
+/// * `body` - The raw body of the request
+///
+/// # Returns
+/// The patch, or the error code and message explaining why it can't be read.
+fn read_question_patch(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<QuestionPatch, (ErrorCode, String)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+
+    match content_type.as_deref() {
+        None | Some("application/json") | Some(MERGE_PATCH_CONTENT_TYPE) => {
+            serde_json::from_slice(body)
+                .map(QuestionPatch::Merge)
+                .map_err(|error| {
+                    (
+                        ErrorCode::InvalidPatch,
+                        format!("Invalid merge patch: {}", error),
+                    )
+                })
+        }
+        Some(JSON_PATCH_CONTENT_TYPE) => serde_json::from_slice(body)
+            .map(QuestionPatch::Json)
+            .map_err(|error| {
+                (
+                    ErrorCode::InvalidPatch,
+                    format!("Invalid JSON Patch: {}", error),
+                )
+            }),
+        Some(other) => Err((
+            ErrorCode::UnsupportedMediaType,
+            format!(
+                "Unsupported Content-Type `{}`, expected `{}` or `{}`",
+                other, MERGE_PATCH_CONTENT_TYPE, JSON_PATCH_CONTENT_TYPE
+            ),
+        )),
+    }
+}
+
+/// Updates some fields of a question in the database with a merge patch or a JSON Patch.
+///
+/// The question is locked while the patch is applied, so concurrent patches and the `test`
+/// operations of a JSON Patch always see the latest version.
 ///
 /// # Arguments
 /// * `q_id` - The question's ID to update
 /// * `database_pool` - A state that contains the database connection pool
-/// * `Json(payload)` - A JSON payload with the most recent question details
+/// * `headers` - The headers of the request, whose `Content-Type` selects the kind of patch
+/// * `body` - The patch, or the reason it couldn't be read
 ///
 /// # Returns
-/// An error message will appear if the update is unsuccessful; otherwise, a success message.
+/// An error message will appear if the patch is invalid or the update is unsuccessful; otherwise, a success message.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/questions/{id}",
+    tag = "questions",
+    params(("id" = i32, Path, description = "ID of the question")),
+    request_body(
+        description = "An RFC 7396 merge patch, where `null` clears a field, or an RFC 6902 JSON Patch",
+        content(
+            (QuestionChanges = "application/merge-patch+json"),
+            (Vec<PatchOperation> = "application/json-patch+json"),
+            (QuestionChanges = "application/json")
+        )
+    ),
+    responses(
+        (status = 200, description = "The question was updated", body = MessageResponse),
+        (status = 400, description = "The patch is invalid or leaves an invalid question", body = ErrorResponse),
+        (status = 404, description = "No question has this ID", body = ErrorResponse),
+        (status = 413, description = "The body exceeds the size limit", body = ErrorResponse),
+        (status = 415, description = "The Content-Type is not a supported patch format", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
 pub async fn update_question(
     Path(q_id): Path<i32>,
     State(database_pool): State<Arc<PgPool>>,
-    Json(payload): Json<Value>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
 ) -> impl IntoResponse {
-    // Retrieve the existing question from the database
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) => {
+            return body_rejection_response(rejection.status(), &rejection.body_text())
+        }
+    };
+    let question_patch = match read_question_patch(&headers, &body) {
+        Ok(question_patch) => question_patch,
+        Err((code, message)) => return error_response(code, &message),
+    };
+
+    let Ok(mut transaction) = database_pool.begin().await else {
+        return error_response(ErrorCode::InternalError, "Internal server error");
+    };
+    // Retrieve the existing question from the database, locking it until the update is committed
     let select_query = sqlx::query_as::<_, QuestionStructure>(
-        "SELECT * FROM questions_table WHERE question_id = $1",
+        "SELECT * FROM questions_table WHERE question_id = $1 FOR UPDATE",
     )
     .bind(q_id)
-    .fetch_optional(&*database_pool)
+    .fetch_optional(&mut *transaction)
     .await;
-    // Verify whether the question was properly obtained.
-    if let Ok(question) = select_query {
-        // Check if the question exists
-        if let Some(mut existing_question) = question {
-            // If available, update the question fields with the payload data.
-            if let Some(question_title) = payload
-                .get("question_title")
-                .and_then(|value| value.as_str())
-            {
-                existing_question.question_title = question_title.to_string();
-            }
-            if let Some(type_of_content) = payload
-                .get("type_of_content")
-                .and_then(|value| value.as_str())
-            {
-                existing_question.type_of_content = type_of_content.to_string();
-            }
-            if let Some(type_of_question) = payload
-                .get("type_of_question")
-                .and_then(|value| value.as_array())
-            {
-                existing_question.type_of_question = type_of_question
-                    .iter()
-                    .filter_map(|t| t.as_str().map(String::from))
-                    .collect();
-            }
-
-            // Update the question in the database
-            let update_query = sqlx::query(
-                "UPDATE questions_table SET question_title = $1, type_of_content = $2, type_of_question = $3 WHERE question_id = $4",
+    let existing_question = match select_query {
+        Ok(Some(existing_question)) => existing_question,
+        Ok(None) => {
+            return error_response(
+                ErrorCode::QuestionNotFound,
+                "Question with this specific ID not found or it doesn't exist!",
             )
-                .bind(&existing_question.question_title)
-                .bind(&existing_question.type_of_content)
-                .bind(&existing_question.type_of_question)
-                .bind(q_id)
-                .execute(&*database_pool)
-                .await;
-
-            // Verify if the query update was successful.
-            if update_query.is_ok() {
-                let success_message = json!({"message": "Question updated successfully"});
-                (StatusCode::OK, Json(success_message)).into_response()
-            } else {
-                // Take action if the update query is unsuccessful.
-                let error_message =
-                    json!({"error": "Internal server error during update of question"});
-                (StatusCode::INTERNAL_SERVER_ERROR, Json(error_message)).into_response()
-            }
-        } else {
-            // Address the situation in which there is no question
-            let error_message =
-                json!({"error":"Question with this specific ID not found or it doesn't exist!"});
-            (StatusCode::NOT_FOUND, Json(error_message)).into_response()
         }
+        Err(_) => return error_response(ErrorCode::InternalError, "Internal server error"),
+    };
+
+    let existing_question = NewQuestion {
+        question_title: existing_question.question_title,
+        type_of_content: existing_question.type_of_content,
+        type_of_question: existing_question.type_of_question,
+    };
+    let patched_question = match question_patch.apply(&existing_question) {
+        Ok(patched_question) => patched_question,
+        Err(message) => return error_response(ErrorCode::InvalidPatch, &message),
+    };
+
+    // Update the question in the database
+    let update_query = sqlx::query(
+        "UPDATE questions_table SET question_title = $1, type_of_content = $2, type_of_question = $3 WHERE question_id = $4",
+    )
+    .bind(&patched_question.question_title)
+    .bind(&patched_question.type_of_content)
+    .bind(&patched_question.type_of_question)
+    .bind(q_id)
+    .execute(&mut *transaction)
+    .await;
+
+    if update_query.is_ok() && transaction.commit().await.is_ok() {
+        message_response(StatusCode::OK, "Question updated successfully")
     } else {
-        // Address the situation where the question cannot be retrieved.
-        let error_message = json!({"error": "Internal server error"});
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_message)).into_response()
+        // Take action if the update query is unsuccessful.
+        error_response(
+            ErrorCode::InternalError,
+            "Internal server error during update of question",
+        )
     }
 }
+
+/// Streams the changes of the questions as server-sent events named `created`, `updated` and
+/// `deleted`, each with an ID and a `QuestionEvent` as JSON data.
+///
+/// A client reconnecting with `Last-Event-ID` first gets the events it missed. When they are
+/// no longer kept, it gets a `resync` event instead and should fetch the questions again.
+///
+/// # Arguments
+/// * `question_events` - The broadcaster of the changes
+/// * `headers` - The headers of the request, with the `Last-Event-ID` of a reconnecting client
+///
+/// # Returns
+/// The event stream, with a comment sent regularly to keep the connection open.
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "questions",
+    params(
+        ("Last-Event-ID" = Option<String>, Header, description = "ID of the last event received before reconnecting")
+    ),
+    responses(
+        (status = 200, description = "The stream of question events", content_type = "text/event-stream", body = QuestionEvent)
+    )
+)]
+pub async fn stream_question_events(
+    State(question_events): State<Arc<QuestionEvents>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    Sse::new(event_stream(question_events.subscribe(last_event_id)))
+        .keep_alive(KeepAlive::default())
+}
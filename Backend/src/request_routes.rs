@@ -1,30 +1,266 @@
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    routing::{delete, get, patch, post},
+    extract::{FromRef, Request},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::{self, Next},
+    routing::{delete, get, patch, post, MethodRouter},
     Router,
 };
+use common::route_paths::{
+    ADD_QUESTION_ROUTE, DELETE_QUESTION_ROUTE, EVENTS_ROUTE, GET_ALL_QUESTIONS_ROUTE,
+    GET_QUESTION_BY_ID_ROUTE, QUESTIONS_ROUTE, QUESTION_ROUTE, SEARCH_QUESTIONS_ROUTE,
+    UPDATE_QUESTION_ROUTE,
+};
+use common::{API_KEY_HEADER, REQUEST_ID_HEADER};
 use sqlx::PgPool;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use utoipa_swagger_ui::SwaggerUi;
 
+use crate::api_documentation::{ApiDocumentation, DOCUMENTATION_UI_ROUTE, OPENAPI_DOCUMENT_ROUTE};
+use crate::question_events::QuestionEvents;
 use crate::request_handlers::{
-    add_questions, delete_question, fetch_all_questions, get_question_by_id, update_question,
+    add_questions, delete_question, fetch_all_questions, get_question_by_id, list_questions,
+    replace_question, search_questions, stream_question_events, update_question,
 };
+use crate::webhooks::webhook_routes;
+
+/// Value of the `Deprecation` header of the legacy routes: the date they were deprecated,
+/// 2026-10-18, as an RFC 9745 Unix timestamp.
+const LEGACY_DEPRECATION_DATE: &str = "@1792281600";
+
+/// How long browsers may cache the answer to a CORS preflight request.
+const CORS_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// The state of the API handlers, each extracting the parts it needs.
+#[derive(Clone)]
+pub struct ApiState {
+    /// The pool of database connections.
+    pub database_pool: Arc<PgPool>,
+    /// The broadcaster of the changes of the questions.
+    pub question_events: Arc<QuestionEvents>,
+}
+
+impl FromRef<ApiState> for Arc<PgPool> {
+    fn from_ref(api_state: &ApiState) -> Self {
+        api_state.database_pool.clone()
+    }
+}
+
+impl FromRef<ApiState> for Arc<QuestionEvents> {
+    fn from_ref(api_state: &ApiState) -> Self {
+        api_state.question_events.clone()
+    }
+}
+
+/// A legacy RPC-style route kept as an alias of a versioned resource route.
+pub struct LegacyAlias {
+    /// The deprecated route.
+    pub legacy_route: &'static str,
+    /// The HTTP method of both routes.
+    pub method: Method,
+    /// The route that replaces it.
+    pub successor_route: &'static str,
+}
+
+/// Every legacy route with its successor.
+pub const LEGACY_ALIASES: [LegacyAlias; 6] = [
+    LegacyAlias {
+        legacy_route: GET_ALL_QUESTIONS_ROUTE,
+        method: Method::GET,
+        successor_route: QUESTIONS_ROUTE,
+    },
+    LegacyAlias {
+        legacy_route: GET_QUESTION_BY_ID_ROUTE,
+        method: Method::GET,
+        successor_route: QUESTION_ROUTE,
+    },
+    LegacyAlias {
+        legacy_route: SEARCH_QUESTIONS_ROUTE,
+        method: Method::GET,
+        successor_route: QUESTIONS_ROUTE,
+    },
+    LegacyAlias {
+        legacy_route: DELETE_QUESTION_ROUTE,
+        method: Method::DELETE,
+        successor_route: QUESTION_ROUTE,
+    },
+    LegacyAlias {
+        legacy_route: ADD_QUESTION_ROUTE,
+        method: Method::POST,
+        successor_route: QUESTIONS_ROUTE,
+    },
+    LegacyAlias {
+        legacy_route: UPDATE_QUESTION_ROUTE,
+        method: Method::PATCH,
+        successor_route: QUESTION_ROUTE,
+    },
+];
+
+/// Marks the answers of a legacy route as deprecated, with a link to its successor.
+///
+/// # Arguments
+///
+/// * `method_router` - The handler of the legacy route
+/// * `successor_route` - The versioned route that replaces it
+fn deprecated(
+    method_router: MethodRouter<ApiState>,
+    successor_route: &'static str,
+) -> MethodRouter<ApiState> {
+    method_router.layer(middleware::from_fn(
+        move |request: Request, next: Next| async move {
+            // The ID, when there is one, is the last segment of every legacy route
+            let question_id = request.uri().path().rsplit('/').next().unwrap_or_default();
+            let mut successor_url = successor_route.replace(":id", question_id);
+            if let Some(query) = request.uri().query() {
+                successor_url = format!("{}?{}", successor_url, query);
+            }
+
+            let mut response = next.run(request).await;
+            let headers = response.headers_mut();
+            headers.insert(
+                "deprecation",
+                HeaderValue::from_static(LEGACY_DEPRECATION_DATE),
+            );
+            if let Ok(link) =
+                HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor_url))
+            {
+                headers.insert("link", link);
+            }
+            response
+        },
+    ))
+}
+
+/// Lets the web pages of the allowed origins call the API from a browser.
+///
+/// The request ID, rate limit and deprecation headers are exposed to the pages, which can
+/// send the request ID and an API key.
+///
+/// # Arguments
+///
+/// * `allowed_origins` - Origins already validated by the configuration, or `*` for any
+///
+/// # Returns
+///
+/// The CORS layer, or `None` when no origin is allowed.
+pub fn cors_layer(allowed_origins: &[String]) -> Option<CorsLayer> {
+    let allow_origin = match allowed_origins {
+        [] => return None,
+        [any_origin] if any_origin == "*" => AllowOrigin::any(),
+        origins => AllowOrigin::list(
+            origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        ),
+    };
+    Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ])
+            .allow_headers([
+                header::CONTENT_TYPE,
+                HeaderName::from_static(REQUEST_ID_HEADER),
+                HeaderName::from_static(API_KEY_HEADER),
+            ])
+            .expose_headers([
+                HeaderName::from_static(REQUEST_ID_HEADER),
+                HeaderName::from_static("ratelimit-limit"),
+                HeaderName::from_static("ratelimit-remaining"),
+                HeaderName::from_static("ratelimit-reset"),
+                HeaderName::from_static("ratelimit-policy"),
+                header::RETRY_AFTER,
+                HeaderName::from_static("deprecation"),
+                header::LINK,
+            ])
+            .max_age(CORS_MAX_AGE),
+    )
+}
+
+/// Compresses the answers with gzip, Brotli or zstd, as negotiated by `Accept-Encoding`.
+///
+/// Images and event streams are left alone, as are answers shorter than the threshold,
+/// which compression would barely shrink.
+///
+/// # Arguments
+///
+/// * `min_bytes` - The smallest answer compressed
+pub fn compression_layer(min_bytes: u16) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(
+        SizeAbove::new(min_bytes)
+            .and(NotForContentType::IMAGES)
+            .and(NotForContentType::SSE),
+    )
+}
 
 /// Sets up the routes for the application
 ///
 /// # Arguments
 ///
 /// * `database_pool` - A PostgresSQL connection pool wrapped with Arc
+/// * `question_events` - The broadcaster of the changes of the questions
+/// * `webhook_admin_keys` - The API keys allowed to manage the webhooks
 ///
 /// # Returns
 ///
-/// Axum {Router} configured with the routes mentioned
-pub fn setup_routes(database_pool: Arc<PgPool>) -> Router {
+/// Axum {Router} configured with the routes mentioned, the OpenAPI document and its documentation UI
+pub fn setup_routes(
+    database_pool: Arc<PgPool>,
+    question_events: Arc<QuestionEvents>,
+    webhook_admin_keys: Arc<HashSet<String>>,
+) -> Router {
     Router::new()
-        .route("/getAllQuestions", get(fetch_all_questions)) // Route to fetch all questions
-        .route("/getQuestionByID/:id", get(get_question_by_id)) // Route to fetch a question by its ID
-        .route("/deleteQuestion/:id", delete(delete_question)) // Route to delete a question by its ID
-        .route("/addQuestion", post(add_questions)) // Route to add a new question
-        .route("/updateQuestion/:id", patch(update_question)) // Route to update a question by its ID
-        .with_state(database_pool)
+        .route(QUESTIONS_ROUTE, get(list_questions).post(add_questions)) // Routes to list, search and add questions
+        .route(
+            QUESTION_ROUTE,
+            get(get_question_by_id)
+                .put(replace_question)
+                .patch(update_question)
+                .delete(delete_question),
+        ) // Routes to fetch, replace, update and delete a question by its ID
+        .route(
+            GET_ALL_QUESTIONS_ROUTE,
+            deprecated(get(fetch_all_questions), QUESTIONS_ROUTE),
+        ) // Legacy route to fetch all questions
+        .route(
+            GET_QUESTION_BY_ID_ROUTE,
+            deprecated(get(get_question_by_id), QUESTION_ROUTE),
+        ) // Legacy route to fetch a question by its ID
+        .route(
+            SEARCH_QUESTIONS_ROUTE,
+            deprecated(get(search_questions), QUESTIONS_ROUTE),
+        ) // Legacy route to search questions by text
+        .route(
+            DELETE_QUESTION_ROUTE,
+            deprecated(delete(delete_question), QUESTION_ROUTE),
+        ) // Legacy route to delete a question by its ID
+        .route(
+            ADD_QUESTION_ROUTE,
+            deprecated(post(add_questions), QUESTIONS_ROUTE),
+        ) // Legacy route to add a new question
+        .route(
+            UPDATE_QUESTION_ROUTE,
+            deprecated(patch(update_question), QUESTION_ROUTE),
+        ) // Legacy route to update a question by its ID
+        .route(EVENTS_ROUTE, get(stream_question_events)) // Route streaming the changes of the questions
+        .merge(webhook_routes(webhook_admin_keys)) // Admin routes managing the webhooks and their deliveries
+        .with_state(ApiState {
+            database_pool,
+            question_events,
+        })
+        .merge(
+            SwaggerUi::new(DOCUMENTATION_UI_ROUTE)
+                .url(OPENAPI_DOCUMENT_ROUTE, ApiDocumentation::document()),
+        ) // Routes serving the OpenAPI document and the documentation UI
 }
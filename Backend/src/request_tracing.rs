@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use axum::{extract::Request, http::HeaderName, response::Response, Router};
+use common::REQUEST_ID_HEADER;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use tracing::{Level, Span};
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use crate::configuration::LogFormat;
+use crate::metrics::{QueryMetricsLayer, SQL_QUERY_TARGET};
+
+/// Installs the global subscriber that writes the structured logs to the standard output
+/// and feeds the SQL statement durations to the metrics.
+///
+/// # Arguments
+/// * `log_format` - Human readable lines or one JSON object per line
+/// * `log_filter` - Which logs are written, already validated by the configuration
+/// * `query_metrics_layer` - The layer recording the SQL statement durations
+pub fn initialize_logging(
+    log_format: LogFormat,
+    log_filter: &str,
+    query_metrics_layer: QueryMetricsLayer,
+) {
+    let log_layer = match log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .boxed(),
+    };
+    // Each layer has its own filter, so the statement events reach the metrics
+    // even when the log filter leaves them out
+    tracing_subscriber::registry()
+        .with(log_layer.with_filter(EnvFilter::new(log_filter)))
+        .with(
+            query_metrics_layer
+                .with_filter(Targets::new().with_target(SQL_QUERY_TARGET, Level::DEBUG)),
+        )
+        .init();
+}
+
+/// Wraps the routes in a span per request, logged with its status and latency once answered.
+///
+/// The request ID sent by the Frontend in `x-request-id` is kept, or a new one is generated,
+/// recorded in the span, so the SQL statements logged inside it carry it too, and sent back.
+///
+/// # Arguments
+/// * `routes` - The routes of the application
+///
+/// # Returns
+/// Axum {Router} with the tracing layers added
+pub fn with_request_tracing(routes: Router) -> Router {
+    let request_id_header = HeaderName::from_static(REQUEST_ID_HEADER);
+    let trace_layer = TraceLayer::new_for_http()
+        .make_span_with(|request: &Request| {
+            let request_id = request
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                path = %request.uri().path(),
+                request_id = %request_id,
+            )
+        })
+        .on_request(())
+        .on_response(|response: &Response, latency: Duration, _span: &Span| {
+            let status = response.status().as_u16();
+            let latency_ms = latency.as_secs_f64() * 1000.0;
+            if response.status().is_server_error() {
+                tracing::error!(status, latency_ms, "Request failed");
+            } else {
+                tracing::info!(status, latency_ms, "Request answered");
+            }
+        })
+        .on_failure(());
+
+    // The last layer added runs first, so the request ID is set before the span is created
+    routes
+        .layer(trace_layer)
+        .layer(PropagateRequestIdLayer::new(request_id_header.clone()))
+        .layer(SetRequestIdLayer::new(request_id_header, MakeRequestUuid))
+}
@@ -0,0 +1,212 @@
+use std::future::{Future, IntoFuture};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+    Router,
+};
+use common::tls::ReloadingTlsConfig;
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::question_events::QuestionEvents;
+use crate::tls_listener::serve_tls;
+
+/// Longest time to wait for the connections of the database pool to be returned and closed.
+const POOL_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Counts the requests being answered, so that the shutdown can tell how many it drained.
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    active: AtomicUsize,
+    served: AtomicU64,
+}
+
+impl InFlightRequests {
+    /// The number of requests being answered.
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// The number of requests answered since the server started.
+    pub fn served(&self) -> u64 {
+        self.served.load(Ordering::Relaxed)
+    }
+}
+
+/// Decrements the active requests when the request is answered or abandoned.
+struct InFlightGuard(Arc<InFlightRequests>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::Relaxed);
+        self.0.served.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Middleware counting the request among the active ones until it is answered.
+///
+/// # Arguments
+/// * `in_flight_requests` - The shared counters
+/// * `request` - The incoming request
+/// * `next` - The rest of the middleware stack and the handler
+pub async fn track_in_flight_requests(
+    State(in_flight_requests): State<Arc<InFlightRequests>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    in_flight_requests.active.fetch_add(1, Ordering::Relaxed);
+    let _guard = InFlightGuard(in_flight_requests);
+    next.run(request).await
+}
+
+/// What happened while the server was shutting down.
+#[derive(Debug)]
+pub struct ShutdownSummary {
+    /// The signal that started the shutdown.
+    pub signal: &'static str,
+    /// Time between the signal and the end of the draining.
+    pub drain_duration: Duration,
+    /// Requests still being answered when the deadline passed, or a second signal came.
+    pub abandoned_requests: usize,
+    /// Requests answered since the server started.
+    pub served_requests: u64,
+}
+
+/// Serves the routes until SIGTERM or SIGINT, then stops accepting connections and waits for
+/// the requests being answered, at most for the drain timeout.
+///
+/// The event streams are ended when the draining starts, as they would otherwise never finish.
+/// A second signal while draining stops waiting at once.
+///
+/// # Arguments
+/// * `tcp_listener` - The listener bound to the configured address
+/// * `routes` - The routes of the application, tracked by `track_in_flight_requests`
+/// * `in_flight_requests` - The counters of the tracked requests
+/// * `drain_timeout` - Longest time to wait for the requests being answered
+/// * `tls_config` - The certificate to serve HTTPS with, or `None` for plain HTTP
+/// * `question_events` - The broadcaster whose event streams are ended
+///
+/// # Returns
+/// The summary of the shutdown, or the error that stopped the server.
+pub async fn serve_with_graceful_shutdown(
+    tcp_listener: TcpListener,
+    routes: Router,
+    in_flight_requests: Arc<InFlightRequests>,
+    drain_timeout: Duration,
+    tls_config: Option<Arc<ReloadingTlsConfig>>,
+    question_events: Arc<QuestionEvents>,
+) -> std::io::Result<ShutdownSummary> {
+    let (shutdown_sender, mut shutdown_receiver) = oneshot::channel();
+    let draining_requests = in_flight_requests.clone();
+    let shutdown_signal = async move {
+        let signal = wait_for_shutdown_signal().await;
+        tracing::info!(
+            signal,
+            active_requests = draining_requests.active(),
+            drain_timeout_secs = drain_timeout.as_secs(),
+            "Shutdown requested, draining the requests being answered"
+        );
+        question_events.close();
+        let _ = shutdown_sender.send((signal, Instant::now()));
+    };
+    let mut server: Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>> = match tls_config {
+        Some(tls_config) => Box::pin(serve_tls(tcp_listener, routes, tls_config, shutdown_signal)),
+        // The address of each connection is kept for the rate limiter
+        None => Box::pin(
+            axum::serve(
+                tcp_listener,
+                routes.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_signal)
+            .into_future(),
+        ),
+    };
+
+    let ((signal, shutdown_started_at), drained) = tokio::select! {
+        served = &mut server => {
+            served?;
+            // With no connection left, the server may stop as soon as the signal is sent
+            match shutdown_receiver.try_recv() {
+                Ok(shutdown_started) => (shutdown_started, true),
+                Err(_) => return Err(std::io::Error::other("The server stopped without a shutdown signal")),
+            }
+        }
+        Ok(shutdown_started) = &mut shutdown_receiver => (shutdown_started, false),
+    };
+    if !drained {
+        tokio::select! {
+            served = &mut server => served?,
+            _ = tokio::time::sleep(drain_timeout) => {
+                tracing::warn!("Drain timeout reached, abandoning the requests being answered");
+            }
+            _ = wait_for_shutdown_signal() => {
+                tracing::warn!("Second shutdown signal, abandoning the requests being answered");
+            }
+        }
+    }
+    Ok(ShutdownSummary {
+        signal,
+        drain_duration: shutdown_started_at.elapsed(),
+        abandoned_requests: in_flight_requests.active(),
+        served_requests: in_flight_requests.served(),
+    })
+}
+
+/// Closes the connections of the database pool, waiting for those still in use.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+///
+/// # Returns
+/// Whether every connection was closed before the timeout, which fails when an abandoned
+/// request still holds one.
+pub async fn close_database_pool(database_pool: &PgPool) -> bool {
+    tokio::time::timeout(POOL_CLOSE_TIMEOUT, database_pool.close())
+        .await
+        .is_ok()
+}
+
+/// Waits for SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+///
+/// # Returns
+/// The name of the signal received.
+async fn wait_for_shutdown_signal() -> &'static str {
+    let interrupt = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "SIGINT",
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't listen for SIGINT");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate_signal) => {
+                terminate_signal.recv().await;
+                "SIGTERM"
+            }
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't listen for SIGTERM");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<&'static str>();
+
+    tokio::select! {
+        signal = interrupt => signal,
+        signal = terminate => signal,
+    }
+}
@@ -0,0 +1,131 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use common::tls::{https_redirect_location, ReloadingTlsConfig};
+use common::ErrorCode;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+use crate::request_handlers::error_response;
+
+/// Longest time a client may take to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, e.g. when the process ran out of file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// Serves the routes over HTTPS until the shutdown signal completes, then stops accepting
+/// connections and waits for those open to finish their requests.
+///
+/// Each connection is accepted with the TLS configuration current at that time, so a
+/// reloaded certificate applies to the new connections.
+///
+/// # Arguments
+/// * `tcp_listener` - The listener bound to the configured address
+/// * `routes` - The routes of the application
+/// * `tls_config` - The certificate and, for mutual TLS, the client certificate authorities
+/// * `shutdown_signal` - Completes when the server must stop accepting connections
+pub async fn serve_tls(
+    tcp_listener: TcpListener,
+    routes: Router,
+    tls_config: Arc<ReloadingTlsConfig>,
+    shutdown_signal: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let graceful_shutdown = GracefulShutdown::new();
+    tokio::pin!(shutdown_signal);
+    loop {
+        let (tcp_stream, peer_address) = tokio::select! {
+            accepted = tcp_listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(error) => {
+                    tracing::warn!(%error, "Couldn't accept a connection");
+                    tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            },
+            _ = &mut shutdown_signal => break,
+        };
+        let tls_acceptor = tls_config.acceptor();
+        let routes = routes.clone();
+        let connection_watcher = graceful_shutdown.watcher();
+        tokio::spawn(async move {
+            let tls_stream =
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(tcp_stream))
+                    .await
+                {
+                    Ok(Ok(tls_stream)) => tls_stream,
+                    Ok(Err(error)) => {
+                        tracing::debug!(%peer_address, %error, "TLS handshake failed");
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!(%peer_address, "TLS handshake timed out");
+                        return;
+                    }
+                };
+            // The address of the connection is kept for the rate limiter, as with plain HTTP
+            let service = hyper::service::service_fn(move |mut request: Request<Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(peer_address));
+                routes.clone().oneshot(request)
+            });
+            let connection = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(tls_stream), service)
+                .into_owned();
+            if let Err(error) = connection_watcher.watch(connection).await {
+                tracing::debug!(%peer_address, %error, "Connection closed with an error");
+            }
+        });
+    }
+    drop(tcp_listener);
+    graceful_shutdown.shutdown().await;
+    Ok(())
+}
+
+/// Sets up the plain HTTP listener that redirects every request to the HTTPS server.
+///
+/// # Arguments
+/// * `https_port` - The port of the HTTPS server
+pub fn https_redirect_routes(https_port: u16) -> Router {
+    Router::new()
+        .fallback(redirect_to_https)
+        .with_state(https_port)
+}
+
+/// Answers `308 Permanent Redirect` to the same host and path on the HTTPS server, keeping
+/// the method and body of the request.
+async fn redirect_to_https(
+    State(https_port): State<u16>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| uri.authority().map(|authority| authority.as_str()));
+    let path_and_query = uri
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or("/");
+    match https_redirect_location(host, path_and_query, https_port) {
+        Some(location) => (
+            StatusCode::PERMANENT_REDIRECT,
+            [(header::LOCATION, location)],
+        )
+            .into_response(),
+        None => error_response(
+            ErrorCode::InvalidInput,
+            "The Host header is missing or invalid",
+        ),
+    }
+}
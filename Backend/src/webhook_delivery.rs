@@ -0,0 +1,682 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::PgPool;
+use tokio::task::JoinSet;
+
+use crate::configuration::BackendConfiguration;
+use crate::question_events::QuestionEvents;
+use crate::webhooks::{install_webhook_tables, DeliveryStatus};
+
+/// Header naming the kind of question event delivered.
+pub const EVENT_HEADER: &str = "x-webhook-event";
+
+/// Header carrying the ID of the delivery, the same for every attempt, so that receivers can
+/// ignore a delivery they already handled.
+pub const DELIVERY_HEADER: &str = "x-webhook-delivery";
+
+/// Header carrying the Unix time, in seconds, at which the attempt was signed.
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+
+/// Header carrying `sha256=` and the hexadecimal HMAC-SHA256 of `<timestamp>.<body>`, keyed
+/// with the secret of the webhook.
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+/// Time between two looks for due deliveries, which are also looked for on every question event.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Deliveries claimed, and attempted concurrently, at once.
+const CLAIMED_DELIVERIES: i64 = 16;
+
+/// Time added to the delivery timeout before a claimed delivery, whose attempt was not
+/// recorded, e.g. because its Backend instance stopped, can be claimed again.
+const LEASE_MARGIN: Duration = Duration::from_secs(30);
+
+/// Time between two prunings of the delivery log.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Longest error kept in the delivery log.
+const MAX_ERROR_LENGTH: usize = 500;
+
+/// Attempts, backoff and timeout of the webhook deliveries.
+#[derive(Debug, Clone)]
+pub struct WebhookSettings {
+    /// Attempts made before a delivery is moved to the dead-letter list.
+    pub max_attempts: u32,
+    /// Wait after the first failed attempt, doubled after every attempt.
+    pub retry_base_delay: Duration,
+    /// Upper bound of the wait between attempts.
+    pub retry_max_delay: Duration,
+    /// Time given to the receiver to answer an attempt.
+    pub timeout: Duration,
+    /// Time the delivered deliveries are kept in the delivery log.
+    pub log_retention: Duration,
+}
+
+impl WebhookSettings {
+    /// Time to wait after a failed attempt: the base delay doubled for every previous
+    /// attempt, bounded by the maximum delay.
+    ///
+    /// # Arguments
+    /// * `attempts` - The number of attempts made so far, at least 1
+    pub fn retry_delay(&self, attempts: u32) -> Duration {
+        let exponential_delay = self
+            .retry_base_delay
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)));
+        exponential_delay.min(self.retry_max_delay)
+    }
+
+    /// Where a delivery stands after an attempt, and when a pending one is attempted again.
+    ///
+    /// # Arguments
+    /// * `outcome` - The result of the attempt
+    /// * `attempts` - The number of attempts made so far, this one included
+    pub fn status_after(
+        &self,
+        outcome: &AttemptOutcome,
+        attempts: u32,
+    ) -> (DeliveryStatus, Duration) {
+        match outcome {
+            AttemptOutcome::Delivered { .. } => (DeliveryStatus::Delivered, Duration::ZERO),
+            AttemptOutcome::Failed { .. } if attempts >= self.max_attempts => {
+                (DeliveryStatus::Dead, Duration::ZERO)
+            }
+            AttemptOutcome::Failed { .. } => (DeliveryStatus::Pending, self.retry_delay(attempts)),
+        }
+    }
+}
+
+/// A delivery claimed for an attempt, with its webhook.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ClaimedDelivery {
+    pub delivery_id: i64,
+    pub webhook_id: i32,
+    pub event_kind: String,
+    /// The attempts made so far, this one included.
+    pub attempts: i32,
+    /// The question event, as queued by the trigger.
+    pub payload: Value,
+    pub url: String,
+    pub secret: String,
+}
+
+/// The result of an attempt to deliver a question event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// The receiver answered with a `2xx` status.
+    Delivered { status_code: u16 },
+    /// The receiver answered with another status, or couldn't be reached in time.
+    Failed {
+        status_code: Option<u16>,
+        error: String,
+    },
+}
+
+/// The HMAC-SHA256 signature of a payload, as sent in `SIGNATURE_HEADER`.
+///
+/// # Arguments
+/// * `secret` - The secret of the webhook
+/// * `timestamp` - The Unix time sent in `TIMESTAMP_HEADER`
+/// * `body` - The JSON body of the request
+pub fn sign_payload(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// The HTTP client posting the deliveries, which doesn't follow redirects, as a redirected
+/// delivery would reach a URL the admins didn't register.
+///
+/// # Arguments
+/// * `settings` - The webhook settings holding the timeout
+pub fn webhook_http_client(settings: &WebhookSettings) -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(settings.timeout)
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!(
+            "questions-backend-webhooks/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()
+}
+
+/// Describes an error with its causes, which reqwest keeps out of its own message.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut description = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        description = format!("{}: {}", description, cause);
+        source = cause.source();
+    }
+    description
+}
+
+/// Posts a question event to the URL of its webhook, signed with the secret of the webhook.
+///
+/// The body is the queued question event with the IDs of the delivery and of the webhook.
+///
+/// # Arguments
+/// * `http_client` - The client posting the deliveries
+/// * `delivery` - The claimed delivery
+pub async fn send_delivery(
+    http_client: &reqwest::Client,
+    delivery: &ClaimedDelivery,
+) -> AttemptOutcome {
+    let mut payload = delivery.payload.clone();
+    if let Some(fields) = payload.as_object_mut() {
+        fields.insert("delivery_id".to_string(), delivery.delivery_id.into());
+        fields.insert("webhook_id".to_string(), delivery.webhook_id.into());
+    }
+    let body = payload.to_string().into_bytes();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default();
+    let signature = sign_payload(&delivery.secret, timestamp, &body);
+
+    let answer = http_client
+        .post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_kind)
+        .header(DELIVERY_HEADER, delivery.delivery_id.to_string())
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(body)
+        .send()
+        .await;
+    match answer {
+        Ok(answer) if answer.status().is_success() => AttemptOutcome::Delivered {
+            status_code: answer.status().as_u16(),
+        },
+        Ok(answer) => AttemptOutcome::Failed {
+            status_code: Some(answer.status().as_u16()),
+            error: format!("The receiver answered {}", answer.status()),
+        },
+        Err(error) => AttemptOutcome::Failed {
+            status_code: None,
+            error: error_chain(&error),
+        },
+    }
+}
+
+/// Claims the due deliveries, counting the attempt about to be made and pushing their next
+/// attempt past the lease, so that no other Backend instance attempts them meanwhile.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `lease` - Time after which the deliveries can be claimed again, if not recorded
+async fn claim_due_deliveries(
+    database_pool: &PgPool,
+    lease: Duration,
+) -> Result<Vec<ClaimedDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, ClaimedDelivery>(
+        "UPDATE webhook_deliveries_table AS delivery \
+         SET attempts = delivery.attempts + 1, \
+             next_attempt_at = now() + make_interval(secs => $3) \
+         FROM webhooks_table AS webhook \
+         WHERE webhook.webhook_id = delivery.webhook_id AND delivery.delivery_id IN ( \
+             SELECT delivery_id FROM webhook_deliveries_table \
+             WHERE status = $1 AND next_attempt_at <= now() \
+             ORDER BY next_attempt_at LIMIT $2 FOR UPDATE SKIP LOCKED) \
+         RETURNING delivery.delivery_id, delivery.webhook_id, delivery.event_kind, \
+             delivery.attempts, delivery.payload, webhook.url, webhook.secret",
+    )
+    .bind(DeliveryStatus::Pending.name())
+    .bind(CLAIMED_DELIVERIES)
+    .bind(lease.as_secs_f64())
+    .fetch_all(database_pool)
+    .await
+}
+
+/// Records the result of an attempt: delivered, dead once every attempt failed, or pending
+/// with the backoff before the next attempt.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `settings` - The webhook settings holding the attempts and the backoff
+/// * `delivery` - The attempted delivery
+/// * `outcome` - The result of the attempt
+async fn record_attempt(
+    database_pool: &PgPool,
+    settings: &WebhookSettings,
+    delivery: &ClaimedDelivery,
+    outcome: &AttemptOutcome,
+) -> Result<(), sqlx::Error> {
+    let attempts = u32::try_from(delivery.attempts).unwrap_or_default();
+    let (status, retry_delay) = settings.status_after(outcome, attempts);
+    let (status_code, error) = match outcome {
+        AttemptOutcome::Delivered { status_code } => (Some(*status_code), None),
+        AttemptOutcome::Failed { status_code, error } => (
+            *status_code,
+            Some(error.chars().take(MAX_ERROR_LENGTH).collect::<String>()),
+        ),
+    };
+    match status {
+        DeliveryStatus::Delivered => tracing::debug!(
+            delivery_id = delivery.delivery_id,
+            webhook_id = delivery.webhook_id,
+            attempts,
+            "Delivered a webhook"
+        ),
+        DeliveryStatus::Pending => tracing::info!(
+            delivery_id = delivery.delivery_id,
+            webhook_id = delivery.webhook_id,
+            attempts,
+            retry_in_ms = retry_delay.as_millis() as u64,
+            error = error.as_deref().unwrap_or_default(),
+            "Webhook delivery failed, retrying"
+        ),
+        DeliveryStatus::Dead => tracing::warn!(
+            delivery_id = delivery.delivery_id,
+            webhook_id = delivery.webhook_id,
+            attempts,
+            error = error.as_deref().unwrap_or_default(),
+            "Webhook delivery failed every attempt, moved to the dead-letter list"
+        ),
+    }
+
+    sqlx::query(
+        "UPDATE webhook_deliveries_table \
+         SET status = $2, last_status_code = $3, last_error = $4, \
+             next_attempt_at = now() + make_interval(secs => $5), \
+             completed_at = CASE WHEN $2 = $6 THEN NULL ELSE now() END \
+         WHERE delivery_id = $1",
+    )
+    .bind(delivery.delivery_id)
+    .bind(status.name())
+    .bind(status_code.map(i32::from))
+    .bind(error)
+    .bind(retry_delay.as_secs_f64())
+    .bind(DeliveryStatus::Pending.name())
+    .execute(database_pool)
+    .await?;
+    Ok(())
+}
+
+/// Attempts every due delivery, a batch at a time.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `http_client` - The client posting the deliveries
+/// * `settings` - The webhook settings
+async fn deliver_due_webhooks(
+    database_pool: &Arc<PgPool>,
+    http_client: &reqwest::Client,
+    settings: &Arc<WebhookSettings>,
+) -> Result<(), sqlx::Error> {
+    loop {
+        let claimed_deliveries =
+            claim_due_deliveries(database_pool, settings.timeout + LEASE_MARGIN).await?;
+        let claimed_count = claimed_deliveries.len();
+        let mut attempts = JoinSet::new();
+        for delivery in claimed_deliveries {
+            let (database_pool, http_client, settings) =
+                (database_pool.clone(), http_client.clone(), settings.clone());
+            attempts.spawn(async move {
+                let outcome = send_delivery(&http_client, &delivery).await;
+                record_attempt(&database_pool, &settings, &delivery, &outcome).await
+            });
+        }
+        while let Some(attempt) = attempts.join_next().await {
+            if let Ok(Err(error)) = attempt {
+                tracing::warn!(%error, "Couldn't record a webhook delivery attempt");
+            }
+        }
+        if claimed_count < CLAIMED_DELIVERIES as usize {
+            return Ok(());
+        }
+    }
+}
+
+/// Deletes the delivered deliveries older than the retention of the delivery log.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `log_retention` - Time the delivered deliveries are kept
+async fn prune_delivery_log(
+    database_pool: &PgPool,
+    log_retention: Duration,
+) -> Result<u64, sqlx::Error> {
+    let pruned = sqlx::query(
+        "DELETE FROM webhook_deliveries_table \
+         WHERE status = $1 AND completed_at < now() - make_interval(secs => $2)",
+    )
+    .bind(DeliveryStatus::Delivered.name())
+    .bind(log_retention.as_secs_f64())
+    .execute(database_pool)
+    .await?;
+    Ok(pruned.rows_affected())
+}
+
+/// Delivers the question events to the registered webhooks until the server shuts down.
+///
+/// The deliveries are queued by a trigger of `questions_table` and claimed by any Backend
+/// instance, so each one is attempted by a single instance at a time. They are looked for on
+/// every question event and every second for the retries. The attempts being made when the
+/// server shuts down are abandoned and made again once their lease expires.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `question_events` - The broadcaster of the changes, telling when deliveries were queued
+/// * `configuration` - The backend configuration holding the webhook and retry settings
+pub async fn dispatch_webhooks(
+    database_pool: Arc<PgPool>,
+    question_events: Arc<QuestionEvents>,
+    configuration: BackendConfiguration,
+) {
+    tokio::select! {
+        _ = question_events.closed() => {}
+        _ = deliver_webhooks(&database_pool, &question_events, &configuration) => {}
+    }
+}
+
+/// Installs the webhook tables, then attempts the due deliveries until the pool is closed.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+/// * `question_events` - The broadcaster of the changes
+/// * `configuration` - The backend configuration holding the webhook and retry settings
+async fn deliver_webhooks(
+    database_pool: &Arc<PgPool>,
+    question_events: &QuestionEvents,
+    configuration: &BackendConfiguration,
+) {
+    let settings = Arc::new(configuration.webhook_settings());
+    let http_client = match webhook_http_client(&settings) {
+        Ok(http_client) => http_client,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't create the webhook client, webhooks are disabled");
+            return;
+        }
+    };
+
+    let mut failed_attempts = 0;
+    while let Err(error) = install_webhook_tables(database_pool).await {
+        if let sqlx::Error::PoolClosed = error {
+            return;
+        }
+        failed_attempts += 1;
+        tracing::warn!(
+            attempt = failed_attempts,
+            %error,
+            "Couldn't install the webhook tables, retrying"
+        );
+        tokio::time::sleep(configuration.database_connect_retry_delay(failed_attempts)).await;
+    }
+
+    let mut subscription = question_events.subscribe(None);
+    let mut poll_interval = tokio::time::interval(POLL_INTERVAL);
+    poll_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut pruned_at: Option<Instant> = None;
+    let mut failed_attempts = 0;
+    loop {
+        match deliver_due_webhooks(database_pool, &http_client, &settings).await {
+            Ok(()) => failed_attempts = 0,
+            Err(sqlx::Error::PoolClosed) => return,
+            Err(error) => {
+                failed_attempts += 1;
+                tracing::warn!(%error, "Couldn't claim the due webhook deliveries");
+                tokio::time::sleep(configuration.database_connect_retry_delay(failed_attempts))
+                    .await;
+                continue;
+            }
+        }
+        if pruned_at.is_none_or(|pruned_at| pruned_at.elapsed() >= PRUNE_INTERVAL) {
+            match prune_delivery_log(database_pool, settings.log_retention).await {
+                Ok(pruned) if pruned > 0 => {
+                    tracing::info!(pruned, "Pruned the webhook delivery log")
+                }
+                Ok(_) => {}
+                Err(error) => tracing::warn!(%error, "Couldn't prune the webhook delivery log"),
+            }
+            pruned_at = Some(Instant::now());
+        }
+        tokio::select! {
+            question_event = subscription.next_event() => {
+                if question_event.is_none() {
+                    return;
+                }
+            }
+            _ = poll_interval.tick() => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        send_delivery, sign_payload, webhook_http_client, AttemptOutcome, ClaimedDelivery,
+        WebhookSettings, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+    };
+    use crate::webhooks::DeliveryStatus;
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::Redirect;
+    use axum::routing::post;
+    use axum::Router;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const SECRET: &str = "a secret of the stand-in receiver";
+
+    /// Settings with short delays, so that the tests don't wait.
+    fn test_settings() -> WebhookSettings {
+        WebhookSettings {
+            max_attempts: 4,
+            retry_base_delay: Duration::from_millis(100),
+            retry_max_delay: Duration::from_millis(300),
+            timeout: Duration::from_millis(500),
+            log_retention: Duration::from_secs(60),
+        }
+    }
+
+    /// A delivery of a `created` event to the given URL.
+    fn test_delivery(url: String) -> ClaimedDelivery {
+        ClaimedDelivery {
+            delivery_id: 42,
+            webhook_id: 7,
+            event_kind: "created".to_string(),
+            attempts: 1,
+            payload: json!({
+                "kind": "created",
+                "question_id": 3,
+                "question": {"question_id": 3, "question_title": "Hello"},
+                "occurred_at": "2026-10-18T12:00:00Z"
+            }),
+            url,
+            secret: SECRET.to_string(),
+        }
+    }
+
+    /// Starts a stand-in receiver on a free local port.
+    ///
+    /// # Returns
+    /// The base URL of the receiver.
+    async fn start_receiver(routes: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("A free local port is available");
+        let address = listener.local_addr().expect("The listener has an address");
+        tokio::spawn(async move { axum::serve(listener, routes).await });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn signed_payloads_reach_the_receiver() {
+        let received = Arc::new(Mutex::new(None::<(HeaderMap, Bytes)>));
+        let receiver_received = received.clone();
+        let receiver_url = start_receiver(Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| async move {
+                *receiver_received.lock().unwrap() = Some((headers, body));
+                StatusCode::NO_CONTENT
+            }),
+        ))
+        .await;
+
+        let http_client = webhook_http_client(&test_settings()).unwrap();
+        let outcome = send_delivery(
+            &http_client,
+            &test_delivery(format!("{}/hook", receiver_url)),
+        )
+        .await;
+        assert_eq!(outcome, AttemptOutcome::Delivered { status_code: 204 });
+
+        let (headers, body) = received
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The receiver was called");
+        let header = |name: &str| headers[name].to_str().unwrap().to_string();
+        assert_eq!(header(EVENT_HEADER), "created");
+        assert_eq!(header(DELIVERY_HEADER), "42");
+        assert_eq!(header("content-type"), "application/json");
+        let timestamp: u64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(
+            header(SIGNATURE_HEADER),
+            sign_payload(SECRET, timestamp, &body)
+        );
+        assert_ne!(
+            header(SIGNATURE_HEADER),
+            sign_payload("another secret", timestamp, &body)
+        );
+
+        let payload: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["delivery_id"], 42);
+        assert_eq!(payload["webhook_id"], 7);
+        assert_eq!(payload["kind"], "created");
+        assert_eq!(payload["question"]["question_title"], "Hello");
+    }
+
+    #[test]
+    fn signatures_are_hmac_sha256_of_the_timestamp_and_body() {
+        // Computed independently with Python's hmac module
+        assert_eq!(
+            sign_payload("key", 1_700_000_000, br#"{"kind":"created"}"#),
+            "sha256=30cdc4ffe545cda3c97d9408ef9c7634580ceb461f1d8af39c71e8edda5e46c2"
+        );
+        assert_ne!(
+            sign_payload("key", 1_700_000_001, br#"{"kind":"created"}"#),
+            sign_payload("key", 1_700_000_000, br#"{"kind":"created"}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn error_answers_and_redirects_fail_the_attempt() {
+        let receiver_url = start_receiver(
+            Router::new()
+                .route(
+                    "/failing",
+                    post(|| async { StatusCode::SERVICE_UNAVAILABLE }),
+                )
+                .route(
+                    "/moved",
+                    post(|| async { Redirect::permanent("/elsewhere") }),
+                )
+                .route("/elsewhere", post(|| async { StatusCode::OK })),
+        )
+        .await;
+        let http_client = webhook_http_client(&test_settings()).unwrap();
+
+        let outcome = send_delivery(
+            &http_client,
+            &test_delivery(format!("{}/failing", receiver_url)),
+        )
+        .await;
+        assert!(matches!(
+            outcome,
+            AttemptOutcome::Failed {
+                status_code: Some(503),
+                ..
+            }
+        ));
+
+        let outcome = send_delivery(
+            &http_client,
+            &test_delivery(format!("{}/moved", receiver_url)),
+        )
+        .await;
+        assert!(matches!(
+            outcome,
+            AttemptOutcome::Failed {
+                status_code: Some(308),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn unreachable_and_slow_receivers_fail_the_attempt() {
+        let receiver_url = start_receiver(Router::new().route(
+            "/slow",
+            post(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                StatusCode::OK
+            }),
+        ))
+        .await;
+        let http_client = webhook_http_client(&test_settings()).unwrap();
+
+        let outcome = send_delivery(
+            &http_client,
+            &test_delivery(format!("{}/slow", receiver_url)),
+        )
+        .await;
+        assert!(matches!(
+            outcome,
+            AttemptOutcome::Failed {
+                status_code: None,
+                ..
+            }
+        ));
+
+        // Nothing listens on the port of a listener that was dropped
+        let closed_address = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let outcome = send_delivery(
+            &http_client,
+            &test_delivery(format!("http://{}/hook", closed_address)),
+        )
+        .await;
+        let AttemptOutcome::Failed { status_code, error } = outcome else {
+            panic!("Delivering to a closed port succeeded");
+        };
+        assert_eq!(status_code, None);
+        assert!(!error.is_empty());
+    }
+
+    #[test]
+    fn failed_attempts_back_off_then_move_to_the_dead_letters() {
+        let settings = test_settings();
+        let failed = AttemptOutcome::Failed {
+            status_code: Some(500),
+            error: "The receiver answered 500".to_string(),
+        };
+        let schedule: Vec<(DeliveryStatus, Duration)> = (1..=4)
+            .map(|attempts| settings.status_after(&failed, attempts))
+            .collect();
+        assert_eq!(
+            schedule,
+            [
+                (DeliveryStatus::Pending, Duration::from_millis(100)),
+                (DeliveryStatus::Pending, Duration::from_millis(200)),
+                (DeliveryStatus::Pending, Duration::from_millis(300)),
+                (DeliveryStatus::Dead, Duration::ZERO),
+            ]
+        );
+        assert_eq!(
+            settings.status_after(&AttemptOutcome::Delivered { status_code: 200 }, 4),
+            (DeliveryStatus::Delivered, Duration::ZERO)
+        );
+    }
+}
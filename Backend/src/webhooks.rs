@@ -0,0 +1,677 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Json, Path, Query, Request, State,
+    },
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, MethodRouter},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use common::route_paths::{
+    DEAD_LETTERS_ROUTE, DEAD_LETTER_REDELIVERY_ROUTE, WEBHOOKS_ROUTE, WEBHOOK_DELIVERIES_ROUTE,
+    WEBHOOK_ROUTE,
+};
+use common::{ErrorCode, ErrorResponse, MessageResponse, QuestionEventKind, API_KEY_HEADER};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::change_feed::create_trigger_unless_exists;
+use crate::request_handlers::{body_rejection_response, error_response};
+use crate::request_routes::ApiState;
+
+/// Statements creating the tables of the webhooks and of their deliveries, if missing.
+///
+/// A delivery is `pending` until its receiver answers with a `2xx` status, then `delivered`,
+/// or `dead` once every attempt failed.
+const WEBHOOK_TABLES_SQL: [&str; 4] = [
+    "CREATE TABLE IF NOT EXISTS webhooks_table (
+        webhook_id SERIAL PRIMARY KEY,
+        url TEXT NOT NULL,
+        secret TEXT NOT NULL,
+        event_kinds TEXT[] NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+    )",
+    "CREATE TABLE IF NOT EXISTS webhook_deliveries_table (
+        delivery_id BIGSERIAL PRIMARY KEY,
+        webhook_id INTEGER NOT NULL REFERENCES webhooks_table ON DELETE CASCADE,
+        event_kind TEXT NOT NULL,
+        question_id INTEGER NOT NULL,
+        payload JSONB NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        attempts INTEGER NOT NULL DEFAULT 0,
+        last_status_code INTEGER,
+        last_error TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+        next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+        completed_at TIMESTAMPTZ
+    )",
+    "CREATE INDEX IF NOT EXISTS webhook_deliveries_due
+        ON webhook_deliveries_table (next_attempt_at) WHERE status = 'pending'",
+    "CREATE INDEX IF NOT EXISTS webhook_deliveries_log
+        ON webhook_deliveries_table (webhook_id, delivery_id)",
+];
+
+/// Function of the trigger queueing a delivery for every webhook interested in a change of
+/// `questions_table`, in the transaction of the change, so that every change is delivered once
+/// whichever Backend instance, or SQL client, made it.
+const ENQUEUE_FUNCTION_SQL: &str = r#"
+CREATE OR REPLACE FUNCTION enqueue_webhook_deliveries() RETURNS trigger AS $$
+DECLARE
+    change_kind TEXT := CASE TG_OP WHEN 'INSERT' THEN 'created' WHEN 'UPDATE' THEN 'updated' ELSE 'deleted' END;
+    changed_question_id INTEGER := CASE TG_OP WHEN 'DELETE' THEN OLD.question_id ELSE NEW.question_id END;
+BEGIN
+    INSERT INTO webhook_deliveries_table (webhook_id, event_kind, question_id, payload)
+    SELECT webhook_id, change_kind, changed_question_id, jsonb_build_object(
+        'kind', change_kind,
+        'question_id', changed_question_id,
+        'question', CASE TG_OP WHEN 'DELETE' THEN NULL ELSE to_jsonb(NEW) END,
+        'occurred_at', now()
+    )
+    FROM webhooks_table
+    WHERE change_kind = ANY (event_kinds);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql
+"#;
+
+/// Name of the trigger of `questions_table` queueing the webhook deliveries.
+const ENQUEUE_TRIGGER_NAME: &str = "questions_table_enqueue_webhooks";
+
+/// Creates the trigger of `questions_table` calling the enqueue function.
+const ENQUEUE_TRIGGER_SQL: &str = "CREATE TRIGGER questions_table_enqueue_webhooks \
+     AFTER INSERT OR UPDATE OR DELETE ON questions_table \
+     FOR EACH ROW EXECUTE FUNCTION enqueue_webhook_deliveries()";
+
+/// Shortest secret accepted when registering a webhook.
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// Number of deliveries listed when the query doesn't say.
+const DEFAULT_LISTED_DELIVERIES: i64 = 50;
+
+/// Largest number of deliveries listed at once.
+const MAX_LISTED_DELIVERIES: i64 = 500;
+
+/// The columns of a delivery, as listed by the delivery log and the dead-letter list.
+const DELIVERY_COLUMNS: &str = "delivery_id, webhook_id, event_kind AS event, question_id, \
+     status, attempts, last_status_code, last_error, created_at, \
+     CASE WHEN status = 'pending' THEN next_attempt_at END AS next_attempt_at, completed_at";
+
+/// Where a delivery stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Not delivered yet, waiting for its next attempt.
+    Pending,
+    /// Answered with a `2xx` status by the receiver.
+    Delivered,
+    /// Failed every attempt, kept in the dead-letter list.
+    Dead,
+}
+
+impl DeliveryStatus {
+    /// The name of the status, as stored in the database.
+    pub fn name(self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Dead => "dead",
+        }
+    }
+}
+
+/// The body of `POST /api/v1/webhooks`.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NewWebhook {
+    /// The `http` or `https` URL the events are posted to.
+    pub url: String,
+    /// The kinds of events posted, every kind when missing.
+    pub events: Option<Vec<QuestionEventKind>>,
+    /// The secret signing the payloads, at least 16 characters, generated when missing.
+    pub secret: Option<String>,
+}
+
+/// A registered webhook.
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct Webhook {
+    pub webhook_id: i32,
+    pub url: String,
+    /// The kinds of events posted.
+    #[schema(value_type = Vec<QuestionEventKind>)]
+    pub events: Vec<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTime<Utc>,
+}
+
+/// The answer of `POST /api/v1/webhooks`: the webhook with its secret, which is not shown again.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// The secret signing the payloads, the key of their HMAC-SHA256 signature.
+    pub secret: String,
+}
+
+/// A delivery of a question event to a webhook, with the result of its last attempt.
+#[derive(Debug, Serialize, ToSchema, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub delivery_id: i64,
+    pub webhook_id: i32,
+    #[schema(value_type = QuestionEventKind)]
+    pub event: String,
+    pub question_id: i32,
+    #[schema(value_type = DeliveryStatus)]
+    pub status: String,
+    /// The attempts made so far.
+    pub attempts: i32,
+    /// The HTTP status of the last answer of the receiver, if it answered.
+    pub last_status_code: Option<i32>,
+    /// Why the last attempt failed.
+    pub last_error: Option<String>,
+    #[schema(value_type = String, format = DateTime)]
+    pub created_at: DateTime<Utc>,
+    /// When the next attempt is due, while the delivery is pending.
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    /// When the delivery succeeded or was given up.
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Query string of the delivery log of a webhook.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveryLogParameters {
+    /// When given, only the deliveries with this status are listed.
+    pub status: Option<DeliveryStatus>,
+    /// Number of deliveries listed, 50 by default and at most 500.
+    pub limit: Option<i64>,
+}
+
+/// Query string of the dead-letter list.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeadLetterParameters {
+    /// Number of deliveries listed, 50 by default and at most 500.
+    pub limit: Option<i64>,
+}
+
+/// Creates the tables, the function and the trigger of the webhooks, unless they are already
+/// there.
+///
+/// The installation is serialized with an advisory lock, so that Backend instances starting
+/// together don't race.
+///
+/// # Arguments
+/// * `database_pool` - The pool of database connections
+pub async fn install_webhook_tables(database_pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut transaction = database_pool.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(ENQUEUE_TRIGGER_NAME)
+        .execute(&mut *transaction)
+        .await?;
+    for statement in WEBHOOK_TABLES_SQL.into_iter().chain([ENQUEUE_FUNCTION_SQL]) {
+        sqlx::query(statement).execute(&mut *transaction).await?;
+    }
+    if create_trigger_unless_exists(&mut transaction, ENQUEUE_TRIGGER_NAME, ENQUEUE_TRIGGER_SQL)
+        .await?
+    {
+        tracing::info!(
+            trigger = ENQUEUE_TRIGGER_NAME,
+            "Installed the trigger queueing the webhook deliveries"
+        );
+    }
+    transaction.commit().await
+}
+
+/// Sets up the routes managing the webhooks, which answer `401 Unauthorized` unless the
+/// request carries one of the admin API keys.
+///
+/// # Arguments
+/// * `admin_keys` - The API keys allowed to manage the webhooks
+pub fn webhook_routes(admin_keys: Arc<HashSet<String>>) -> Router<ApiState> {
+    // Layered on each method router, so that other methods are still answered `405`
+    let admin_only = |method_router: MethodRouter<ApiState>| {
+        method_router.route_layer(middleware::from_fn_with_state(
+            admin_keys.clone(),
+            require_admin_key,
+        ))
+    };
+    Router::new()
+        .route(
+            WEBHOOKS_ROUTE,
+            admin_only(get(list_webhooks).post(register_webhook)),
+        )
+        .route(WEBHOOK_ROUTE, admin_only(delete(delete_webhook)))
+        .route(
+            WEBHOOK_DELIVERIES_ROUTE,
+            admin_only(get(list_webhook_deliveries)),
+        )
+        .route(DEAD_LETTERS_ROUTE, admin_only(get(list_dead_letters)))
+        .route(
+            DEAD_LETTER_REDELIVERY_ROUTE,
+            admin_only(post(redeliver_dead_letter)),
+        )
+}
+
+/// Middleware refusing the requests without a known admin API key in `X-API-Key`.
+///
+/// # Arguments
+/// * `admin_keys` - The API keys allowed to manage the webhooks
+/// * `request` - The incoming request
+/// * `next` - The rest of the middleware stack and the handler
+async fn require_admin_key(
+    State(admin_keys): State<Arc<HashSet<String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_admin = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|api_key| admin_keys.contains(api_key));
+    if !is_admin {
+        return error_response(
+            ErrorCode::Unauthorized,
+            "Managing webhooks requires an admin API key in X-API-Key",
+        );
+    }
+    next.run(request).await
+}
+
+/// Builds the JSON error answer of a database failure, logging its cause.
+///
+/// # Arguments
+/// * `error` - The error of the database
+fn database_error_response(error: sqlx::Error) -> Response {
+    tracing::error!(%error, "Webhook query failed");
+    error_response(
+        ErrorCode::InternalError,
+        "Internal server error while managing webhooks",
+    )
+}
+
+/// Checks a new webhook and fills in the missing events and secret.
+///
+/// # Arguments
+/// * `new_webhook` - The body of the registration
+///
+/// # Returns
+/// The URL, the names of the event kinds and the secret, or the reason they are invalid.
+fn validate_new_webhook(new_webhook: NewWebhook) -> Result<(String, Vec<String>, String), String> {
+    let url = Url::parse(&new_webhook.url)
+        .map_err(|error| format!("url is not a valid URL: {}", error))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("url must be an http or https URL with a host".to_string());
+    }
+
+    let event_kinds = new_webhook.events.unwrap_or_else(|| {
+        vec![
+            QuestionEventKind::Created,
+            QuestionEventKind::Updated,
+            QuestionEventKind::Deleted,
+        ]
+    });
+    if event_kinds.is_empty() {
+        return Err("events must name at least one kind of event".to_string());
+    }
+    let mut event_names: Vec<String> = Vec::new();
+    for event_kind in event_kinds {
+        if !event_names.iter().any(|name| name == event_kind.name()) {
+            event_names.push(event_kind.name().to_string());
+        }
+    }
+
+    let secret = match new_webhook.secret {
+        Some(secret) if secret.chars().count() < MIN_SECRET_LENGTH => {
+            return Err(format!(
+                "secret must be at least {} characters long",
+                MIN_SECRET_LENGTH
+            ))
+        }
+        Some(secret) => secret,
+        None => hex::encode(rand::random::<[u8; 32]>()),
+    };
+    Ok((url.to_string(), event_names, secret))
+}
+
+/// Registers a webhook, posted every question event of the chosen kinds from now on.
+///
+/// # Arguments
+/// * `database_pool` - A state where the pool of database connections is located
+/// * `input` - The new webhook, or the reason it couldn't be read
+///
+/// # Returns
+/// `201 Created` with the webhook and its secret, or an error message.
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(("X-API-Key" = String, Header, description = "An admin API key")),
+    request_body = NewWebhook,
+    responses(
+        (status = 201, description = "The webhook was registered", body = CreatedWebhook),
+        (status = 400, description = "The URL, events or secret are invalid", body = ErrorResponse),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn register_webhook(
+    State(database_pool): State<Arc<PgPool>>,
+    input: Result<Json<NewWebhook>, JsonRejection>,
+) -> Response {
+    let new_webhook = match input {
+        Ok(Json(new_webhook)) => new_webhook,
+        Err(rejection) => {
+            return body_rejection_response(rejection.status(), &rejection.body_text())
+        }
+    };
+    let (url, event_names, secret) = match validate_new_webhook(new_webhook) {
+        Ok(webhook_fields) => webhook_fields,
+        Err(message) => return error_response(ErrorCode::InvalidInput, &message),
+    };
+
+    let insert_result = sqlx::query_as::<_, Webhook>(
+        "INSERT INTO webhooks_table (url, secret, event_kinds) VALUES ($1, $2, $3) \
+         RETURNING webhook_id, url, event_kinds AS events, created_at",
+    )
+    .bind(&url)
+    .bind(&secret)
+    .bind(&event_names)
+    .fetch_one(&*database_pool)
+    .await;
+    match insert_result {
+        Ok(webhook) => {
+            tracing::info!(webhook_id = webhook.webhook_id, %url, "Registered a webhook");
+            (
+                StatusCode::CREATED,
+                Json(CreatedWebhook { webhook, secret }),
+            )
+                .into_response()
+        }
+        Err(error) => database_error_response(error),
+    }
+}
+
+/// Lists the registered webhooks, without their secrets.
+///
+/// # Arguments
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A JSON list of the webhooks ordered by ID, or an error message.
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    params(("X-API-Key" = String, Header, description = "An admin API key")),
+    responses(
+        (status = 200, description = "The webhooks, ordered by ID", body = [Webhook]),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn list_webhooks(State(database_pool): State<Arc<PgPool>>) -> Response {
+    let webhooks = sqlx::query_as::<_, Webhook>(
+        "SELECT webhook_id, url, event_kinds AS events, created_at FROM webhooks_table \
+         ORDER BY webhook_id",
+    )
+    .fetch_all(&*database_pool)
+    .await;
+    match webhooks {
+        Ok(webhooks) => Json(webhooks).into_response(),
+        Err(error) => database_error_response(error),
+    }
+}
+
+/// Unregisters a webhook, dropping its pending deliveries and its delivery log.
+///
+/// # Arguments
+/// * `webhook_id` - The ID of the webhook
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A success message, or an error message if the webhook does not exist.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "ID of the webhook"),
+        ("X-API-Key" = String, Header, description = "An admin API key")
+    ),
+    responses(
+        (status = 200, description = "The webhook was unregistered", body = MessageResponse),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 404, description = "No webhook has this ID", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn delete_webhook(
+    Path(webhook_id): Path<i32>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> Response {
+    let delete_result = sqlx::query("DELETE FROM webhooks_table WHERE webhook_id = $1")
+        .bind(webhook_id)
+        .execute(&*database_pool)
+        .await;
+    match delete_result {
+        Ok(deleted) if deleted.rows_affected() > 0 => {
+            tracing::info!(webhook_id, "Unregistered a webhook");
+            Json(MessageResponse {
+                message: "Webhook deleted successfully".to_string(),
+            })
+            .into_response()
+        }
+        Ok(_) => error_response(
+            ErrorCode::WebhookNotFound,
+            &format!("Webhook with ID {} not found", webhook_id),
+        ),
+        Err(error) => database_error_response(error),
+    }
+}
+
+/// The number of deliveries to list, bounded by `MAX_LISTED_DELIVERIES`.
+///
+/// # Arguments
+/// * `limit` - The `limit` of the query string, if any
+///
+/// # Returns
+/// The number of deliveries, or the reason the limit is invalid.
+fn listed_deliveries(limit: Option<i64>) -> Result<i64, String> {
+    match limit.unwrap_or(DEFAULT_LISTED_DELIVERIES) {
+        limit @ 1..=MAX_LISTED_DELIVERIES => Ok(limit),
+        _ => Err(format!(
+            "limit must be between 1 and {}",
+            MAX_LISTED_DELIVERIES
+        )),
+    }
+}
+
+/// Lists the deliveries of a webhook, newest first, with the result of their last attempt.
+///
+/// # Arguments
+/// * `webhook_id` - The ID of the webhook
+/// * `parameters` - The optional `status` and `limit`, or the reason they are invalid
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A JSON list of deliveries, or an error message if the webhook does not exist.
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = i32, Path, description = "ID of the webhook"),
+        ("X-API-Key" = String, Header, description = "An admin API key"),
+        DeliveryLogParameters
+    ),
+    responses(
+        (status = 200, description = "The deliveries, newest first", body = [WebhookDelivery]),
+        (status = 400, description = "The status or limit is invalid", body = ErrorResponse),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 404, description = "No webhook has this ID", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn list_webhook_deliveries(
+    Path(webhook_id): Path<i32>,
+    parameters: Result<Query<DeliveryLogParameters>, QueryRejection>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> Response {
+    let parameters = match parameters {
+        Ok(Query(parameters)) => parameters,
+        Err(rejection) => return error_response(ErrorCode::InvalidInput, &rejection.body_text()),
+    };
+    let limit = match listed_deliveries(parameters.limit) {
+        Ok(limit) => limit,
+        Err(message) => return error_response(ErrorCode::InvalidInput, &message),
+    };
+
+    let webhook_exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM webhooks_table WHERE webhook_id = $1)",
+    )
+    .bind(webhook_id)
+    .fetch_one(&*database_pool)
+    .await;
+    match webhook_exists {
+        Ok(true) => {}
+        Ok(false) => {
+            return error_response(
+                ErrorCode::WebhookNotFound,
+                &format!("Webhook with ID {} not found", webhook_id),
+            )
+        }
+        Err(error) => return database_error_response(error),
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT {} FROM webhook_deliveries_table \
+         WHERE webhook_id = $1 AND ($2::TEXT IS NULL OR status = $2) \
+         ORDER BY delivery_id DESC LIMIT $3",
+        DELIVERY_COLUMNS
+    ))
+    .bind(webhook_id)
+    .bind(parameters.status.map(DeliveryStatus::name))
+    .bind(limit)
+    .fetch_all(&*database_pool)
+    .await;
+    match deliveries {
+        Ok(deliveries) => Json(deliveries).into_response(),
+        Err(error) => database_error_response(error),
+    }
+}
+
+/// Lists the deliveries of every webhook that failed every attempt, newest first.
+///
+/// # Arguments
+/// * `parameters` - The optional `limit`, or the reason it is invalid
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// A JSON list of dead deliveries, or an error message.
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/dead-letters",
+    tag = "webhooks",
+    params(
+        ("X-API-Key" = String, Header, description = "An admin API key"),
+        DeadLetterParameters
+    ),
+    responses(
+        (status = 200, description = "The dead deliveries, newest first", body = [WebhookDelivery]),
+        (status = 400, description = "The limit is invalid", body = ErrorResponse),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn list_dead_letters(
+    parameters: Result<Query<DeadLetterParameters>, QueryRejection>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> Response {
+    let parameters = match parameters {
+        Ok(Query(parameters)) => parameters,
+        Err(rejection) => return error_response(ErrorCode::InvalidInput, &rejection.body_text()),
+    };
+    let limit = match listed_deliveries(parameters.limit) {
+        Ok(limit) => limit,
+        Err(message) => return error_response(ErrorCode::InvalidInput, &message),
+    };
+    let dead_letters = sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT {} FROM webhook_deliveries_table WHERE status = $1 \
+         ORDER BY delivery_id DESC LIMIT $2",
+        DELIVERY_COLUMNS
+    ))
+    .bind(DeliveryStatus::Dead.name())
+    .bind(limit)
+    .fetch_all(&*database_pool)
+    .await;
+    match dead_letters {
+        Ok(dead_letters) => Json(dead_letters).into_response(),
+        Err(error) => database_error_response(error),
+    }
+}
+
+/// Moves a dead delivery back to pending, to be delivered again with a fresh set of attempts.
+///
+/// # Arguments
+/// * `delivery_id` - The ID of the dead delivery
+/// * `database_pool` - A state where the pool of database connections is located
+///
+/// # Returns
+/// `202 Accepted` with a success message, or an error message if no dead delivery has the ID.
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/dead-letters/{id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = i64, Path, description = "ID of the dead delivery"),
+        ("X-API-Key" = String, Header, description = "An admin API key")
+    ),
+    responses(
+        (status = 202, description = "The delivery will be attempted again", body = MessageResponse),
+        (status = 401, description = "No admin API key was sent", body = ErrorResponse),
+        (status = 404, description = "No dead delivery has this ID", body = ErrorResponse),
+        (status = 500, description = "The database failed", body = ErrorResponse)
+    )
+)]
+pub async fn redeliver_dead_letter(
+    Path(delivery_id): Path<i64>,
+    State(database_pool): State<Arc<PgPool>>,
+) -> Response {
+    let update_result = sqlx::query(
+        "UPDATE webhook_deliveries_table \
+         SET status = $2, attempts = 0, next_attempt_at = now(), completed_at = NULL \
+         WHERE delivery_id = $1 AND status = $3",
+    )
+    .bind(delivery_id)
+    .bind(DeliveryStatus::Pending.name())
+    .bind(DeliveryStatus::Dead.name())
+    .execute(&*database_pool)
+    .await;
+    match update_result {
+        Ok(updated) if updated.rows_affected() > 0 => {
+            tracing::info!(delivery_id, "Redelivering a dead webhook delivery");
+            (
+                StatusCode::ACCEPTED,
+                Json(MessageResponse {
+                    message: "Delivery scheduled again".to_string(),
+                }),
+            )
+                .into_response()
+        }
+        Ok(_) => error_response(
+            ErrorCode::DeliveryNotFound,
+            &format!("No dead delivery with ID {}", delivery_id),
+        ),
+        Err(error) => database_error_response(error),
+    }
+}
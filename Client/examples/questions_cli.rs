@@ -0,0 +1,90 @@
+//! Small command line tool built on the questions client, for use in scripts.
+//!
+//! ```bash
+//! cargo run --example questions_cli -- list
+//! cargo run --example questions_cli -- get 42
+//! cargo run --example questions_cli -- search rust
+//! cargo run --example questions_cli -- create "Title" "Markdown content" "type1,type2"
+//! cargo run --example questions_cli -- update 42 "Title" "Markdown content" "type1,type2"
+//! cargo run --example questions_cli -- delete 42
+//! ```
+//!
+//! The backend URL is read from `QUESTIONS_API_URL` (default `http://localhost:1000`).
+
+use questions_client::{ClientError, NewQuestion, QuestionsClient};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: questions_cli <list | get ID | search TEXT | create TITLE CONTENT TYPES | update ID TITLE CONTENT TYPES | delete ID>";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+    let base_url =
+        std::env::var("QUESTIONS_API_URL").unwrap_or_else(|_| "http://localhost:1000".to_string());
+
+    match run(&base_url, &arguments).await {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the command and returns the text to print.
+async fn run(base_url: &str, arguments: &[String]) -> Result<String, String> {
+    let client = QuestionsClient::new(base_url).map_err(|error| error.to_string())?;
+    let arguments: Vec<&str> = arguments.iter().map(String::as_str).collect();
+
+    let output = match arguments.as_slice() {
+        ["list"] => to_json(&client.list().await)?,
+        ["get", question_id] => to_json(&client.get(parse_id(question_id)?).await)?,
+        ["search", search_text] => to_json(&client.search(search_text).await)?,
+        ["create", title, content, types] => client
+            .create(&new_question(title, content, types))
+            .await
+            .map_err(|error| error.to_string())?,
+        ["update", question_id, title, content, types] => client
+            .update(parse_id(question_id)?, &new_question(title, content, types))
+            .await
+            .map_err(|error| error.to_string())?,
+        ["delete", question_id] => client
+            .delete(parse_id(question_id)?)
+            .await
+            .map_err(|error| error.to_string())?,
+        _ => return Err(USAGE.to_string()),
+    };
+    Ok(output)
+}
+
+/// Parses a question ID argument.
+fn parse_id(question_id: &str) -> Result<i32, String> {
+    question_id
+        .parse()
+        .map_err(|_| format!("Invalid question ID: {}", question_id))
+}
+
+/// Builds the question to send from the command line arguments.
+fn new_question(title: &str, content: &str, types: &str) -> NewQuestion {
+    NewQuestion {
+        question_title: title.to_string(),
+        type_of_content: content.to_string(),
+        type_of_question: types
+            .split(',')
+            .map(str::trim)
+            .filter(|type_of_question| !type_of_question.is_empty())
+            .map(String::from)
+            .collect(),
+    }
+}
+
+/// Pretty prints a client result as JSON.
+fn to_json<T: serde::Serialize>(result: &Result<T, ClientError>) -> Result<String, String> {
+    match result {
+        Ok(value) => serde_json::to_string_pretty(value).map_err(|error| error.to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
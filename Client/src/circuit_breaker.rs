@@ -0,0 +1,297 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The state of the circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests are sent to the backend API.
+    Closed,
+    /// The backend API failed too often, requests fail immediately.
+    Open,
+    /// The open period is over, a single trial request decides whether to close again.
+    HalfOpen,
+}
+
+/// A snapshot of the circuit breaker, as shown on status endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitBreakerStatus {
+    /// The current state.
+    pub state: CircuitState,
+    /// Number of failed requests since the last success.
+    pub consecutive_failures: u32,
+    /// Number of consecutive failures that opens the circuit.
+    pub failure_threshold: u32,
+    /// Seconds left before a trial request is allowed, while the circuit is open.
+    pub retry_after_secs: Option<u64>,
+}
+
+/// Mutable state shared by every clone of the client.
+#[derive(Debug)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    trial_in_flight: bool,
+}
+
+/// Stops sending requests to the backend API after repeated failures.
+///
+/// After `failure_threshold` consecutive failures the circuit opens and requests fail
+/// immediately for `open_duration`. Then one trial request is let through: its success
+/// closes the circuit, its failure opens it again.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker.
+    ///
+    /// # Arguments
+    /// * `failure_threshold` - Consecutive failures that open the circuit, 0 disables the breaker.
+    /// * `open_duration` - How long the circuit stays open before a trial request.
+    pub(crate) fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold,
+            open_duration,
+            state: Mutex::new(BreakerState {
+                consecutive_failures: 0,
+                open_until: None,
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    /// Locks the shared state, recovering it if a holder panicked.
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Decides whether a request may be sent now.
+    ///
+    /// # Returns
+    /// The permit to send the request, whose outcome must be recorded through it, or the time
+    /// left before the next trial.
+    pub(crate) fn try_acquire(&self) -> Result<CircuitPermit<'_>, Duration> {
+        if self.failure_threshold == 0 {
+            return Ok(CircuitPermit::new(self, false));
+        }
+        let mut state = self.lock();
+        match state.open_until {
+            None => Ok(CircuitPermit::new(self, false)),
+            Some(open_until) => {
+                let now = Instant::now();
+                if now < open_until {
+                    Err(open_until - now)
+                } else if state.trial_in_flight {
+                    // Another request is already probing the backend API
+                    Err(Duration::ZERO)
+                } else {
+                    state.trial_in_flight = true;
+                    Ok(CircuitPermit::new(self, true))
+                }
+            }
+        }
+    }
+
+    /// Records that the backend API answered normally, which closes the circuit.
+    fn record_success(&self) {
+        let mut state = self.lock();
+        state.consecutive_failures = 0;
+        state.open_until = None;
+        state.trial_in_flight = false;
+    }
+
+    /// Records that the backend API could not be reached or was unavailable.
+    fn record_failure(&self) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.lock();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if state.trial_in_flight || state.consecutive_failures >= self.failure_threshold {
+            // An open period too long to be represented keeps the circuit open until the next
+            // failure, rather than panicking
+            let now = Instant::now();
+            state.open_until = Some(now.checked_add(self.open_duration).unwrap_or(now));
+        }
+        state.trial_in_flight = false;
+    }
+
+    /// Takes a snapshot of the circuit breaker.
+    pub(crate) fn status(&self) -> CircuitBreakerStatus {
+        let state = self.lock();
+        let now = Instant::now();
+        let (circuit_state, retry_after_secs) = match state.open_until {
+            None => (CircuitState::Closed, None),
+            Some(open_until) if now < open_until => (
+                CircuitState::Open,
+                Some((open_until - now).as_secs_f64().ceil() as u64),
+            ),
+            Some(_) => (CircuitState::HalfOpen, None),
+        };
+        CircuitBreakerStatus {
+            state: circuit_state,
+            consecutive_failures: state.consecutive_failures,
+            failure_threshold: self.failure_threshold,
+            retry_after_secs,
+        }
+    }
+}
+
+/// The permission to send one request, given by the circuit breaker.
+///
+/// A trial request dropped before its outcome was recorded, e.g. because the client of the
+/// Frontend went away, counts as a failure, so that it doesn't hold the trial slot forever.
+/// Other requests dropped that way leave the circuit breaker unchanged.
+#[derive(Debug)]
+pub(crate) struct CircuitPermit<'a> {
+    circuit_breaker: &'a CircuitBreaker,
+    trial: bool,
+    recorded: bool,
+}
+
+impl<'a> CircuitPermit<'a> {
+    /// Creates the permit of a request.
+    ///
+    /// # Arguments
+    /// * `circuit_breaker` - The circuit breaker that gave the permit.
+    /// * `trial` - Whether the request is the trial of a half-open circuit.
+    fn new(circuit_breaker: &'a CircuitBreaker, trial: bool) -> Self {
+        CircuitPermit {
+            circuit_breaker,
+            trial,
+            recorded: false,
+        }
+    }
+
+    /// Records that the backend API answered normally, which closes the circuit.
+    pub(crate) fn record_success(mut self) {
+        self.recorded = true;
+        self.circuit_breaker.record_success();
+    }
+
+    /// Records that the backend API could not be reached or was unavailable.
+    pub(crate) fn record_failure(mut self) {
+        self.recorded = true;
+        self.circuit_breaker.record_failure();
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.recorded {
+            self.circuit_breaker.record_failure();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, CircuitState};
+    use std::time::Duration;
+
+    /// Records the given number of failures, each through its own permit.
+    fn fail(circuit_breaker: &CircuitBreaker, failures: u32) {
+        for _ in 0..failures {
+            circuit_breaker
+                .try_acquire()
+                .expect("The circuit lets the request through")
+                .record_failure();
+        }
+    }
+
+    #[test]
+    fn the_circuit_opens_at_the_failure_threshold() {
+        let circuit_breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+        fail(&circuit_breaker, 2);
+        assert_eq!(circuit_breaker.status().state, CircuitState::Closed);
+        assert_eq!(circuit_breaker.status().consecutive_failures, 2);
+
+        fail(&circuit_breaker, 1);
+        let status = circuit_breaker.status();
+        assert_eq!(status.state, CircuitState::Open);
+        assert_eq!(status.retry_after_secs, Some(60));
+        let retry_after = circuit_breaker.try_acquire().unwrap_err();
+        assert!(retry_after > Duration::from_secs(59));
+    }
+
+    #[test]
+    fn a_success_resets_the_failure_count() {
+        let circuit_breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+        fail(&circuit_breaker, 1);
+        circuit_breaker.try_acquire().unwrap().record_success();
+        fail(&circuit_breaker, 1);
+        assert_eq!(circuit_breaker.status().state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn a_half_open_circuit_lets_a_single_trial_through() {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::ZERO);
+        fail(&circuit_breaker, 1);
+        assert_eq!(circuit_breaker.status().state, CircuitState::HalfOpen);
+
+        let trial = circuit_breaker
+            .try_acquire()
+            .expect("The trial is let through");
+        assert_eq!(circuit_breaker.try_acquire().unwrap_err(), Duration::ZERO);
+        trial.record_success();
+        assert_eq!(circuit_breaker.status().state, CircuitState::Closed);
+        assert!(circuit_breaker.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn a_failed_trial_opens_the_circuit_again() {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        fail(&circuit_breaker, 1);
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(circuit_breaker.status().state, CircuitState::HalfOpen);
+
+        circuit_breaker.try_acquire().unwrap().record_failure();
+        assert_eq!(circuit_breaker.status().state, CircuitState::Open);
+        assert!(circuit_breaker.try_acquire().unwrap_err() > Duration::ZERO);
+    }
+
+    #[test]
+    fn a_cancelled_trial_releases_the_trial_slot() {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        fail(&circuit_breaker, 1);
+        std::thread::sleep(Duration::from_millis(60));
+
+        drop(
+            circuit_breaker
+                .try_acquire()
+                .expect("The trial is let through"),
+        );
+        assert_eq!(circuit_breaker.status().state, CircuitState::Open);
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(circuit_breaker.try_acquire().is_ok());
+    }
+
+    #[test]
+    fn cancelled_requests_of_a_closed_circuit_are_not_failures() {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        drop(circuit_breaker.try_acquire().unwrap());
+        assert_eq!(circuit_breaker.status().state, CircuitState::Closed);
+        assert_eq!(circuit_breaker.status().consecutive_failures, 0);
+    }
+
+    #[test]
+    fn a_huge_open_duration_does_not_overflow() {
+        let circuit_breaker = CircuitBreaker::new(1, Duration::MAX);
+        fail(&circuit_breaker, 1);
+        assert_ne!(circuit_breaker.status().state, CircuitState::Closed);
+    }
+
+    #[test]
+    fn a_zero_threshold_disables_the_breaker() {
+        let circuit_breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+        fail(&circuit_breaker, 10);
+        assert_eq!(circuit_breaker.status().state, CircuitState::Closed);
+    }
+}
@@ -0,0 +1,147 @@
+use common::ErrorCode;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors returned by the questions client.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The base URL of the backend API is not a valid http:// or https:// URL.
+    InvalidBaseUrl(String),
+    /// The connection to the backend API could not be established.
+    Connect(reqwest::Error),
+    /// The backend API did not answer in time.
+    Timeout(reqwest::Error),
+    /// The request failed for another transport reason.
+    Request(reqwest::Error),
+    /// The question does not exist.
+    NotFound(String),
+    /// The backend API answered with an error status, code and message.
+    Api {
+        status: u16,
+        code: ErrorCode,
+        message: String,
+    },
+    /// The answer of the backend API is not the expected JSON.
+    Decode(String),
+    /// The circuit breaker is open after repeated failures, so the request was not sent.
+    CircuitOpen {
+        /// Time left before the next trial request is allowed.
+        retry_after: Duration,
+    },
+}
+
+impl ClientError {
+    /// Classifies a reqwest error into a connect, timeout or generic request error.
+    pub(crate) fn from_transport(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ClientError::Timeout(error)
+        } else if error.is_connect() {
+            ClientError::Connect(error)
+        } else if error.is_decode() {
+            ClientError::Decode(error.to_string())
+        } else {
+            ClientError::Request(error)
+        }
+    }
+
+    /// Whether the error means the backend API is down or overloaded,
+    /// as opposed to an answer about the request itself.
+    pub(crate) fn is_transport(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Connect(_) | ClientError::Timeout(_) | ClientError::Request(_)
+        )
+    }
+
+    /// A short name of the kind of error, for logs and metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClientError::InvalidBaseUrl(_) => "invalid_base_url",
+            ClientError::Connect(_) => "connect",
+            ClientError::Timeout(_) => "timeout",
+            ClientError::Request(_) => "request",
+            ClientError::NotFound(_) => "not_found",
+            ClientError::Api { .. } => "api",
+            ClientError::Decode(_) => "decode",
+            ClientError::CircuitOpen { .. } => "circuit_open",
+        }
+    }
+
+    /// The error code of the backend answer, when the backend answered at all.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            ClientError::NotFound(_) => Some(ErrorCode::QuestionNotFound),
+            ClientError::Api { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status of the backend answer, when the backend answered at all.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::NotFound(_) => Some(404),
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidBaseUrl(base_url) => {
+                write!(formatter, "Invalid backend API URL: {}", base_url)
+            }
+            ClientError::Connect(error) => {
+                write!(formatter, "Couldn't connect to the backend API: {}", error)
+            }
+            ClientError::Timeout(error) => {
+                write!(
+                    formatter,
+                    "The backend API did not answer in time: {}",
+                    error
+                )
+            }
+            ClientError::Request(error) => {
+                write!(formatter, "Request to the backend API failed: {}", error)
+            }
+            ClientError::NotFound(message) => write!(formatter, "{}", message),
+            ClientError::Api {
+                status,
+                code,
+                message,
+            } => {
+                write!(
+                    formatter,
+                    "Backend API error {} ({}): {}",
+                    status, code, message
+                )
+            }
+            ClientError::Decode(message) => {
+                write!(
+                    formatter,
+                    "Invalid answer from the backend API: {}",
+                    message
+                )
+            }
+            ClientError::CircuitOpen { retry_after } => {
+                write!(
+                    formatter,
+                    "The backend API is failing, requests are paused for {} more seconds",
+                    retry_after.as_secs_f64().ceil() as u64
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Connect(error)
+            | ClientError::Timeout(error)
+            | ClientError::Request(error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Typed async client for the questions backend API.
+//!
+//! Shared by the Frontend and by scripts that need to talk to the Backend.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), questions_client::ClientError> {
+//! let client = questions_client::QuestionsClient::new("http://localhost:1000")?;
+//! for question in client.list().await? {
+//!     println!("{}: {}", question.question_id, question.question_title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Circuit breaker that pauses requests while the backend API keeps failing.
+mod circuit_breaker;
+
+/// Errors returned by the client.
+mod client_error;
+
+/// The client and its options.
+mod questions_client;
+
+/// The request ID and client addresses propagated to the backend API.
+mod request_context;
+
+pub use circuit_breaker::{CircuitBreakerStatus, CircuitState};
+pub use client_error::ClientError;
+pub use common::{
+    ErrorCode, ErrorResponse, MessageResponse, NewQuestion, PatchOperation, Question,
+    QuestionEvent, QuestionEventKind, ReadinessCheck, ReadinessReport,
+};
+pub use questions_client::{
+    ClientOptions, EventStream, QuestionsClient, RawResponse, BACKEND_CALL_TARGET,
+};
+pub use request_context::{
+    current_forwarded_for, current_request_id, with_forwarded_for, with_request_id,
+};
+pub use reqwest::header::{HeaderMap, HeaderValue};
+pub use reqwest::{Method, StatusCode};
@@ -0,0 +1,669 @@
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerStatus};
+use crate::client_error::ClientError;
+use crate::request_context::{current_forwarded_for, current_request_id};
+use common::route_paths::{question_path, EVENTS_ROUTE, QUESTIONS_ROUTE, READINESS_ROUTE};
+use common::{
+    ErrorCode, ErrorResponse, MessageResponse, NewQuestion, PatchOperation, Question,
+    ReadinessReport, JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE, REQUEST_ID_HEADER,
+};
+use serde_json::Value;
+
+/// Target of the debug event logged for every attempt to call the backend API, with its
+/// `method`, `url`, `outcome` (the status code, or the kind of error) and `elapsed_secs`.
+pub const BACKEND_CALL_TARGET: &str = "questions_client::backend_call";
+
+/// Longest silence tolerated on an event stream, which the backend API breaks with a
+/// keep-alive comment every 15 seconds.
+const EVENT_STREAM_READ_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Timeouts, retries, circuit breaker, connection pool and TLS settings of the questions client.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Time to wait for a complete answer from the backend API, for each attempt.
+    pub timeout: Duration,
+    /// Time to wait while connecting to the backend API.
+    pub connect_timeout: Duration,
+    /// Maximum number of idle connections kept open to the backend API.
+    pub pool_max_idle_per_host: usize,
+    /// Number of times an idempotent request is sent again after a transport failure
+    /// or a 502, 503 or 504 answer.
+    pub max_retries: u32,
+    /// Base of the exponential backoff between retries.
+    pub retry_base_delay: Duration,
+    /// Upper bound of the backoff between retries.
+    pub retry_max_delay: Duration,
+    /// Consecutive failures that open the circuit breaker, 0 disables it.
+    pub circuit_failure_threshold: u32,
+    /// How long the circuit breaker stays open before a trial request.
+    pub circuit_open_duration: Duration,
+    /// PEM certificate authority trusted, besides the system ones, to sign the certificate
+    /// of an `https://` backend API.
+    pub root_certificate_pem: Option<Vec<u8>>,
+    /// PEM certificate chain followed by its private key, presented to a backend API that
+    /// requires client certificates.
+    pub identity_pem: Option<Vec<u8>>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(5),
+            pool_max_idle_per_host: 16,
+            max_retries: 2,
+            retry_base_delay: Duration::from_millis(100),
+            retry_max_delay: Duration::from_secs(2),
+            circuit_failure_threshold: 5,
+            circuit_open_duration: Duration::from_secs(10),
+            root_certificate_pem: None,
+            identity_pem: None,
+        }
+    }
+}
+
+/// An answer of the backend API that has not been interpreted.
+#[derive(Debug, Clone)]
+pub struct RawResponse {
+    /// The HTTP status of the answer.
+    pub status: StatusCode,
+    /// The headers of the answer.
+    pub headers: HeaderMap,
+    /// The body of the answer.
+    pub body: Vec<u8>,
+}
+
+/// The server-sent events of the backend API, read as they arrive.
+#[derive(Debug)]
+pub struct EventStream {
+    response: reqwest::Response,
+}
+
+impl EventStream {
+    /// Waits for the next chunk of the stream, which holds whole or partial events.
+    ///
+    /// # Returns
+    /// The chunk, `None` once the backend API ended the stream, or an error when the
+    /// connection failed or stayed silent for too long.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, ClientError> {
+        let chunk = self
+            .response
+            .chunk()
+            .await
+            .map_err(ClientError::from_transport)?;
+        Ok(chunk.map(|chunk| chunk.to_vec()))
+    }
+}
+
+/// Typed async client for the questions backend API.
+///
+/// The client keeps a pool of connections and a circuit breaker, so it should be created
+/// once and shared (it is cheap to clone, clones share both) instead of being created
+/// for every request.
+#[derive(Debug, Clone)]
+pub struct QuestionsClient {
+    base_url: String,
+    http_client: reqwest::Client,
+    event_stream_client: reqwest::Client,
+    options: ClientOptions,
+    circuit_breaker: Arc<CircuitBreaker>,
+}
+
+impl QuestionsClient {
+    /// Creates a client with the default timeouts.
+    ///
+    /// # Arguments
+    /// * `base_url` - The base URL of the backend API, e.g. `http://localhost:1000`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::with_options(base_url, ClientOptions::default())
+    }
+
+    /// Creates a client with the given timeouts, retries, circuit breaker, pool and TLS settings.
+    ///
+    /// # Arguments
+    /// * `base_url` - The base URL of the backend API, e.g. `http://localhost:1000`.
+    /// * `options` - The timeouts, retries, circuit breaker, connection pool and TLS settings.
+    pub fn with_options(base_url: &str, options: ClientOptions) -> Result<Self, ClientError> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        match reqwest::Url::parse(&base_url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ => return Err(ClientError::InvalidBaseUrl(base_url)),
+        }
+
+        let http_client = Self::http_client_builder(&options)?
+            .timeout(options.timeout)
+            .build()
+            .map_err(ClientError::Request)?;
+        // Event streams last as long as the browser keeps the page open, so only their silences are limited
+        let event_stream_client = Self::http_client_builder(&options)?
+            .read_timeout(EVENT_STREAM_READ_TIMEOUT)
+            .build()
+            .map_err(ClientError::Request)?;
+
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            options.circuit_failure_threshold,
+            options.circuit_open_duration,
+        ));
+        Ok(QuestionsClient {
+            base_url,
+            http_client,
+            event_stream_client,
+            options,
+            circuit_breaker,
+        })
+    }
+
+    /// The connection, pool and TLS settings shared by the HTTP clients.
+    ///
+    /// # Arguments
+    /// * `options` - The settings of the questions client.
+    fn http_client_builder(options: &ClientOptions) -> Result<reqwest::ClientBuilder, ClientError> {
+        let mut client_builder = reqwest::Client::builder()
+            .connect_timeout(options.connect_timeout)
+            .pool_max_idle_per_host(options.pool_max_idle_per_host);
+        if let Some(root_certificate_pem) = &options.root_certificate_pem {
+            let root_certificate = reqwest::Certificate::from_pem(root_certificate_pem)
+                .map_err(ClientError::Request)?;
+            client_builder = client_builder
+                .use_rustls_tls()
+                .add_root_certificate(root_certificate);
+        }
+        if let Some(identity_pem) = &options.identity_pem {
+            let identity =
+                reqwest::Identity::from_pem(identity_pem).map_err(ClientError::Request)?;
+            client_builder = client_builder.use_rustls_tls().identity(identity);
+        }
+        Ok(client_builder)
+    }
+
+    /// The base URL of the backend API, without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// A snapshot of the circuit breaker shared by this client and its clones.
+    pub fn circuit_status(&self) -> CircuitBreakerStatus {
+        self.circuit_breaker.status()
+    }
+
+    /// Asks the backend API whether it is ready to take traffic.
+    ///
+    /// The probe is sent once, without retries and around the circuit breaker, so that it
+    /// reports the current state of the backend API and doesn't change the breaker.
+    ///
+    /// # Returns
+    /// The readiness report of the backend API, which is not ready when it answered
+    /// `503 Service Unavailable`, or an error when it could not be reached or understood.
+    pub async fn readiness(&self) -> Result<ReadinessReport, ClientError> {
+        let raw_response = self
+            .send_once(
+                Method::GET,
+                &format!("{}{}", self.base_url, READINESS_ROUTE),
+                &HeaderMap::new(),
+                None::<&()>,
+            )
+            .await?;
+        if raw_response.status == StatusCode::SERVICE_UNAVAILABLE {
+            return serde_json::from_slice(&raw_response.body)
+                .map_err(|error| ClientError::Decode(error.to_string()));
+        }
+        Self::read_json(raw_response)
+    }
+
+    /// Retrieves every question.
+    pub async fn list(&self) -> Result<Vec<Question>, ClientError> {
+        self.send_json(Method::GET, QUESTIONS_ROUTE, None::<&()>)
+            .await
+    }
+
+    /// Retrieves a question by its ID.
+    ///
+    /// # Returns
+    /// The question, or `ClientError::NotFound` when it does not exist.
+    pub async fn get(&self, question_id: i32) -> Result<Question, ClientError> {
+        self.send_json(Method::GET, &question_path(question_id), None::<&()>)
+            .await
+    }
+
+    /// Adds a new question.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn create(&self, new_question: &NewQuestion) -> Result<String, ClientError> {
+        let response: MessageResponse = self
+            .send_json(Method::POST, QUESTIONS_ROUTE, Some(new_question))
+            .await?;
+        Ok(response.message)
+    }
+
+    /// Replaces every field of the question with the given ID.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn replace(
+        &self,
+        question_id: i32,
+        replacement: &NewQuestion,
+    ) -> Result<String, ClientError> {
+        let response: MessageResponse = self
+            .send_json(Method::PUT, &question_path(question_id), Some(replacement))
+            .await?;
+        Ok(response.message)
+    }
+
+    /// Updates the question with the given ID, sent as a merge patch of every field.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn update(
+        &self,
+        question_id: i32,
+        updated_question: &NewQuestion,
+    ) -> Result<String, ClientError> {
+        let response: MessageResponse = self
+            .send_json(
+                Method::PATCH,
+                &question_path(question_id),
+                Some(updated_question),
+            )
+            .await?;
+        Ok(response.message)
+    }
+
+    /// Changes some fields of the question with the given ID with an RFC 7396 JSON Merge Patch.
+    ///
+    /// # Arguments
+    /// * `question_id` - The ID of the question.
+    /// * `merge_patch` - The fields to change, `null` clearing `type_of_content` or `type_of_question`.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn merge_patch(
+        &self,
+        question_id: i32,
+        merge_patch: &Value,
+    ) -> Result<String, ClientError> {
+        self.send_patch(question_id, MERGE_PATCH_CONTENT_TYPE, merge_patch)
+            .await
+    }
+
+    /// Changes the question with the given ID with an RFC 6902 JSON Patch.
+    ///
+    /// # Arguments
+    /// * `question_id` - The ID of the question.
+    /// * `operations` - The operations, applied all or nothing.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn json_patch(
+        &self,
+        question_id: i32,
+        operations: &[PatchOperation],
+    ) -> Result<String, ClientError> {
+        self.send_patch(question_id, JSON_PATCH_CONTENT_TYPE, &operations)
+            .await
+    }
+
+    /// Sends a PATCH request with the given `Content-Type`.
+    async fn send_patch(
+        &self,
+        question_id: i32,
+        content_type: &'static str,
+        patch: &impl Serialize,
+    ) -> Result<String, ClientError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        let raw_response = self
+            .send_raw_with_headers(
+                Method::PATCH,
+                &question_path(question_id),
+                &headers,
+                Some(patch),
+            )
+            .await?;
+        let response: MessageResponse = Self::read_json(raw_response)?;
+        Ok(response.message)
+    }
+
+    /// Deletes the question with the given ID.
+    ///
+    /// # Returns
+    /// The success message of the backend API.
+    pub async fn delete(&self, question_id: i32) -> Result<String, ClientError> {
+        let response: MessageResponse = self
+            .send_json(Method::DELETE, &question_path(question_id), None::<&()>)
+            .await?;
+        Ok(response.message)
+    }
+
+    /// Searches questions whose title, content or types contain the text, or whose ID equals it.
+    pub async fn search(&self, search_text: &str) -> Result<Vec<Question>, ClientError> {
+        let mut search_url = reqwest::Url::parse(&self.base_url)
+            .map_err(|_| ClientError::InvalidBaseUrl(self.base_url.clone()))?;
+        search_url.set_path(&format!(
+            "{}{}",
+            search_url.path().trim_end_matches('/'),
+            QUESTIONS_ROUTE
+        ));
+        search_url.query_pairs_mut().append_pair("q", search_text);
+        let raw_response = self
+            .send(
+                Method::GET,
+                search_url.as_str(),
+                &HeaderMap::new(),
+                None::<&()>,
+            )
+            .await?;
+        Self::read_json(raw_response)
+    }
+
+    /// Opens the stream of the changes of the questions, as server-sent events.
+    ///
+    /// The stream is opened once, without retries and around the circuit breaker, since the
+    /// browser reconnects by itself and a long-lived stream says little about the backend API.
+    ///
+    /// # Arguments
+    /// * `last_event_id` - The `Last-Event-ID` of a reconnecting browser, to get the events it missed.
+    ///
+    /// # Returns
+    /// The open stream, or an error when the backend API could not be reached or refused it.
+    pub async fn events(&self, last_event_id: Option<&str>) -> Result<EventStream, ClientError> {
+        let mut headers = HeaderMap::new();
+        if let Some(last_event_id) =
+            last_event_id.and_then(|last_event_id| HeaderValue::from_str(last_event_id).ok())
+        {
+            headers.insert("last-event-id", last_event_id);
+        }
+        let response = self
+            .request(
+                &self.event_stream_client,
+                Method::GET,
+                &format!("{}{}", self.base_url, EVENTS_ROUTE),
+                &headers,
+            )
+            .send()
+            .await
+            .map_err(ClientError::from_transport)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .bytes()
+                .await
+                .map_err(ClientError::from_transport)?;
+            return Err(Self::error_from_answer(status, &body));
+        }
+        Ok(EventStream { response })
+    }
+
+    /// Sends a request and returns the backend answer as is, whatever its status.
+    ///
+    /// Used by proxies that must forward the status, headers and body of the backend.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `path_and_query` - The path of the endpoint, with its query string if any.
+    /// * `body` - The optional JSON body of the request.
+    ///
+    /// # Returns
+    /// The raw backend answer, or an error when the backend could not be reached.
+    pub async fn send_raw(
+        &self,
+        method: Method,
+        path_and_query: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<RawResponse, ClientError> {
+        self.send_raw_with_headers(method, path_and_query, &HeaderMap::new(), body)
+            .await
+    }
+
+    /// Sends a request with extra headers and returns the backend answer as is.
+    ///
+    /// Used for conditional requests, e.g. with `If-None-Match`.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `path_and_query` - The path of the endpoint, with its query string if any.
+    /// * `headers` - The headers added to the request.
+    /// * `body` - The optional JSON body of the request.
+    ///
+    /// # Returns
+    /// The raw backend answer, or an error when the backend could not be reached.
+    pub async fn send_raw_with_headers(
+        &self,
+        method: Method,
+        path_and_query: &str,
+        headers: &HeaderMap,
+        body: Option<&impl Serialize>,
+    ) -> Result<RawResponse, ClientError> {
+        self.send(
+            method,
+            &format!("{}{}", self.base_url, path_and_query),
+            headers,
+            body,
+        )
+        .await
+    }
+
+    /// Sends a request with an optional JSON body and parses the JSON answer.
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<T, ClientError> {
+        let raw_response = self
+            .send(
+                method,
+                &format!("{}{}", self.base_url, path),
+                &HeaderMap::new(),
+                body,
+            )
+            .await?;
+        Self::read_json(raw_response)
+    }
+
+    /// Sends a request through the circuit breaker, retrying idempotent requests.
+    ///
+    /// Transport failures and 502, 503 or 504 answers count as failures of the backend API.
+    /// Idempotent requests are retried after a jittered exponential backoff, while the
+    /// circuit breaker makes requests fail immediately once the backend API keeps failing.
+    ///
+    /// # Arguments
+    /// * `method` - The HTTP method of the request.
+    /// * `url` - The full URL of the endpoint.
+    /// * `headers` - The headers added to the request.
+    /// * `body` - The optional JSON body of the request.
+    ///
+    /// # Returns
+    /// The backend answer, or the error of the last attempt.
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &HeaderMap,
+        body: Option<&impl Serialize>,
+    ) -> Result<RawResponse, ClientError> {
+        let max_retries = if is_idempotent(&method) {
+            self.options.max_retries
+        } else {
+            0
+        };
+        let mut attempt = 0;
+        loop {
+            let circuit_permit = match self.circuit_breaker.try_acquire() {
+                Ok(circuit_permit) => circuit_permit,
+                Err(retry_after) => {
+                    let error = ClientError::CircuitOpen { retry_after };
+                    tracing::debug!(
+                        target: BACKEND_CALL_TARGET,
+                        %method,
+                        url,
+                        outcome = error.kind(),
+                        "Backend request not sent"
+                    );
+                    return Err(error);
+                }
+            };
+
+            let started_at = Instant::now();
+            let outcome = self.send_once(method.clone(), url, headers, body).await;
+            tracing::debug!(
+                target: BACKEND_CALL_TARGET,
+                %method,
+                url,
+                outcome = match &outcome {
+                    Ok(raw_response) => raw_response.status.as_str(),
+                    Err(error) => error.kind(),
+                },
+                elapsed_secs = started_at.elapsed().as_secs_f64(),
+                "Backend request finished"
+            );
+            let backend_failed = match &outcome {
+                Ok(raw_response) => is_unavailable_status(raw_response.status),
+                Err(error) => error.is_transport(),
+            };
+            if !backend_failed {
+                circuit_permit.record_success();
+                return outcome;
+            }
+            circuit_permit.record_failure();
+            if attempt >= max_retries {
+                return outcome;
+            }
+            let retry_delay = self.retry_delay(attempt);
+            tracing::warn!(
+                %method,
+                url,
+                attempt = attempt + 1,
+                retry_delay_ms = retry_delay.as_millis() as u64,
+                "Backend request failed, retrying"
+            );
+            tokio::time::sleep(retry_delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Builds a request carrying the request ID and the client addresses of the current request.
+    ///
+    /// # Arguments
+    /// * `http_client` - The HTTP client sending the request.
+    /// * `method` - The HTTP method of the request.
+    /// * `url` - The full URL of the endpoint.
+    /// * `headers` - The headers added to the request.
+    fn request(
+        &self,
+        http_client: &reqwest::Client,
+        method: Method,
+        url: &str,
+        headers: &HeaderMap,
+    ) -> reqwest::RequestBuilder {
+        let mut request = http_client.request(method, url).headers(headers.clone());
+        if let Some(request_id) =
+            current_request_id().and_then(|request_id| HeaderValue::from_str(&request_id).ok())
+        {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+        if let Some(forwarded_for) = current_forwarded_for()
+            .and_then(|forwarded_for| HeaderValue::from_str(&forwarded_for).ok())
+        {
+            request = request.header("x-forwarded-for", forwarded_for);
+        }
+        request
+    }
+
+    /// Sends a single attempt of a request and reads the whole answer.
+    async fn send_once(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &HeaderMap,
+        body: Option<&impl Serialize>,
+    ) -> Result<RawResponse, ClientError> {
+        let mut request = self.request(&self.http_client, method, url, headers);
+        if let Some(body_data) = body {
+            request = request.json(body_data);
+        }
+        let response = request.send().await.map_err(ClientError::from_transport)?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response
+            .bytes()
+            .await
+            .map_err(ClientError::from_transport)?
+            .to_vec();
+        Ok(RawResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    /// The "full jitter" backoff before a retry: a random delay up to the exponential bound.
+    ///
+    /// # Arguments
+    /// * `attempt` - The number of the attempt that just failed, starting at 0.
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let exponential_delay = self
+            .options
+            .retry_base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.options.retry_max_delay);
+        let max_millis = exponential_delay.as_millis() as u64;
+        Duration::from_millis(fastrand::u64(0..=max_millis))
+    }
+
+    /// Turns the backend answer into the expected type or a typed error.
+    fn read_json<T: DeserializeOwned>(raw_response: RawResponse) -> Result<T, ClientError> {
+        let status = raw_response.status;
+        let body = raw_response.body;
+
+        if status.is_success() {
+            return serde_json::from_slice(&body)
+                .map_err(|error| ClientError::Decode(error.to_string()));
+        }
+        Err(Self::error_from_answer(status, &body))
+    }
+
+    /// Turns an error answer of the backend into a typed error.
+    ///
+    /// # Arguments
+    /// * `status` - The error status of the answer.
+    /// * `body` - The body of the answer.
+    fn error_from_answer(status: StatusCode, body: &[u8]) -> ClientError {
+        // Use the `{"error": ..., "code": ...}` answer of the backend when there is one.
+        let error_response = serde_json::from_slice::<ErrorResponse>(body).unwrap_or_else(|_| {
+            ErrorResponse::new(ErrorCode::Unknown, String::from_utf8_lossy(body))
+        });
+        if status == StatusCode::NOT_FOUND {
+            ClientError::NotFound(error_response.error)
+        } else {
+            ClientError::Api {
+                status: status.as_u16(),
+                code: error_response.code,
+                message: error_response.error,
+            }
+        }
+    }
+}
+
+/// Whether sending the request twice has the same effect as sending it once.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+/// Whether the status means the backend API is down or overloaded.
+fn is_unavailable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
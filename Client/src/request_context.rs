@@ -0,0 +1,44 @@
+use std::future::Future;
+
+tokio::task_local! {
+    /// The ID of the request being handled by the task, sent along with every backend call.
+    static REQUEST_ID: String;
+}
+
+/// Runs a future with a request ID, which the client sends as `x-request-id` on every call
+/// made by that future, so the Backend logs can be matched with those of the caller.
+///
+/// # Arguments
+/// * `request_id` - The ID of the request, e.g. generated by the Frontend for each browser request.
+/// * `future` - The work done for the request.
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(request_id, future).await
+}
+
+/// The request ID of the current task, if it runs inside `with_request_id`.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|request_id| request_id.clone()).ok()
+}
+
+tokio::task_local! {
+    /// The addresses the request handled by the task came through, the client first.
+    static FORWARDED_FOR: String;
+}
+
+/// Runs a future with the addresses of the client and of the proxies before the caller,
+/// which the client sends as `x-forwarded-for` on every call made by that future, so the
+/// Backend can tell the clients of a proxy apart, e.g. for its rate limit.
+///
+/// # Arguments
+/// * `forwarded_for` - Comma separated addresses, the client first, e.g. `203.0.113.7`.
+/// * `future` - The work done for the request.
+pub async fn with_forwarded_for<F: Future>(forwarded_for: String, future: F) -> F::Output {
+    FORWARDED_FOR.scope(forwarded_for, future).await
+}
+
+/// The forwarded addresses of the current task, if it runs inside `with_forwarded_for`.
+pub fn current_forwarded_for() -> Option<String> {
+    FORWARDED_FOR
+        .try_with(|forwarded_for| forwarded_for.clone())
+        .ok()
+}
@@ -0,0 +1,129 @@
+//! Embeds the static assets of `templates/` into the Frontend binary.
+//!
+//! Every asset gets a content hash, `index.html` is rewritten to reference the hashed
+//! names of the stylesheet and the script, and gzip and brotli variants are precompressed.
+//! The generated `embedded_assets.rs` is included by `src/static_assets.rs`.
+
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+/// Assets referenced by `index.html`, with their content type.
+const REFERENCED_ASSETS: [(&str, &str); 2] = [
+    ("index.css", "text/css; charset=utf-8"),
+    ("index.js", "text/javascript; charset=utf-8"),
+];
+
+/// The entry page, which keeps its name so that `/` can serve it.
+const ENTRY_PAGE: (&str, &str) = ("index.html", "text/html; charset=utf-8");
+
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by Cargo");
+    let out_dir = Path::new(&out_dir);
+    let mut generated = String::from("pub(crate) static EMBEDDED_ASSETS: &[EmbeddedAsset] = &[\n");
+
+    // Hash the referenced assets first, so the entry page can point at their hashed names
+    let mut entry_page = read_template(ENTRY_PAGE.0);
+    for (file_name, content_type) in REFERENCED_ASSETS {
+        let body = read_template(file_name).into_bytes();
+        let content_hash = content_hash(&body);
+        let hashed_name = hashed_file_name(file_name, &content_hash);
+        entry_page = entry_page
+            .replace(
+                &format!("href=\"{}\"", file_name),
+                &format!("href=\"/{}\"", hashed_name),
+            )
+            .replace(
+                &format!("src=\"{}\"", file_name),
+                &format!("src=\"/{}\"", hashed_name),
+            );
+        write_asset(out_dir, &mut generated, file_name, content_type, &body);
+    }
+    write_asset(
+        out_dir,
+        &mut generated,
+        ENTRY_PAGE.0,
+        ENTRY_PAGE.1,
+        entry_page.as_bytes(),
+    );
+
+    generated.push_str("];\n");
+    std::fs::write(out_dir.join("embedded_assets.rs"), generated)
+        .expect("Couldn't write embedded_assets.rs");
+}
+
+/// Reads an asset of `templates/` and asks Cargo to rebuild when it changes.
+fn read_template(file_name: &str) -> String {
+    let template_path = Path::new("templates").join(file_name);
+    println!("cargo:rerun-if-changed={}", template_path.display());
+    std::fs::read_to_string(&template_path)
+        .unwrap_or_else(|error| panic!("Couldn't read {}: {}", template_path.display(), error))
+}
+
+/// The first 16 hexadecimal digits of the SHA-256 of the content.
+fn content_hash(body: &[u8]) -> String {
+    Sha256::digest(body)
+        .iter()
+        .take(8)
+        .fold(String::new(), |mut hash, byte| {
+            let _ = write!(hash, "{:02x}", byte);
+            hash
+        })
+}
+
+/// Inserts the hash before the extension, e.g. `index.css` becomes `index.0123abcd.css`.
+fn hashed_file_name(file_name: &str, content_hash: &str) -> String {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}.{}.{}", stem, content_hash, extension),
+        None => format!("{}.{}", file_name, content_hash),
+    }
+}
+
+/// Writes the asset and its compressed variants to `OUT_DIR` and adds its entry to the table.
+fn write_asset(
+    out_dir: &Path,
+    generated: &mut String,
+    file_name: &str,
+    content_type: &str,
+    body: &[u8],
+) {
+    let content_hash = content_hash(body);
+
+    let mut gzip_encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    gzip_encoder
+        .write_all(body)
+        .expect("Couldn't gzip the asset");
+    let gzip_body = gzip_encoder.finish().expect("Couldn't gzip the asset");
+
+    let mut brotli_body = Vec::new();
+    {
+        let mut brotli_encoder = brotli::CompressorWriter::new(&mut brotli_body, 4096, 11, 22);
+        brotli_encoder
+            .write_all(body)
+            .expect("Couldn't compress the asset with brotli");
+    }
+
+    for (suffix, content) in [
+        ("", body),
+        (".gz", &gzip_body[..]),
+        (".br", &brotli_body[..]),
+    ] {
+        std::fs::write(out_dir.join(format!("{}{}", file_name, suffix)), content)
+            .expect("Couldn't write the embedded asset");
+    }
+
+    let _ = writeln!(
+        generated,
+        "    EmbeddedAsset {{
+        file_name: {file_name:?},
+        hashed_name: {hashed_name:?},
+        content_type: {content_type:?},
+        content_hash: {content_hash:?},
+        body: include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_name}\")),
+        gzip_body: include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_name}.gz\")),
+        brotli_body: include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{file_name}.br\")),
+    }},",
+        hashed_name = hashed_file_name(file_name, &content_hash),
+    );
+}
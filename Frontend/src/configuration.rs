@@ -0,0 +1,599 @@
+use crate::presence::MAX_LOCK_TTL_SECS;
+use crate::response_cache::{CacheSettings, MAX_CACHE_LIFETIME_SECS};
+use clap::Parser;
+use common::tls::TlsFiles;
+use questions_client::ClientOptions;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Longest the circuit breaker may stay open before a trial request, in seconds: one hour.
+const MAX_BACKEND_CIRCUIT_OPEN_SECS: u64 = 60 * 60;
+
+/// Settings of the frontend server.
+///
+/// Values are layered from lowest to highest priority: built-in defaults,
+/// the TOML configuration file, environment variables and command line flags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontendConfiguration {
+    /// Address the HTTP server listens on.
+    pub bind_address: SocketAddr,
+    /// PEM certificate chain served over HTTPS; with `tls_key_path`, the server listens for
+    /// HTTPS instead of HTTP. The files are reloaded when they change.
+    pub tls_cert_path: Option<PathBuf>,
+    /// PEM private key of the certificate.
+    pub tls_key_path: Option<PathBuf>,
+    /// Address of a plain HTTP listener redirecting every request to HTTPS.
+    pub tls_redirect_bind_address: Option<SocketAddr>,
+    /// Base URL of the backend API, without a trailing slash.
+    pub backend_url: String,
+    /// PEM certificate authority trusted to sign the certificate of an `https://` backend API,
+    /// besides the system ones.
+    pub backend_ca_path: Option<PathBuf>,
+    /// PEM client certificate presented to a backend API that requires one (mutual TLS).
+    pub backend_client_cert_path: Option<PathBuf>,
+    /// PEM private key of the client certificate.
+    pub backend_client_key_path: Option<PathBuf>,
+    /// Seconds to wait for a complete answer from the backend API.
+    pub backend_timeout_secs: u64,
+    /// Seconds to wait while connecting to the backend API.
+    pub backend_connect_timeout_secs: u64,
+    /// Number of retries of idempotent backend requests that failed or got a 502, 503 or 504.
+    pub backend_max_retries: u32,
+    /// Milliseconds of the first backoff between retries, doubled on every retry.
+    pub backend_retry_base_delay_ms: u64,
+    /// Upper bound in milliseconds of the backoff between retries.
+    pub backend_retry_max_delay_ms: u64,
+    /// Consecutive backend failures that open the circuit breaker, 0 disables it.
+    pub backend_circuit_failure_threshold: u32,
+    /// Seconds the circuit breaker stays open before a trial request.
+    pub backend_circuit_open_secs: u64,
+    /// Seconds a backend answer stays cached when the backend gives no `max-age`, 0 disables the cache.
+    pub cache_ttl_secs: u64,
+    /// Maximum number of cached backend answers.
+    pub cache_max_entries: usize,
+    /// Maximum total size in bytes of the cached backend answers.
+    pub cache_max_bytes: usize,
+    /// Directory whose `index.html`, `index.css` and `index.js` replace the embedded ones,
+    /// read on every request, for development.
+    pub assets_dir: Option<PathBuf>,
+    /// Seconds to wait for the requests being answered after SIGTERM or SIGINT.
+    pub shutdown_timeout_secs: u64,
+    /// Largest request body accepted, in bytes; larger ones are answered `413 Payload Too Large`.
+    pub max_body_bytes: u64,
+    /// Compress the answers with gzip, Brotli or zstd when the client accepts it (true), or not (false).
+    pub compression_enabled: bool,
+    /// Smallest answer compressed, in bytes, as compressing tiny answers costs more than it saves.
+    pub compression_min_bytes: u16,
+    /// Seconds a soft lock of the update form lasts without being renewed by its holder.
+    pub presence_lock_ttl_secs: u64,
+    /// Format of the log lines.
+    pub log_format: LogFormat,
+    /// Which logs are written, in the `tracing_subscriber::EnvFilter` syntax,
+    /// e.g. `info,questions_client=debug`.
+    pub log_filter: String,
+}
+
+/// Format of the log lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable lines, for development.
+    Pretty,
+    /// One JSON object per line, for log collectors.
+    Json,
+}
+
+impl Default for FrontendConfiguration {
+    fn default() -> Self {
+        FrontendConfiguration {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 2000)),
+            tls_cert_path: None,
+            tls_key_path: None,
+            tls_redirect_bind_address: None,
+            backend_url: "http://localhost:1000".to_string(),
+            backend_ca_path: None,
+            backend_client_cert_path: None,
+            backend_client_key_path: None,
+            backend_timeout_secs: 30,
+            backend_connect_timeout_secs: 5,
+            backend_max_retries: 2,
+            backend_retry_base_delay_ms: 100,
+            backend_retry_max_delay_ms: 2000,
+            backend_circuit_failure_threshold: 5,
+            backend_circuit_open_secs: 10,
+            cache_ttl_secs: 5,
+            cache_max_entries: 256,
+            cache_max_bytes: 8 * 1024 * 1024,
+            assets_dir: None,
+            shutdown_timeout_secs: 30,
+            max_body_bytes: 1024 * 1024,
+            compression_enabled: true,
+            compression_min_bytes: 1024,
+            presence_lock_ttl_secs: 60,
+            log_format: LogFormat::Pretty,
+            log_filter: "info".to_string(),
+        }
+    }
+}
+
+/// Command line flags of the frontend server.
+/// Every flag can also be set through the environment variable named next to it.
+#[derive(Debug, Parser)]
+#[command(name = "Frontend", about = "Web interface for the questions database")]
+pub struct FrontendArguments {
+    /// Path of a TOML configuration file.
+    #[arg(long, env = "FRONTEND_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Address the HTTP server listens on, e.g. 0.0.0.0:2000.
+    #[arg(long, env = "FRONTEND_BIND_ADDRESS")]
+    pub bind_address: Option<SocketAddr>,
+
+    /// PEM certificate chain, to serve HTTPS instead of HTTP.
+    #[arg(long, env = "FRONTEND_TLS_CERT_PATH")]
+    pub tls_cert_path: Option<PathBuf>,
+
+    /// PEM private key of the certificate.
+    #[arg(long, env = "FRONTEND_TLS_KEY_PATH")]
+    pub tls_key_path: Option<PathBuf>,
+
+    /// Address of a plain HTTP listener redirecting to HTTPS, e.g. 0.0.0.0:80.
+    #[arg(long, env = "FRONTEND_TLS_REDIRECT_BIND_ADDRESS")]
+    pub tls_redirect_bind_address: Option<SocketAddr>,
+
+    /// Base URL of the backend API, e.g. http://localhost:1000.
+    #[arg(long, env = "FRONTEND_BACKEND_URL")]
+    pub backend_url: Option<String>,
+
+    /// PEM certificate authority trusted to sign the certificate of an https:// backend API.
+    #[arg(long, env = "FRONTEND_BACKEND_CA_PATH")]
+    pub backend_ca_path: Option<PathBuf>,
+
+    /// PEM client certificate presented to the backend API (mutual TLS).
+    #[arg(long, env = "FRONTEND_BACKEND_CLIENT_CERT_PATH")]
+    pub backend_client_cert_path: Option<PathBuf>,
+
+    /// PEM private key of the client certificate.
+    #[arg(long, env = "FRONTEND_BACKEND_CLIENT_KEY_PATH")]
+    pub backend_client_key_path: Option<PathBuf>,
+
+    /// Seconds to wait for a complete answer from the backend API.
+    #[arg(long, env = "FRONTEND_BACKEND_TIMEOUT_SECS")]
+    pub backend_timeout_secs: Option<u64>,
+
+    /// Seconds to wait while connecting to the backend API.
+    #[arg(long, env = "FRONTEND_BACKEND_CONNECT_TIMEOUT_SECS")]
+    pub backend_connect_timeout_secs: Option<u64>,
+
+    /// Number of retries of idempotent backend requests.
+    #[arg(long, env = "FRONTEND_BACKEND_MAX_RETRIES")]
+    pub backend_max_retries: Option<u32>,
+
+    /// Milliseconds of the first backoff between retries.
+    #[arg(long, env = "FRONTEND_BACKEND_RETRY_BASE_DELAY_MS")]
+    pub backend_retry_base_delay_ms: Option<u64>,
+
+    /// Upper bound in milliseconds of the backoff between retries.
+    #[arg(long, env = "FRONTEND_BACKEND_RETRY_MAX_DELAY_MS")]
+    pub backend_retry_max_delay_ms: Option<u64>,
+
+    /// Consecutive backend failures that open the circuit breaker, 0 disables it.
+    #[arg(long, env = "FRONTEND_BACKEND_CIRCUIT_FAILURE_THRESHOLD")]
+    pub backend_circuit_failure_threshold: Option<u32>,
+
+    /// Seconds the circuit breaker stays open before a trial request.
+    #[arg(long, env = "FRONTEND_BACKEND_CIRCUIT_OPEN_SECS")]
+    pub backend_circuit_open_secs: Option<u64>,
+
+    /// Seconds a backend answer stays cached when the backend gives no max-age, 0 disables the cache.
+    #[arg(long, env = "FRONTEND_CACHE_TTL_SECS")]
+    pub cache_ttl_secs: Option<u64>,
+
+    /// Maximum number of cached backend answers.
+    #[arg(long, env = "FRONTEND_CACHE_MAX_ENTRIES")]
+    pub cache_max_entries: Option<usize>,
+
+    /// Maximum total size in bytes of the cached backend answers.
+    #[arg(long, env = "FRONTEND_CACHE_MAX_BYTES")]
+    pub cache_max_bytes: Option<usize>,
+
+    /// Directory whose static files replace the embedded ones, e.g. templates/ during development.
+    #[arg(long, env = "FRONTEND_ASSETS_DIR")]
+    pub assets_dir: Option<PathBuf>,
+
+    /// Seconds to wait for the requests being answered after SIGTERM or SIGINT.
+    #[arg(long, env = "FRONTEND_SHUTDOWN_TIMEOUT_SECS")]
+    pub shutdown_timeout_secs: Option<u64>,
+
+    /// Largest request body accepted, in bytes.
+    #[arg(long, env = "FRONTEND_MAX_BODY_BYTES")]
+    pub max_body_bytes: Option<u64>,
+
+    /// Compress the answers when the client accepts it (true), or not (false).
+    #[arg(long, env = "FRONTEND_COMPRESSION_ENABLED")]
+    pub compression_enabled: Option<bool>,
+
+    /// Smallest answer compressed, in bytes.
+    #[arg(long, env = "FRONTEND_COMPRESSION_MIN_BYTES")]
+    pub compression_min_bytes: Option<u16>,
+
+    /// Seconds a soft lock of the update form lasts without being renewed.
+    #[arg(long, env = "FRONTEND_PRESENCE_LOCK_TTL_SECS")]
+    pub presence_lock_ttl_secs: Option<u64>,
+
+    /// Format of the log lines.
+    #[arg(long, env = "FRONTEND_LOG_FORMAT", value_enum)]
+    pub log_format: Option<LogFormat>,
+
+    /// Which logs are written, e.g. info,questions_client=debug.
+    #[arg(long, env = "FRONTEND_LOG_FILTER")]
+    pub log_filter: Option<String>,
+
+    /// Print the resolved configuration as TOML, report whether it is valid, and exit.
+    #[arg(long)]
+    pub print_config: bool,
+}
+
+/// Errors raised while loading or validating the configuration.
+#[derive(Debug)]
+pub enum ConfigurationError {
+    /// The configuration file could not be read.
+    ReadFile(PathBuf, std::io::Error),
+    /// The configuration file is not valid TOML or contains unknown settings.
+    ParseFile(PathBuf, toml::de::Error),
+    /// A setting has a value that cannot be used.
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigurationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigurationError::ReadFile(path, error) => write!(
+                formatter,
+                "Couldn't read configuration file {}: {}",
+                path.display(),
+                error
+            ),
+            ConfigurationError::ParseFile(path, error) => write!(
+                formatter,
+                "Couldn't parse configuration file {}: {}",
+                path.display(),
+                error
+            ),
+            ConfigurationError::Invalid(message) => {
+                write!(formatter, "Invalid configuration: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigurationError {}
+
+impl FrontendConfiguration {
+    /// Builds the configuration from the defaults, the optional file, the environment and the flags.
+    ///
+    /// # Parameters
+    /// - `arguments`: The parsed command line flags, which already include the environment variables.
+    ///
+    /// # Returns
+    /// The validated configuration or the reason it could not be loaded.
+    pub fn load(arguments: &FrontendArguments) -> Result<Self, ConfigurationError> {
+        let configuration = Self::resolve(arguments)?;
+        configuration.validate()?;
+        Ok(configuration)
+    }
+
+    /// Merges the defaults, the optional file, the environment and the flags without validating them.
+    ///
+    /// # Parameters
+    /// - `arguments`: The parsed command line flags, which already include the environment variables.
+    ///
+    /// # Returns
+    /// The merged configuration, which may still be invalid, or the reason the file could not be read.
+    pub fn resolve(arguments: &FrontendArguments) -> Result<Self, ConfigurationError> {
+        let mut configuration = match &arguments.config {
+            Some(config_path) => Self::from_file(config_path)?,
+            None => FrontendConfiguration::default(),
+        };
+        configuration.apply_arguments(arguments);
+        configuration.backend_url = configuration.backend_url.trim_end_matches('/').to_string();
+        Ok(configuration)
+    }
+
+    /// Reads the settings of a TOML file, using the defaults for missing keys.
+    fn from_file(config_path: &Path) -> Result<Self, ConfigurationError> {
+        let file_content = std::fs::read_to_string(config_path)
+            .map_err(|error| ConfigurationError::ReadFile(config_path.to_path_buf(), error))?;
+        toml::from_str(&file_content)
+            .map_err(|error| ConfigurationError::ParseFile(config_path.to_path_buf(), error))
+    }
+
+    /// Overrides the settings with every flag or environment variable that was given.
+    fn apply_arguments(&mut self, arguments: &FrontendArguments) {
+        if let Some(bind_address) = arguments.bind_address {
+            self.bind_address = bind_address;
+        }
+        if let Some(tls_cert_path) = &arguments.tls_cert_path {
+            self.tls_cert_path = Some(tls_cert_path.clone());
+        }
+        if let Some(tls_key_path) = &arguments.tls_key_path {
+            self.tls_key_path = Some(tls_key_path.clone());
+        }
+        if let Some(tls_redirect_bind_address) = arguments.tls_redirect_bind_address {
+            self.tls_redirect_bind_address = Some(tls_redirect_bind_address);
+        }
+        if let Some(backend_url) = &arguments.backend_url {
+            self.backend_url = backend_url.clone();
+        }
+        if let Some(backend_ca_path) = &arguments.backend_ca_path {
+            self.backend_ca_path = Some(backend_ca_path.clone());
+        }
+        if let Some(backend_client_cert_path) = &arguments.backend_client_cert_path {
+            self.backend_client_cert_path = Some(backend_client_cert_path.clone());
+        }
+        if let Some(backend_client_key_path) = &arguments.backend_client_key_path {
+            self.backend_client_key_path = Some(backend_client_key_path.clone());
+        }
+        if let Some(backend_timeout) = arguments.backend_timeout_secs {
+            self.backend_timeout_secs = backend_timeout;
+        }
+        if let Some(connect_timeout) = arguments.backend_connect_timeout_secs {
+            self.backend_connect_timeout_secs = connect_timeout;
+        }
+        if let Some(max_retries) = arguments.backend_max_retries {
+            self.backend_max_retries = max_retries;
+        }
+        if let Some(base_delay) = arguments.backend_retry_base_delay_ms {
+            self.backend_retry_base_delay_ms = base_delay;
+        }
+        if let Some(max_delay) = arguments.backend_retry_max_delay_ms {
+            self.backend_retry_max_delay_ms = max_delay;
+        }
+        if let Some(failure_threshold) = arguments.backend_circuit_failure_threshold {
+            self.backend_circuit_failure_threshold = failure_threshold;
+        }
+        if let Some(open_secs) = arguments.backend_circuit_open_secs {
+            self.backend_circuit_open_secs = open_secs;
+        }
+        if let Some(cache_ttl) = arguments.cache_ttl_secs {
+            self.cache_ttl_secs = cache_ttl;
+        }
+        if let Some(max_entries) = arguments.cache_max_entries {
+            self.cache_max_entries = max_entries;
+        }
+        if let Some(max_bytes) = arguments.cache_max_bytes {
+            self.cache_max_bytes = max_bytes;
+        }
+        if let Some(assets_dir) = &arguments.assets_dir {
+            self.assets_dir = Some(assets_dir.clone());
+        }
+        if let Some(shutdown_timeout) = arguments.shutdown_timeout_secs {
+            self.shutdown_timeout_secs = shutdown_timeout;
+        }
+        if let Some(max_body_bytes) = arguments.max_body_bytes {
+            self.max_body_bytes = max_body_bytes;
+        }
+        if let Some(compression_enabled) = arguments.compression_enabled {
+            self.compression_enabled = compression_enabled;
+        }
+        if let Some(compression_min_bytes) = arguments.compression_min_bytes {
+            self.compression_min_bytes = compression_min_bytes;
+        }
+        if let Some(lock_ttl) = arguments.presence_lock_ttl_secs {
+            self.presence_lock_ttl_secs = lock_ttl;
+        }
+        if let Some(log_format) = arguments.log_format {
+            self.log_format = log_format;
+        }
+        if let Some(log_filter) = &arguments.log_filter {
+            self.log_filter = log_filter.clone();
+        }
+    }
+
+    /// Checks that the settings can be used to start the server.
+    pub fn validate(&self) -> Result<(), ConfigurationError> {
+        match reqwest::Url::parse(&self.backend_url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            _ => {
+                return Err(ConfigurationError::Invalid(format!(
+                    "backend_url must be an http:// or https:// URL, got {:?}",
+                    self.backend_url
+                )))
+            }
+        }
+        if self.backend_timeout_secs == 0 || self.backend_connect_timeout_secs == 0 {
+            return Err(ConfigurationError::Invalid(
+                "backend_timeout_secs and backend_connect_timeout_secs must be greater than 0"
+                    .to_string(),
+            ));
+        }
+        if let Some(assets_dir) = &self.assets_dir {
+            if !assets_dir.is_dir() {
+                return Err(ConfigurationError::Invalid(format!(
+                    "assets_dir {} is not a directory",
+                    assets_dir.display()
+                )));
+            }
+        }
+        if self.backend_retry_base_delay_ms > self.backend_retry_max_delay_ms {
+            return Err(ConfigurationError::Invalid(
+                "backend_retry_base_delay_ms must not exceed backend_retry_max_delay_ms"
+                    .to_string(),
+            ));
+        }
+        if self.backend_circuit_failure_threshold > 0 && self.backend_circuit_open_secs == 0 {
+            return Err(ConfigurationError::Invalid(
+                "backend_circuit_open_secs must be greater than 0 when the circuit breaker is enabled"
+                    .to_string(),
+            ));
+        }
+        if self.backend_circuit_open_secs > MAX_BACKEND_CIRCUIT_OPEN_SECS {
+            return Err(ConfigurationError::Invalid(format!(
+                "backend_circuit_open_secs must not exceed {} (one hour)",
+                MAX_BACKEND_CIRCUIT_OPEN_SECS
+            )));
+        }
+        if self.backend_client_cert_path.is_some() != self.backend_client_key_path.is_some() {
+            return Err(ConfigurationError::Invalid(
+                "backend_client_cert_path and backend_client_key_path must be set together"
+                    .to_string(),
+            ));
+        }
+        if self.tls_cert_path.is_some() != self.tls_key_path.is_some() {
+            return Err(ConfigurationError::Invalid(
+                "tls_cert_path and tls_key_path must be set together".to_string(),
+            ));
+        }
+        if self.tls_cert_path.is_none() && self.tls_redirect_bind_address.is_some() {
+            return Err(ConfigurationError::Invalid(
+                "tls_redirect_bind_address requires tls_cert_path and tls_key_path".to_string(),
+            ));
+        }
+        if self.tls_redirect_bind_address == Some(self.bind_address) {
+            return Err(ConfigurationError::Invalid(
+                "tls_redirect_bind_address must differ from bind_address".to_string(),
+            ));
+        }
+        if let Some(missing_file) = [
+            &self.tls_cert_path,
+            &self.tls_key_path,
+            &self.backend_ca_path,
+            &self.backend_client_cert_path,
+            &self.backend_client_key_path,
+        ]
+        .into_iter()
+        .flatten()
+        .find(|path| !path.is_file())
+        {
+            return Err(ConfigurationError::Invalid(format!(
+                "{} is not a file",
+                missing_file.display()
+            )));
+        }
+        if self.max_body_bytes == 0 {
+            return Err(ConfigurationError::Invalid(
+                "max_body_bytes must be greater than 0".to_string(),
+            ));
+        }
+        if self.cache_ttl_secs > MAX_CACHE_LIFETIME_SECS {
+            return Err(ConfigurationError::Invalid(format!(
+                "cache_ttl_secs must not exceed {} (one year)",
+                MAX_CACHE_LIFETIME_SECS
+            )));
+        }
+        if self.presence_lock_ttl_secs == 0 {
+            return Err(ConfigurationError::Invalid(
+                "presence_lock_ttl_secs must be greater than 0".to_string(),
+            ));
+        }
+        if self.presence_lock_ttl_secs > MAX_LOCK_TTL_SECS {
+            return Err(ConfigurationError::Invalid(format!(
+                "presence_lock_ttl_secs must not exceed {} (one day)",
+                MAX_LOCK_TTL_SECS
+            )));
+        }
+        if let Err(error) = tracing_subscriber::EnvFilter::try_new(&self.log_filter) {
+            return Err(ConfigurationError::Invalid(format!(
+                "log_filter is not a valid filter: {}",
+                error
+            )));
+        }
+        Ok(())
+    }
+
+    /// Time to wait for a complete answer from the backend API.
+    pub fn backend_timeout(&self) -> Duration {
+        Duration::from_secs(self.backend_timeout_secs)
+    }
+
+    /// Time to wait while connecting to the backend API.
+    pub fn backend_connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.backend_connect_timeout_secs)
+    }
+
+    /// Longest time to wait for the requests being answered when shutting down.
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    /// Options of the backend API client: timeouts, retries, circuit breaker and TLS.
+    ///
+    /// # Returns
+    /// The options, or an error when a certificate file can't be read.
+    pub fn client_options(&self) -> Result<ClientOptions, ConfigurationError> {
+        let read_pem_file = |path: &Path| {
+            std::fs::read(path).map_err(|error| {
+                ConfigurationError::Invalid(format!("Couldn't read {}: {}", path.display(), error))
+            })
+        };
+        let root_certificate_pem = self
+            .backend_ca_path
+            .as_deref()
+            .map(read_pem_file)
+            .transpose()?;
+        // reqwest reads the client certificate chain and its key from a single PEM buffer
+        let identity_pem = match (
+            &self.backend_client_cert_path,
+            &self.backend_client_key_path,
+        ) {
+            (Some(cert_path), Some(key_path)) => {
+                let mut identity_pem = read_pem_file(cert_path)?;
+                identity_pem.push(b'\n');
+                identity_pem.extend(read_pem_file(key_path)?);
+                Some(identity_pem)
+            }
+            _ => None,
+        };
+        Ok(ClientOptions {
+            timeout: self.backend_timeout(),
+            connect_timeout: self.backend_connect_timeout(),
+            max_retries: self.backend_max_retries,
+            retry_base_delay: Duration::from_millis(self.backend_retry_base_delay_ms),
+            retry_max_delay: Duration::from_millis(self.backend_retry_max_delay_ms),
+            circuit_failure_threshold: self.backend_circuit_failure_threshold,
+            circuit_open_duration: Duration::from_secs(self.backend_circuit_open_secs),
+            root_certificate_pem,
+            identity_pem,
+            ..ClientOptions::default()
+        })
+    }
+
+    /// The certificate files of the HTTPS server, or `None` to serve plain HTTP.
+    pub fn tls_files(&self) -> Option<TlsFiles> {
+        Some(TlsFiles {
+            cert_path: self.tls_cert_path.clone()?,
+            key_path: self.tls_key_path.clone()?,
+            client_ca_path: None,
+        })
+    }
+
+    /// Smallest answer compressed, or `None` when compression is disabled.
+    pub fn compression_threshold(&self) -> Option<u16> {
+        self.compression_enabled
+            .then_some(self.compression_min_bytes)
+    }
+
+    /// Time a soft lock of the update form lasts without being renewed.
+    pub fn presence_lock_ttl(&self) -> Duration {
+        Duration::from_secs(self.presence_lock_ttl_secs)
+    }
+
+    /// TTL and size limits of the cache of backend answers.
+    pub fn cache_settings(&self) -> CacheSettings {
+        CacheSettings {
+            ttl: Duration::from_secs(self.cache_ttl_secs),
+            max_entries: self.cache_max_entries,
+            max_bytes: self.cache_max_bytes,
+        }
+    }
+
+    /// Renders the configuration as TOML.
+    pub fn to_printable_toml(&self) -> String {
+        toml::to_string_pretty(self)
+            .unwrap_or_else(|error| format!("# Couldn't print configuration: {}", error))
+    }
+}
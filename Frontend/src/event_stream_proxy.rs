@@ -0,0 +1,280 @@
+use crate::response_cache::ResponseCache;
+use crate::route_handlers::reject_client_error;
+use questions_client::{EventStream, QuestionsClient};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::body::{Bytes, Sender};
+use warp::hyper::Body;
+use warp::Rejection;
+
+/// The event streams relayed from the backend API to the browsers.
+///
+/// They are ended when the server shuts down, as they would otherwise hold the drain until
+/// its timeout.
+pub struct EventStreams {
+    closed: watch::Sender<bool>,
+}
+
+impl EventStreams {
+    /// Creates the set of streams, open until `close` is called.
+    pub fn new() -> Self {
+        EventStreams {
+            closed: watch::Sender::new(false),
+        }
+    }
+
+    /// Ends every stream being relayed and those opened afterwards.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
+/// Recognizes the events of a stream, rather than its keep-alive comments, across chunks that
+/// may end anywhere, even within a line.
+///
+/// Only the start of the current line is kept, so long `data` lines are not buffered.
+#[derive(Default)]
+struct EventScanner {
+    /// The first bytes of the line being received, enough to recognize a `data` field.
+    line_start: Vec<u8>,
+    /// Whether the event being received has a `data` field.
+    event_has_data: bool,
+}
+
+impl EventScanner {
+    /// Longest line start that is kept.
+    const LINE_START_BYTES: usize = b"data:".len();
+
+    /// Reads the next chunk of the stream.
+    ///
+    /// # Parameters
+    /// - `chunk`: The chunk as received from the backend API.
+    ///
+    /// # Returns
+    /// Whether an event with data was completed, by the blank line ending it, within the chunk.
+    fn completes_event(&mut self, chunk: &[u8]) -> bool {
+        let mut completed = false;
+        for byte in chunk {
+            if *byte != b'\n' {
+                if self.line_start.len() < Self::LINE_START_BYTES {
+                    self.line_start.push(*byte);
+                }
+                continue;
+            }
+            if self.line_start.is_empty() || self.line_start == b"\r" {
+                completed |= self.event_has_data;
+                self.event_has_data = false;
+            } else if self.line_start.starts_with(b"data:") {
+                self.event_has_data = true;
+            }
+            self.line_start.clear();
+        }
+        completed
+    }
+}
+
+/// First wait before opening the event stream of the backend API again, doubled after every
+/// failed attempt.
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait before opening the event stream of the backend API again.
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Empties the response cache on every change of the questions, made through any Frontend or
+/// Backend instance or straight in the database, for as long as the server runs.
+///
+/// The cache is also emptied whenever the event stream of the backend API is opened again, as
+/// the changes made meanwhile were not seen.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
+pub async fn invalidate_cache_on_changes(
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) {
+    let mut reconnect_delay = RECONNECT_BASE_DELAY;
+    loop {
+        match backend_client.events(None).await {
+            Ok(mut event_stream) => {
+                response_cache.invalidate_all();
+                reconnect_delay = RECONNECT_BASE_DELAY;
+                let mut event_scanner = EventScanner::default();
+                loop {
+                    match event_stream.next_chunk().await {
+                        Ok(Some(chunk)) => {
+                            if event_scanner.completes_event(&chunk) {
+                                response_cache.invalidate_all();
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(error) => {
+                            tracing::debug!(%error, "The backend event stream failed");
+                            break;
+                        }
+                    }
+                }
+            }
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't open the backend event stream, retrying");
+            }
+        }
+        tokio::time::sleep(reconnect_delay).await;
+        reconnect_delay = (reconnect_delay * 2).min(RECONNECT_MAX_DELAY);
+    }
+}
+
+/// Copies the events of the backend API to the browser until either side leaves or the
+/// server shuts down.
+///
+/// # Parameters
+/// - `event_stream`: The stream opened on the backend API.
+/// - `body_sender`: The body of the answer to the browser.
+/// - `closed`: Becomes `true` when the server shuts down.
+async fn relay_events(
+    mut event_stream: EventStream,
+    mut body_sender: Sender,
+    mut closed: watch::Receiver<bool>,
+) {
+    loop {
+        let chunk = tokio::select! {
+            biased;
+            _ = closed.wait_for(|closed| *closed) => return,
+            chunk = event_stream.next_chunk() => chunk,
+        };
+        match chunk {
+            Ok(Some(chunk)) => {
+                // Fails once the browser closed the page
+                if body_sender.send_data(Bytes::from(chunk)).await.is_err() {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(error) => {
+                tracing::debug!(%error, "The backend event stream failed");
+                return;
+            }
+        }
+    }
+}
+
+/// Relays the stream of the changes of the questions from the backend API, passing on the
+/// `Last-Event-ID` of a reconnecting browser so that it gets the events it missed.
+///
+/// # Parameters
+/// - `last_event_id`: The `Last-Event-ID` header of the request, if any.
+/// - `backend_client`: The shared client of the backend API.
+/// - `event_streams`: The streams ended when the server shuts down.
+///
+/// # Returns
+/// The event stream, or a Warp rejection when the backend API refused it or is unreachable.
+pub async fn relay_question_events(
+    last_event_id: Option<String>,
+    backend_client: Arc<QuestionsClient>,
+    event_streams: Arc<EventStreams>,
+) -> Result<warp::reply::Response, Rejection> {
+    let event_stream = backend_client
+        .events(last_event_id.as_deref())
+        .await
+        .map_err(reject_client_error)?;
+    let (body_sender, body) = Body::channel();
+    tokio::spawn(relay_events(
+        event_stream,
+        body_sender,
+        event_streams.closed.subscribe(),
+    ));
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(body);
+    Ok(response.unwrap_or_else(|_| Response::new(Body::empty())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventScanner;
+
+    /// An event of the backend API, as a single chunk.
+    const EVENT: &[u8] = b"id: 0123456789abcdef-7\nevent: created\ndata: {\"question_id\":3}\n\n";
+
+    /// Feeds the chunks to a new scanner.
+    ///
+    /// # Returns
+    /// Whether each chunk completed an event with data.
+    fn scan(chunks: &[&[u8]]) -> Vec<bool> {
+        let mut event_scanner = EventScanner::default();
+        chunks
+            .iter()
+            .map(|chunk| event_scanner.completes_event(chunk))
+            .collect()
+    }
+
+    #[test]
+    fn a_whole_event_is_found() {
+        assert_eq!(scan(&[EVENT]), [true]);
+    }
+
+    #[test]
+    fn events_split_across_chunks_are_found_once_complete() {
+        let data_start = EVENT
+            .windows(5)
+            .position(|window| window == b"data:")
+            .unwrap();
+        // Split within the `data:` field name, and before the blank line ending the event
+        let (head, tail) = EVENT.split_at(data_start + 2);
+        let (tail, end) = tail.split_at(tail.len() - 1);
+        assert_eq!(scan(&[head, tail, end]), [false, false, true]);
+
+        let bytes: Vec<&[u8]> = EVENT.chunks(1).collect();
+        let found = scan(&bytes);
+        assert_eq!(found.iter().filter(|found| **found).count(), 1);
+        assert_eq!(found.last(), Some(&true));
+    }
+
+    #[test]
+    fn crlf_line_endings_are_understood() {
+        assert_eq!(
+            scan(&[b"event: deleted\r\ndata: {\"question_id\":3}\r\n", b"\r\n"]),
+            [false, true]
+        );
+    }
+
+    #[test]
+    fn keep_alive_comments_are_not_events() {
+        assert_eq!(scan(&[b":\n\n", b": keep-alive\n\n:\n\n"]), [false, false]);
+        // Fields other than `data` don't make an event either
+        assert_eq!(
+            scan(&[b"retry: 1000\n\n", b"id: 1\nevent: ping\n\n"]),
+            [false, false]
+        );
+    }
+
+    #[test]
+    fn several_events_in_one_chunk_are_found() {
+        let mut chunk = EVENT.to_vec();
+        chunk.extend_from_slice(b":\n\n");
+        chunk.extend_from_slice(EVENT);
+        assert_eq!(scan(&[&chunk]), [true]);
+
+        // A complete event followed by the start of the next one
+        let mut chunk = EVENT.to_vec();
+        chunk.extend_from_slice(b"data: {\"ques");
+        assert_eq!(
+            scan(&[&chunk, b"tion_id\":4}\n", b"\n"]),
+            [true, false, true]
+        );
+    }
+
+    #[test]
+    fn long_data_lines_are_not_buffered() {
+        let mut event_scanner = EventScanner::default();
+        let long_line = vec![b'x'; 64 * 1024];
+        assert!(!event_scanner.completes_event(b"data: "));
+        assert!(!event_scanner.completes_event(&long_line));
+        assert!(event_scanner.line_start.len() <= EventScanner::LINE_START_BYTES);
+        assert!(event_scanner.completes_event(b"\n\n"));
+    }
+}
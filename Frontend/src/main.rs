@@ -1,11 +1,170 @@
+mod configuration;
+mod event_stream_proxy;
+mod metrics;
+mod page_handlers;
+mod presence;
+mod request_tracing;
+mod response_cache;
+mod response_compression;
 mod route_handlers;
 mod routes;
+mod shutdown;
+mod static_assets;
+mod tls_listener;
+
+use crate::configuration::{FrontendArguments, FrontendConfiguration};
+use crate::event_stream_proxy::{invalidate_cache_on_changes, EventStreams};
+use crate::metrics::{BackendCallMetricsLayer, FrontendMetrics};
+use crate::presence::QuestionPresence;
+use crate::request_tracing::{initialize_logging, serve_with_request_tracing};
+use crate::response_cache::ResponseCache;
+use crate::static_assets::StaticAssets;
+use crate::tls_listener::serve_https_redirect;
+use clap::Parser;
+use common::tls::ReloadingTlsConfig;
+use questions_client::QuestionsClient;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() {
+    // Resolve the configuration from the defaults, the config file, the environment and the flags
+    let arguments = FrontendArguments::parse();
+    if arguments.print_config {
+        print_configuration(&arguments);
+    }
+    let configuration = match FrontendConfiguration::load(&arguments) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+
+    // Write structured logs, as configured, from now on, and collect metrics
+    let metrics = Arc::new(FrontendMetrics::new());
+    initialize_logging(
+        configuration.log_format,
+        &configuration.log_filter,
+        BackendCallMetricsLayer::new(metrics.clone()),
+    );
+
+    // Create the pooled backend API client, with its retries, circuit breaker and TLS settings,
+    // shared by every request
+    let client_options = match configuration.client_options() {
+        Ok(client_options) => client_options,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't create the backend API client");
+            std::process::exit(2);
+        }
+    };
+    let backend_client =
+        match QuestionsClient::with_options(&configuration.backend_url, client_options) {
+            Ok(backend_client) => backend_client,
+            Err(error) => {
+                tracing::error!(%error, "Couldn't create the backend API client");
+                std::process::exit(2);
+            }
+        };
+
+    // Create the cache of backend answers shared by every request, emptied on every change of
+    // the questions
+    let backend_client = Arc::new(backend_client);
+    let response_cache = Arc::new(ResponseCache::new(configuration.cache_settings()));
+    tokio::spawn(invalidate_cache_on_changes(
+        backend_client.clone(),
+        response_cache.clone(),
+    ));
+
+    // Serve the static files embedded in the binary, or those of the override directory
+    let static_assets = StaticAssets::new(configuration.assets_dir.clone());
+
+    // Relay the events of the backend API to the browsers until the server shuts down
+    let event_streams = Arc::new(EventStreams::new());
+
+    // Track who views and who edits each question in the update form, expiring the soft locks
+    // that are not renewed
+    let presence = Arc::new(QuestionPresence::new(configuration.presence_lock_ttl()));
+    tokio::spawn(presence.clone().expire_locks());
+
     // Create all the routes for the application
-    let all_routes = routes::create_routes();
+    let all_routes = routes::create_routes(
+        backend_client,
+        response_cache,
+        Arc::new(static_assets),
+        metrics.clone(),
+        event_streams.clone(),
+        presence.clone(),
+        configuration.max_body_bytes,
+        configuration.compression_threshold(),
+    );
+
+    // Load the certificate when serving HTTPS, reloading it whenever its files change
+    let tls_config = match configuration.tls_files().map(ReloadingTlsConfig::load) {
+        Some(Ok(tls_config)) => Some(tls_config),
+        Some(Err(error)) => {
+            tracing::error!(%error, "Couldn't start the server");
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    // Redirect plain HTTP to HTTPS when asked to
+    if let Some(redirect_bind_address) = configuration.tls_redirect_bind_address {
+        let https_port = configuration.bind_address.port();
+        tokio::spawn(async move {
+            if let Err(error) = serve_https_redirect(redirect_bind_address, https_port).await {
+                tracing::error!(%error, "HTTP to HTTPS redirect failed");
+            }
+        });
+        tracing::info!(%redirect_bind_address, "Redirecting HTTP to HTTPS");
+    }
+
+    // Start the server on the configured address, with a span and a request ID per request,
+    // until SIGTERM or SIGINT
+    match serve_with_request_tracing(
+        warp::service(all_routes),
+        configuration.bind_address,
+        metrics,
+        configuration.shutdown_timeout(),
+        tls_config,
+        event_streams,
+        presence,
+    )
+    .await
+    {
+        Ok(shutdown_summary) => tracing::info!(
+            signal = shutdown_summary.signal,
+            drain_ms = shutdown_summary.drain_duration.as_millis() as u64,
+            served_requests = shutdown_summary.served_requests,
+            abandoned_requests = shutdown_summary.abandoned_requests,
+            "Server has stopped"
+        ),
+        Err(error) => {
+            tracing::error!(%error, "Server failed");
+            std::process::exit(1);
+        }
+    }
+}
 
-    // Start the Warp server and run it on port 2000
-    warp::serve(all_routes).run(([0, 0, 0, 0], 2000)).await;
+/// Prints the merged configuration, even when it is invalid, then exits.
+///
+/// The validation errors are reported on the standard error, after the configuration,
+/// and make the process exit with status 2.
+///
+/// # Parameters
+/// - `arguments`: The parsed command line flags, which already include the environment variables.
+fn print_configuration(arguments: &FrontendArguments) -> ! {
+    let configuration = match FrontendConfiguration::resolve(arguments) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    print!("{}", configuration.to_printable_toml());
+    if let Err(error) = configuration.validate() {
+        eprintln!("{}", error);
+        std::process::exit(2);
+    }
+    std::process::exit(0);
 }
@@ -0,0 +1,253 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use questions_client::BACKEND_CALL_TARGET;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use warp::http::header;
+use warp::reply::Reply;
+
+/// Route label of every `404 Not Found` answer: Warp doesn't tell which route matched,
+/// so grouping them keeps unknown paths from adding label values.
+const NOT_FOUND_ROUTE: &str = "not_found";
+
+/// The Prometheus metrics of the Frontend.
+pub struct FrontendMetrics {
+    registry: Registry,
+    http_requests_total: IntCounterVec,
+    http_request_duration_seconds: HistogramVec,
+    backend_requests_total: IntCounterVec,
+    backend_request_duration_seconds: HistogramVec,
+    backend_request_errors_total: IntCounterVec,
+}
+
+impl FrontendMetrics {
+    /// Creates and registers every metric of the Frontend.
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests answered"),
+            &["method", "route", "status"],
+        )
+        .expect("The metric is valid");
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to answer HTTP requests",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("The metric is valid");
+        let backend_requests_total = IntCounterVec::new(
+            Opts::new(
+                "backend_requests_total",
+                "Calls to the backend API, by outcome: the status code or the kind of error",
+            ),
+            &["method", "route", "outcome"],
+        )
+        .expect("The metric is valid");
+        let backend_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "backend_request_duration_seconds",
+                "Time taken by the calls to the backend API",
+            ),
+            &["method", "route", "outcome"],
+        )
+        .expect("The metric is valid");
+        let backend_request_errors_total = IntCounterVec::new(
+            Opts::new(
+                "backend_request_errors_total",
+                "Calls to the backend API that failed, answered 5xx or were refused by the circuit breaker",
+            ),
+            &["method", "route", "kind"],
+        )
+        .expect("The metric is valid");
+
+        for collector in [
+            Box::new(http_requests_total.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration_seconds.clone()),
+            Box::new(backend_requests_total.clone()),
+            Box::new(backend_request_duration_seconds.clone()),
+            Box::new(backend_request_errors_total.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("Every metric has a unique name");
+        }
+
+        FrontendMetrics {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            backend_requests_total,
+            backend_request_duration_seconds,
+            backend_request_errors_total,
+        }
+    }
+
+    /// Counts an answered request and its latency.
+    ///
+    /// # Parameters
+    /// - `method`: The HTTP method of the request.
+    /// - `path`: The requested path, turned into a route label.
+    /// - `status`: The status of the answer.
+    /// - `latency`: The time taken to answer.
+    pub fn observe_request(&self, method: &str, path: &str, status: u16, latency: Duration) {
+        let route = if status == 404 {
+            NOT_FOUND_ROUTE.to_string()
+        } else {
+            route_label(path)
+        };
+        let status = status.to_string();
+        let labels = [method, route.as_str(), status.as_str()];
+        self.http_requests_total.with_label_values(&labels).inc();
+        self.http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(latency.as_secs_f64());
+    }
+
+    /// Counts a call to the backend API, its latency, and whether it failed.
+    ///
+    /// # Parameters
+    /// - `method`: The HTTP method of the call.
+    /// - `url`: The URL of the call.
+    /// - `outcome`: The status code of the answer, or the kind of error.
+    /// - `elapsed_secs`: The duration of the call, when it was sent at all.
+    fn observe_backend_call(
+        &self,
+        method: &str,
+        url: &str,
+        outcome: &str,
+        elapsed_secs: Option<f64>,
+    ) {
+        let path = reqwest::Url::parse(url)
+            .map(|url| url.path().to_string())
+            .unwrap_or_default();
+        let route = route_label(&path);
+        let labels = [method, route.as_str(), outcome];
+        self.backend_requests_total.with_label_values(&labels).inc();
+        if let Some(elapsed_secs) = elapsed_secs {
+            self.backend_request_duration_seconds
+                .with_label_values(&labels)
+                .observe(elapsed_secs);
+        }
+
+        let error_kind = match outcome.parse::<u16>() {
+            Ok(status) if status >= 500 => Some("server_error"),
+            Ok(_) => None,
+            Err(_) => Some(outcome),
+        };
+        if let Some(error_kind) = error_kind {
+            self.backend_request_errors_total
+                .with_label_values(&[method, route.as_str(), error_kind])
+                .inc();
+        }
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(error) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!(%error, "Couldn't encode the metrics");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Turns a path into a route label, replacing the numeric segments (question IDs) with `:id`.
+///
+/// # Parameters
+/// - `path`: The path of a request, e.g. `/api/v1/questions/7`.
+fn route_label(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.chars().all(|character| character.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Answers the metrics in the Prometheus text format.
+///
+/// # Parameters
+/// - `metrics`: The metrics of the Frontend.
+pub async fn render_metrics(
+    metrics: Arc<FrontendMetrics>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    Ok(warp::reply::with_header(
+        metrics.render(),
+        header::CONTENT_TYPE,
+        prometheus::TEXT_FORMAT,
+    )
+    .into_response())
+}
+
+/// Tracing layer recording the calls to the backend API logged by the questions client.
+pub struct BackendCallMetricsLayer {
+    metrics: Arc<FrontendMetrics>,
+}
+
+impl BackendCallMetricsLayer {
+    /// Creates the layer.
+    ///
+    /// # Parameters
+    /// - `metrics`: The metrics the calls are recorded in.
+    pub fn new(metrics: Arc<FrontendMetrics>) -> Self {
+        BackendCallMetricsLayer { metrics }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for BackendCallMetricsLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        if event.metadata().target() != BACKEND_CALL_TARGET {
+            return;
+        }
+        let mut call_fields = BackendCallFields::default();
+        event.record(&mut call_fields);
+        self.metrics.observe_backend_call(
+            &call_fields.method,
+            &call_fields.url,
+            &call_fields.outcome,
+            call_fields.elapsed_secs,
+        );
+    }
+}
+
+/// The fields of a backend call event used by the metrics.
+#[derive(Default)]
+struct BackendCallFields {
+    method: String,
+    url: String,
+    outcome: String,
+    elapsed_secs: Option<f64>,
+}
+
+impl Visit for BackendCallFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "url" => self.url = value.to_string(),
+            "outcome" => self.outcome = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        // The method is recorded with its `Display` form, which `Debug` forwards to
+        if field.name() == "method" {
+            self.method = format!("{:?}", value);
+        }
+    }
+}
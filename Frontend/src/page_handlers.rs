@@ -0,0 +1,345 @@
+use askama::Template;
+use serde::Deserialize;
+use std::sync::Arc;
+use warp::http::{StatusCode, Uri};
+use warp::reply::{self, Reply};
+use warp::Rejection;
+
+use crate::response_cache::ResponseCache;
+use crate::route_handlers::{backend_error_message, reject_client_error};
+use common::{NewQuestion, Question};
+use questions_client::{ClientError, QuestionsClient};
+
+/// Page listing every question.
+#[derive(Template)]
+#[template(path = "pages/question_list.html")]
+struct QuestionListPage {
+    questions: Vec<Question>,
+}
+
+/// Page showing the details of a single question.
+#[derive(Template)]
+#[template(path = "pages/question_detail.html")]
+struct QuestionDetailPage {
+    question: Question,
+}
+
+/// Page with the search form and the matching questions.
+#[derive(Template)]
+#[template(path = "pages/search_results.html")]
+struct SearchResultsPage {
+    search_query: String,
+    questions: Vec<Question>,
+}
+
+/// Page with the form used to add or update a question.
+#[derive(Template)]
+#[template(path = "pages/question_form.html")]
+struct QuestionFormPage {
+    form_heading: &'static str,
+    form_action: String,
+    question_form: QuestionForm,
+    error_message: Option<String>,
+}
+
+/// Page explaining why a request could not be completed.
+#[derive(Template)]
+#[template(path = "pages/error_page.html")]
+struct ErrorPage {
+    error_message: String,
+}
+
+/// Query string of the search page.
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    q: String,
+}
+
+/// Fields submitted by the add and update HTML forms.
+/// `type_of_question` holds the comma separated list typed by the user.
+#[derive(Debug, Default, Deserialize)]
+pub struct QuestionForm {
+    question_title: String,
+    type_of_content: String,
+    type_of_question: String,
+}
+
+impl QuestionForm {
+    /// Builds a form pre-filled with the values of an existing question.
+    fn from_question(question: &Question) -> Self {
+        QuestionForm {
+            question_title: question.question_title.clone(),
+            type_of_content: question.type_of_content.clone(),
+            type_of_question: question.type_of_question.join(", "),
+        }
+    }
+
+    /// Converts the form fields into the question sent to the backend API.
+    fn to_new_question(&self) -> NewQuestion {
+        NewQuestion {
+            question_title: self.question_title.trim().to_string(),
+            type_of_content: self.type_of_content.clone(),
+            type_of_question: self
+                .type_of_question
+                .split(',')
+                .map(str::trim)
+                .filter(|type_of_question| !type_of_question.is_empty())
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+/// Renders a template into an HTML reply with the given status code.
+fn render_page(page: &impl Template, status: StatusCode) -> reply::Response {
+    match page.render() {
+        Ok(html) => reply::with_status(reply::html(html), status).into_response(),
+        Err(_) => reply::with_status(
+            "Error occurred while rendering the page",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+        .into_response(),
+    }
+}
+
+/// Renders the error page with the given message and status code.
+fn render_error_page(error_message: &str, status: StatusCode) -> reply::Response {
+    let page = ErrorPage {
+        error_message: error_message.to_string(),
+    };
+    render_page(&page, status)
+}
+
+/// Redirects the browser to another page after a form submission.
+fn redirect_to(location: &str) -> reply::Response {
+    match location.parse::<Uri>() {
+        Ok(uri) => warp::redirect::see_other(uri).into_response(),
+        Err(_) => render_error_page(
+            "Invalid redirect location",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+    }
+}
+
+/// Looks up a question, treating a missing question as `None`.
+async fn find_question(
+    backend_client: &QuestionsClient,
+    question_id: i32,
+) -> Result<Option<Question>, Rejection> {
+    match backend_client.get(question_id).await {
+        Ok(question) => Ok(Some(question)),
+        Err(ClientError::NotFound(_)) => Ok(None),
+        Err(error) => Err(reject_client_error(error)),
+    }
+}
+
+/// Returns the backend error message of a form submission, or `None` when it succeeded.
+fn form_error_message(result: Result<String, ClientError>) -> Result<Option<String>, Rejection> {
+    match result {
+        Ok(_) => Ok(None),
+        Err(error) => match backend_error_message(&error) {
+            Some(message) => Ok(Some(message.to_string())),
+            None => Err(reject_client_error(error)),
+        },
+    }
+}
+
+/// Renders the page listing every question.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// An HTML reply or a Warp rejection.
+pub async fn render_question_list_page(
+    backend_client: Arc<QuestionsClient>,
+) -> Result<reply::Response, Rejection> {
+    let questions = backend_client.list().await.map_err(reject_client_error)?;
+    Ok(render_page(&QuestionListPage { questions }, StatusCode::OK))
+}
+
+/// Renders the detail page of a question.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to show.
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// An HTML reply (404 page when the question does not exist) or a Warp rejection.
+pub async fn render_question_detail_page(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+) -> Result<reply::Response, Rejection> {
+    match find_question(&backend_client, question_id).await? {
+        Some(question) => Ok(render_page(
+            &QuestionDetailPage { question },
+            StatusCode::OK,
+        )),
+        None => Ok(render_error_page(
+            "Question with this specific ID not found or it doesn't exist!",
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+/// Renders the search page and the questions matching the query.
+/// The backend matches the question ID, title, content and types.
+///
+/// # Parameters
+/// - `search_query`: The `q` query string parameter.
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// An HTML reply or a Warp rejection.
+pub async fn render_search_results_page(
+    search_query: SearchQuery,
+    backend_client: Arc<QuestionsClient>,
+) -> Result<reply::Response, Rejection> {
+    let search_query = search_query.q.trim().to_string();
+
+    let questions = if search_query.is_empty() {
+        Vec::new()
+    } else {
+        backend_client
+            .search(&search_query)
+            .await
+            .map_err(reject_client_error)?
+    };
+
+    let page = SearchResultsPage {
+        search_query,
+        questions,
+    };
+    Ok(render_page(&page, StatusCode::OK))
+}
+
+/// Renders the empty form used to add a question.
+///
+/// # Returns
+/// An HTML reply or a Warp rejection.
+pub async fn render_new_question_page() -> Result<reply::Response, Rejection> {
+    let page = QuestionFormPage {
+        form_heading: "Add Question",
+        form_action: "/questions".to_string(),
+        question_form: QuestionForm::default(),
+        error_message: None,
+    };
+    Ok(render_page(&page, StatusCode::OK))
+}
+
+/// Renders the form used to update a question, pre-filled with its current values.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to edit.
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// An HTML reply or a Warp rejection.
+pub async fn render_edit_question_page(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+) -> Result<reply::Response, Rejection> {
+    match find_question(&backend_client, question_id).await? {
+        Some(question) => {
+            let page = QuestionFormPage {
+                form_heading: "Update Question",
+                form_action: format!("/questions/{}/edit", question_id),
+                question_form: QuestionForm::from_question(&question),
+                error_message: None,
+            };
+            Ok(render_page(&page, StatusCode::OK))
+        }
+        None => Ok(render_error_page(
+            "Question with this specific ID not found or it doesn't exist!",
+            StatusCode::NOT_FOUND,
+        )),
+    }
+}
+
+/// Handles the submission of the add form and redirects to the list of questions.
+///
+/// # Parameters
+/// - `question_form`: The submitted form fields.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+///
+/// # Returns
+/// A redirect, the form with the backend error message, or a Warp rejection.
+pub async fn submit_new_question_form(
+    question_form: QuestionForm,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<reply::Response, Rejection> {
+    let create_result = backend_client
+        .create(&question_form.to_new_question())
+        .await;
+
+    if let Some(error_message) = form_error_message(create_result)? {
+        let page = QuestionFormPage {
+            form_heading: "Add Question",
+            form_action: "/questions".to_string(),
+            error_message: Some(error_message),
+            question_form,
+        };
+        return Ok(render_page(&page, StatusCode::BAD_REQUEST));
+    }
+    response_cache.invalidate_all();
+    Ok(redirect_to("/questions"))
+}
+
+/// Handles the submission of the update form and redirects to the updated question.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to update.
+/// - `question_form`: The submitted form fields.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+///
+/// # Returns
+/// A redirect, the form with the backend error message, or a Warp rejection.
+pub async fn submit_edit_question_form(
+    question_id: i32,
+    question_form: QuestionForm,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<reply::Response, Rejection> {
+    let update_result = backend_client
+        .update(question_id, &question_form.to_new_question())
+        .await;
+
+    if let Some(error_message) = form_error_message(update_result)? {
+        let page = QuestionFormPage {
+            form_heading: "Update Question",
+            form_action: format!("/questions/{}/edit", question_id),
+            error_message: Some(error_message),
+            question_form,
+        };
+        return Ok(render_page(&page, StatusCode::BAD_REQUEST));
+    }
+    response_cache.invalidate_all();
+    Ok(redirect_to(&format!("/questions/{}", question_id)))
+}
+
+/// Handles the delete button of the detail page and redirects to the list of questions.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to delete.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+///
+/// # Returns
+/// A redirect, the error page, or a Warp rejection.
+pub async fn submit_delete_question_form(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<reply::Response, Rejection> {
+    let delete_result = backend_client.delete(question_id).await;
+
+    if let Some(error_message) = form_error_message(delete_result)? {
+        return Ok(render_error_page(&error_message, StatusCode::NOT_FOUND));
+    }
+    response_cache.invalidate_all();
+    Ok(redirect_to("/questions"))
+}
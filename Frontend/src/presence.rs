@@ -0,0 +1,689 @@
+use crate::route_handlers::error_reply;
+use common::{ErrorCode, ErrorResponse};
+use futures_util::{FutureExt, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use warp::http::Uri;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::{Rejection, Reply};
+
+/// Largest message accepted from a client, in bytes.
+const MAX_MESSAGE_BYTES: usize = 4 * 1024;
+
+/// Longest name shown to the others, in characters.
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Name shown for the clients that don't give one.
+const ANONYMOUS_NAME: &str = "Anonymous";
+
+/// Interval of the pings finding the clients that left without closing their socket: a
+/// client that sent nothing, not even a pong, since the previous ping is dropped.
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval at which the expired locks are released.
+const LOCK_EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest a lock may last without being renewed, in seconds: one day.
+pub const MAX_LOCK_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Query string of the presence socket.
+#[derive(Debug, Deserialize)]
+pub struct PresenceParameters {
+    /// Name shown to the others viewing the same question.
+    pub name: Option<String>,
+}
+
+/// What a client announces on its socket.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+enum PresenceRequest {
+    /// The client views a question, and no longer the one it viewed before.
+    View { question_id: i32 },
+    /// The client edits a question: it takes its soft lock when free, or renews it.
+    Edit { question_id: i32 },
+    /// The client stopped editing, releasing its lock but still viewing the question.
+    Release,
+    /// The client no longer views any question.
+    Leave,
+}
+
+/// A client viewing a question.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresentViewer {
+    pub session_id: u64,
+    pub name: String,
+}
+
+/// The holder of the soft lock of a question.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LockHolder {
+    pub session_id: u64,
+    pub name: String,
+    /// Seconds left before the lock expires, unless it is renewed.
+    pub expires_in_secs: u64,
+}
+
+/// Who views a question and who holds its lock, sent to every viewer on each change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresenceSnapshot {
+    pub question_id: i32,
+    pub viewers: Vec<PresentViewer>,
+    pub lock: Option<LockHolder>,
+}
+
+/// What the server sends on a socket.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum PresenceMessage<'a> {
+    /// Sent once the socket is open, with the session ID the client is known by.
+    Welcome {
+        session_id: u64,
+        name: &'a str,
+        lock_ttl_secs: u64,
+    },
+    /// The presence of the question the client views.
+    Presence(&'a PresenceSnapshot),
+    /// A message of the client was not understood.
+    Error(ErrorResponse),
+}
+
+/// A connected client.
+struct Session {
+    name: String,
+    question_id: Option<i32>,
+    /// The presence of the viewed question, replaced on every change.
+    snapshots: watch::Sender<Option<PresenceSnapshot>>,
+}
+
+/// The soft lock of a question.
+struct SoftLock {
+    session_id: u64,
+    expires_at: Instant,
+}
+
+/// The connected clients and the locks they hold.
+#[derive(Default)]
+struct PresenceState {
+    sessions: HashMap<u64, Session>,
+    locks: HashMap<i32, SoftLock>,
+}
+
+/// Presence of the clients of the update form, and the soft locks of the questions they edit.
+///
+/// Locks are advisory: they tell the others someone is editing, but updates are never refused.
+/// A lock expires when its holder doesn't renew it within the lock TTL, and is released when
+/// the holder leaves the question or closes its socket.
+pub struct QuestionPresence {
+    lock_ttl: Duration,
+    next_session_id: AtomicU64,
+    state: Mutex<PresenceState>,
+    closed: watch::Sender<bool>,
+    /// Number of open sockets, so that the shutdown can wait for their close frames.
+    open_sockets: watch::Sender<usize>,
+}
+
+impl QuestionPresence {
+    /// Creates the presence, without any client yet.
+    ///
+    /// # Parameters
+    /// - `lock_ttl`: How long a lock lasts without being renewed.
+    pub fn new(lock_ttl: Duration) -> Self {
+        QuestionPresence {
+            lock_ttl,
+            next_session_id: AtomicU64::new(1),
+            state: Mutex::new(PresenceState::default()),
+            closed: watch::Sender::new(false),
+            open_sockets: watch::Sender::new(0),
+        }
+    }
+
+    /// Locks the state, recovering it if a holder panicked.
+    fn lock_state(&self) -> MutexGuard<'_, PresenceState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Closes every socket, and those opened afterwards, as the server shuts down.
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Waits until every socket is closed, once `close` was called.
+    pub async fn sockets_closed(&self) {
+        let _ = self
+            .open_sockets
+            .subscribe()
+            .wait_for(|open_sockets| *open_sockets == 0)
+            .await;
+    }
+
+    /// Registers a new client, viewing no question yet.
+    ///
+    /// # Parameters
+    /// - `name`: The name shown to the others.
+    ///
+    /// # Returns
+    /// The session ID of the client, and the presence of the question it will view.
+    fn join(&self, name: String) -> (u64, watch::Receiver<Option<PresenceSnapshot>>) {
+        let session_id = self.next_session_id.fetch_add(1, Ordering::Relaxed);
+        let (snapshots, snapshot_receiver) = watch::channel(None);
+        let mut state = self.lock_state();
+        state.sessions.insert(
+            session_id,
+            Session {
+                name,
+                question_id: None,
+                snapshots,
+            },
+        );
+        self.open_sockets.send_replace(state.sessions.len());
+        (session_id, snapshot_receiver)
+    }
+
+    /// Applies what a client announced, and sends the new presence to the viewers concerned.
+    ///
+    /// # Parameters
+    /// - `session_id`: The session of the client.
+    /// - `request`: The announcement.
+    fn apply(&self, session_id: u64, request: PresenceRequest) {
+        let mut state = self.lock_state();
+        let question_id = match request {
+            PresenceRequest::View { question_id } | PresenceRequest::Edit { question_id } => {
+                Some(question_id)
+            }
+            PresenceRequest::Release => state
+                .sessions
+                .get(&session_id)
+                .and_then(|session| session.question_id),
+            PresenceRequest::Leave => None,
+        };
+        let left_question_id = move_session(&mut state, session_id, question_id);
+        if let Some(question_id) = question_id {
+            let now = Instant::now();
+            let held_by = state
+                .locks
+                .get(&question_id)
+                .filter(|lock| lock.expires_at > now)
+                .map(|lock| lock.session_id);
+            match request {
+                PresenceRequest::Edit { .. }
+                    if held_by.is_none_or(|holder| holder == session_id) =>
+                {
+                    if let Some(expires_at) = now.checked_add(self.lock_ttl) {
+                        state.locks.insert(
+                            question_id,
+                            SoftLock {
+                                session_id,
+                                expires_at,
+                            },
+                        );
+                    }
+                }
+                PresenceRequest::Release if held_by == Some(session_id) => {
+                    state.locks.remove(&question_id);
+                }
+                _ => {}
+            }
+        }
+        for question_id in left_question_id.into_iter().chain(question_id) {
+            publish_presence(&state, question_id);
+        }
+    }
+
+    /// Forgets a client whose socket closed, releasing its lock.
+    ///
+    /// # Parameters
+    /// - `session_id`: The session of the client.
+    fn leave(&self, session_id: u64) {
+        let mut state = self.lock_state();
+        let left_question_id = move_session(&mut state, session_id, None);
+        state.sessions.remove(&session_id);
+        self.open_sockets.send_replace(state.sessions.len());
+        if let Some(question_id) = left_question_id {
+            publish_presence(&state, question_id);
+        }
+    }
+
+    /// Releases the locks that were not renewed in time, and tells the viewers of their
+    /// questions, until the server shuts down.
+    pub async fn expire_locks(self: Arc<Self>) {
+        let mut closed = self.closed.subscribe();
+        let mut expiry_ticks = tokio::time::interval(LOCK_EXPIRY_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = closed.wait_for(|closed| *closed) => return,
+                _ = expiry_ticks.tick() => {}
+            }
+            self.release_expired_locks(Instant::now());
+        }
+    }
+
+    /// Releases the locks that expired, and tells the viewers of their questions.
+    ///
+    /// # Parameters
+    /// - `now`: The time the locks are checked at.
+    fn release_expired_locks(&self, now: Instant) {
+        let mut state = self.lock_state();
+        let expired_questions: Vec<i32> = state
+            .locks
+            .iter()
+            .filter(|(_, lock)| lock.expires_at <= now)
+            .map(|(question_id, _)| *question_id)
+            .collect();
+        for question_id in expired_questions {
+            state.locks.remove(&question_id);
+            publish_presence(&state, question_id);
+        }
+    }
+}
+
+/// Moves a session to another question, or to none, releasing its lock of the question it
+/// viewed.
+///
+/// # Parameters
+/// - `state`: The connected clients and their locks.
+/// - `session_id`: The session of the client.
+/// - `question_id`: The question the client now views, if any.
+///
+/// # Returns
+/// The question the session left, whose viewers must be told.
+fn move_session(
+    state: &mut PresenceState,
+    session_id: u64,
+    question_id: Option<i32>,
+) -> Option<i32> {
+    let session = state.sessions.get_mut(&session_id)?;
+    let previous_question_id = std::mem::replace(&mut session.question_id, question_id);
+    if question_id.is_none() {
+        session.snapshots.send_replace(None);
+    }
+    let left_question_id =
+        previous_question_id.filter(|previous| Some(*previous) != question_id)?;
+    if state
+        .locks
+        .get(&left_question_id)
+        .is_some_and(|lock| lock.session_id == session_id)
+    {
+        state.locks.remove(&left_question_id);
+    }
+    Some(left_question_id)
+}
+
+/// Sends the presence of a question to every client viewing it.
+///
+/// # Parameters
+/// - `state`: The connected clients and their locks.
+/// - `question_id`: The question whose presence changed.
+fn publish_presence(state: &PresenceState, question_id: i32) {
+    let mut viewers: Vec<PresentViewer> = state
+        .sessions
+        .iter()
+        .filter(|(_, session)| session.question_id == Some(question_id))
+        .map(|(session_id, session)| PresentViewer {
+            session_id: *session_id,
+            name: session.name.clone(),
+        })
+        .collect();
+    viewers.sort_by_key(|viewer| viewer.session_id);
+    let now = Instant::now();
+    let lock = state.locks.get(&question_id).and_then(|lock| {
+        let session = state.sessions.get(&lock.session_id)?;
+        Some(LockHolder {
+            session_id: lock.session_id,
+            name: session.name.clone(),
+            expires_in_secs: lock
+                .expires_at
+                .saturating_duration_since(now)
+                .as_secs_f64()
+                .ceil() as u64,
+        })
+    });
+    let snapshot = PresenceSnapshot {
+        question_id,
+        viewers,
+        lock,
+    };
+    for session in state.sessions.values() {
+        if session.question_id == Some(question_id) {
+            session.snapshots.send_replace(Some(snapshot.clone()));
+        }
+    }
+}
+
+/// The name a client asked to be shown with, trimmed and shortened, or `Anonymous`.
+///
+/// # Parameters
+/// - `name`: The `name` of the query string, if any.
+fn display_name(name: Option<String>) -> String {
+    let name: String = name
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|character| !character.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect();
+    if name.is_empty() {
+        ANONYMOUS_NAME.to_string()
+    } else {
+        name
+    }
+}
+
+/// Whether a WebSocket was opened by a page of the Frontend itself, or by a client that is not
+/// a browser, as browsers let any site open WebSockets to any host.
+///
+/// # Parameters
+/// - `origin`: The `Origin` header of the request, sent by browsers.
+/// - `host`: The `Host` header of the request.
+fn is_same_origin(origin: Option<&str>, host: Option<&str>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+    let origin_authority = origin
+        .parse::<Uri>()
+        .ok()
+        .and_then(|origin| origin.authority().map(|authority| authority.to_string()));
+    matches!(
+        (origin_authority, host),
+        (Some(origin_authority), Some(host)) if origin_authority.eq_ignore_ascii_case(host)
+    )
+}
+
+/// Opens the presence socket of a client of the update form.
+///
+/// # Parameters
+/// - `ws`: The WebSocket upgrade of the request.
+/// - `parameters`: The query string, with the name of the client.
+/// - `origin`: The `Origin` header of the request, if any.
+/// - `host`: The `Host` header of the request, if any.
+/// - `presence`: The shared presence of the clients.
+///
+/// # Returns
+/// The upgrade to the WebSocket, or a `403` when a page of another site opened it.
+pub async fn open_presence_socket(
+    ws: Ws,
+    parameters: PresenceParameters,
+    origin: Option<String>,
+    host: Option<String>,
+    presence: Arc<QuestionPresence>,
+) -> Result<warp::reply::Response, Rejection> {
+    if !is_same_origin(origin.as_deref(), host.as_deref()) {
+        return Ok(error_reply(
+            ErrorCode::ForbiddenOrigin,
+            "The presence socket may only be opened by the pages of the Frontend",
+        ));
+    }
+    let name = display_name(parameters.name);
+    Ok(ws
+        .max_message_size(MAX_MESSAGE_BYTES)
+        .on_upgrade(move |socket| serve_presence_socket(socket, name, presence))
+        .into_response())
+}
+
+/// Sends a message to a client.
+///
+/// # Returns
+/// Whether the message was sent, `false` once the client is gone.
+async fn send_message(
+    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    message: &PresenceMessage<'_>,
+) -> bool {
+    let Ok(text) = serde_json::to_string(message) else {
+        return true;
+    };
+    sender.send(Message::text(text)).await.is_ok()
+}
+
+/// Relays the announcements of a client and the presence of the question it views, until
+/// either side closes the socket or the server shuts down.
+///
+/// # Parameters
+/// - `socket`: The WebSocket of the client.
+/// - `name`: The name shown to the others.
+/// - `presence`: The shared presence of the clients.
+async fn serve_presence_socket(socket: WebSocket, name: String, presence: Arc<QuestionPresence>) {
+    let (session_id, mut snapshots) = presence.join(name.clone());
+    let mut closed = presence.closed.subscribe();
+    let (mut sender, mut receiver) = socket.split();
+    let welcome = PresenceMessage::Welcome {
+        session_id,
+        name: &name,
+        lock_ttl_secs: presence.lock_ttl.as_secs(),
+    };
+    tracing::debug!(session_id, "Presence socket opened");
+    let mut pings =
+        tokio::time::interval_at(tokio::time::Instant::now() + PING_INTERVAL, PING_INTERVAL);
+    // Whether nothing was received since the last ping
+    let mut awaiting_pong = false;
+    let mut connected = send_message(&mut sender, &welcome).await;
+    while connected {
+        tokio::select! {
+            biased;
+            // Mapped to `()`, as the guard it yields isn't `Send` and would be kept across the awaits below
+            _ = closed.wait_for(|closed| *closed).map(drop) => break,
+            changed = snapshots.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let snapshot = snapshots.borrow_and_update().clone();
+                if let Some(snapshot) = snapshot {
+                    connected = send_message(&mut sender, &PresenceMessage::Presence(&snapshot)).await;
+                }
+            }
+            received = receiver.next() => {
+                // Any message, pongs included, shows that the client is still there
+                awaiting_pong = false;
+                match received {
+                    Some(Ok(message)) if message.is_close() => break,
+                    Some(Ok(message)) if message.is_text() || message.is_binary() => {
+                        let request = std::str::from_utf8(message.as_bytes())
+                            .map_err(|error| error.to_string())
+                            .and_then(|text| {
+                                serde_json::from_str::<PresenceRequest>(text)
+                                    .map_err(|error| error.to_string())
+                            });
+                        match request {
+                            Ok(request) => presence.apply(session_id, request),
+                            Err(error) => {
+                                let error = PresenceMessage::Error(ErrorResponse::new(
+                                    ErrorCode::InvalidInput,
+                                    format!("Invalid presence message: {}", error),
+                                ));
+                                connected = send_message(&mut sender, &error).await;
+                            }
+                        }
+                    }
+                    // Pongs, and pings which are answered by the socket itself
+                    Some(Ok(_)) => {}
+                    Some(Err(error)) => {
+                        tracing::debug!(session_id, %error, "Presence socket failed");
+                        break;
+                    }
+                    None => break,
+                }
+            }
+            _ = pings.tick() => {
+                if awaiting_pong {
+                    tracing::debug!(session_id, "Presence socket didn't answer the ping");
+                    break;
+                }
+                awaiting_pong = true;
+                connected = sender.send(Message::ping(Vec::new())).await.is_ok();
+            }
+        }
+    }
+    let shutting_down = *closed.borrow();
+    if shutting_down {
+        let _ = sender
+            .send(Message::close_with(1001u16, "The server is shutting down"))
+            .await;
+    }
+    presence.leave(session_id);
+    tracing::debug!(session_id, "Presence socket closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PresenceRequest, PresenceSnapshot, QuestionPresence};
+    use std::time::{Duration, Instant};
+    use tokio::sync::watch;
+
+    const LOCK_TTL: Duration = Duration::from_secs(60);
+
+    /// A client of the presence, with the snapshots it receives.
+    struct Client {
+        session_id: u64,
+        snapshots: watch::Receiver<Option<PresenceSnapshot>>,
+    }
+
+    impl Client {
+        /// Registers a new client.
+        fn join(presence: &QuestionPresence, name: &str) -> Self {
+            let (session_id, snapshots) = presence.join(name.to_string());
+            Client {
+                session_id,
+                snapshots,
+            }
+        }
+
+        /// The last presence sent to the client.
+        fn snapshot(&self) -> Option<PresenceSnapshot> {
+            self.snapshots.borrow().clone()
+        }
+
+        /// The session holding the lock of the viewed question, as last sent to the client.
+        fn lock_holder(&self) -> Option<u64> {
+            self.snapshot()?.lock.map(|lock| lock.session_id)
+        }
+    }
+
+    #[test]
+    fn viewers_are_told_who_else_views_the_question() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(ada.session_id, PresenceRequest::View { question_id: 1 });
+        presence.apply(bob.session_id, PresenceRequest::View { question_id: 1 });
+
+        let snapshot = ada.snapshot().expect("Ada views a question");
+        assert_eq!(snapshot.question_id, 1);
+        let names: Vec<&str> = snapshot
+            .viewers
+            .iter()
+            .map(|viewer| viewer.name.as_str())
+            .collect();
+        assert_eq!(names, ["Ada", "Bob"]);
+        assert_eq!(snapshot.lock, None);
+
+        presence.apply(bob.session_id, PresenceRequest::View { question_id: 2 });
+        assert_eq!(ada.snapshot().unwrap().viewers.len(), 1);
+        assert_eq!(bob.snapshot().unwrap().question_id, 2);
+    }
+
+    #[test]
+    fn a_free_lock_is_taken_and_renewed_by_its_holder() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+
+        let lock = ada.snapshot().unwrap().lock.expect("Ada took the lock");
+        assert_eq!(lock.session_id, ada.session_id);
+        assert_eq!(lock.name, "Ada");
+        assert_eq!(lock.expires_in_secs, LOCK_TTL.as_secs());
+
+        let first_expiry = presence.lock_state().locks[&1].expires_at;
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert!(presence.lock_state().locks[&1].expires_at >= first_expiry);
+        assert_eq!(ada.lock_holder(), Some(ada.session_id));
+    }
+
+    #[test]
+    fn a_lock_held_by_another_session_is_refused() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(bob.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert_eq!(bob.lock_holder(), Some(ada.session_id));
+
+        // Bob can't release the lock of Ada either
+        presence.apply(bob.session_id, PresenceRequest::Release);
+        assert_eq!(bob.lock_holder(), Some(ada.session_id));
+    }
+
+    #[test]
+    fn locks_are_released_by_their_holder() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(bob.session_id, PresenceRequest::View { question_id: 1 });
+
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(ada.session_id, PresenceRequest::Release);
+        assert_eq!(bob.lock_holder(), None);
+        // Ada still views the question
+        assert_eq!(bob.snapshot().unwrap().viewers.len(), 2);
+
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(ada.session_id, PresenceRequest::Leave);
+        assert_eq!(bob.lock_holder(), None);
+        assert_eq!(bob.snapshot().unwrap().viewers.len(), 1);
+        assert_eq!(ada.snapshot(), None);
+
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(ada.session_id, PresenceRequest::View { question_id: 2 });
+        assert_eq!(bob.lock_holder(), None);
+
+        presence.apply(bob.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert_eq!(bob.lock_holder(), Some(bob.session_id));
+    }
+
+    #[test]
+    fn closing_the_socket_releases_the_lock() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(bob.session_id, PresenceRequest::View { question_id: 1 });
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert_eq!(*presence.open_sockets.borrow(), 2);
+
+        presence.leave(ada.session_id);
+        assert_eq!(bob.lock_holder(), None);
+        assert_eq!(bob.snapshot().unwrap().viewers.len(), 1);
+        assert_eq!(*presence.open_sockets.borrow(), 1);
+        assert!(presence.lock_state().locks.is_empty());
+    }
+
+    #[test]
+    fn expired_locks_are_released_and_can_be_taken() {
+        let presence = QuestionPresence::new(LOCK_TTL);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(bob.session_id, PresenceRequest::View { question_id: 1 });
+
+        presence.release_expired_locks(Instant::now() + LOCK_TTL / 2);
+        assert_eq!(bob.lock_holder(), Some(ada.session_id));
+        presence.release_expired_locks(Instant::now() + LOCK_TTL);
+        assert_eq!(bob.lock_holder(), None);
+
+        presence.apply(bob.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert_eq!(ada.lock_holder(), Some(bob.session_id));
+    }
+
+    #[test]
+    fn a_lock_past_its_expiry_can_be_taken_before_it_is_released() {
+        let presence = QuestionPresence::new(Duration::ZERO);
+        let ada = Client::join(&presence, "Ada");
+        let bob = Client::join(&presence, "Bob");
+        presence.apply(ada.session_id, PresenceRequest::Edit { question_id: 1 });
+        presence.apply(bob.session_id, PresenceRequest::Edit { question_id: 1 });
+        assert_eq!(bob.lock_holder(), Some(bob.session_id));
+    }
+}
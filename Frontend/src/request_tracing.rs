@@ -0,0 +1,236 @@
+use crate::configuration::LogFormat;
+use crate::event_stream_proxy::EventStreams;
+use crate::metrics::{BackendCallMetricsLayer, FrontendMetrics};
+use crate::presence::QuestionPresence;
+use crate::shutdown::{serve_until_drained, InFlightRequests, ServerError, ShutdownSummary};
+use crate::tls_listener::{tls_incoming, TlsConnection};
+use common::tls::ReloadingTlsConfig;
+use common::REQUEST_ID_HEADER;
+use questions_client::{with_forwarded_for, with_request_id, BACKEND_CALL_TARGET};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tracing::{Instrument, Level};
+use tracing_subscriber::filter::{EnvFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+use uuid::Uuid;
+use warp::http::{HeaderValue, Request, Response};
+use warp::hyper::server::conn::AddrStream;
+use warp::hyper::service::{make_service_fn, service_fn, Service};
+use warp::hyper::{Body, Server};
+
+/// Longest request ID accepted from the browser or a proxy in front of the Frontend.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Installs the global subscriber that writes the structured logs to the standard output
+/// and feeds the backend API calls to the metrics.
+///
+/// # Parameters
+/// - `log_format`: Human readable lines or one JSON object per line.
+/// - `log_filter`: Which logs are written, already validated by the configuration.
+/// - `backend_call_metrics_layer`: The layer recording the backend API calls.
+pub fn initialize_logging(
+    log_format: LogFormat,
+    log_filter: &str,
+    backend_call_metrics_layer: BackendCallMetricsLayer,
+) {
+    let log_layer = match log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .boxed(),
+    };
+    // Each layer has its own filter, so the backend call events reach the metrics
+    // even when the log filter leaves them out
+    tracing_subscriber::registry()
+        .with(log_layer.with_filter(EnvFilter::new(log_filter)))
+        .with(
+            backend_call_metrics_layer
+                .with_filter(Targets::new().with_target(BACKEND_CALL_TARGET, Level::DEBUG)),
+        )
+        .init();
+}
+
+/// Serves the routes over HTTP or HTTPS, wrapping every request in a span with its request ID,
+/// until SIGTERM or SIGINT, then drains the requests being answered.
+///
+/// # Parameters
+/// - `routes_service`: The routes of the application, turned into a service by `warp::service`.
+/// - `bind_address`: The address the server listens on.
+/// - `metrics`: The metrics counting every request.
+/// - `drain_timeout`: Longest time to wait for the requests being answered when shutting down.
+/// - `tls_config`: The certificate to serve HTTPS with, or `None` for plain HTTP.
+/// - `event_streams`: The event streams ended when the server shuts down.
+/// - `presence`: The presence whose sockets are closed when the server shuts down.
+///
+/// # Returns
+/// The summary of the shutdown, or an error when the server could not be started or failed.
+pub async fn serve_with_request_tracing<S>(
+    routes_service: S,
+    bind_address: SocketAddr,
+    metrics: Arc<FrontendMetrics>,
+    drain_timeout: Duration,
+    tls_config: Option<Arc<ReloadingTlsConfig>>,
+    event_streams: Arc<EventStreams>,
+    presence: Arc<QuestionPresence>,
+) -> Result<ShutdownSummary, ServerError>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    let in_flight_requests = Arc::new(InFlightRequests::default());
+    let tracked_requests = in_flight_requests.clone();
+    let connection_service = move |peer_address: SocketAddr| {
+        let routes_service = routes_service.clone();
+        let metrics = metrics.clone();
+        let tracked_requests = tracked_requests.clone();
+        service_fn(move |request| {
+            let in_flight_guard = tracked_requests.start();
+            let response = trace_request(
+                routes_service.clone(),
+                metrics.clone(),
+                peer_address,
+                request,
+            );
+            async move {
+                let response = response.await;
+                drop(in_flight_guard);
+                response
+            }
+        })
+    };
+    match tls_config {
+        None => {
+            let server = Server::try_bind(&bind_address)?.serve(make_service_fn(
+                move |connection: &AddrStream| {
+                    let service = connection_service(connection.remote_addr());
+                    async move { Ok::<_, Infallible>(service) }
+                },
+            ));
+            tracing::info!(%bind_address, tls = false, "Server has started");
+            serve_until_drained(
+                |shutdown_signal| server.with_graceful_shutdown(shutdown_signal),
+                in_flight_requests,
+                drain_timeout,
+                event_streams,
+                presence,
+            )
+            .await
+        }
+        Some(tls_config) => {
+            let tcp_listener = TcpListener::bind(bind_address).await?;
+            let server = Server::builder(tls_incoming(tcp_listener, tls_config)).serve(
+                make_service_fn(move |connection: &TlsConnection| {
+                    let service = connection_service(connection.remote_addr());
+                    async move { Ok::<_, Infallible>(service) }
+                }),
+            );
+            tracing::info!(%bind_address, tls = true, "Server has started");
+            serve_until_drained(
+                |shutdown_signal| server.with_graceful_shutdown(shutdown_signal),
+                in_flight_requests,
+                drain_timeout,
+                event_streams,
+                presence,
+            )
+            .await
+        }
+    }
+}
+
+/// Handles one request inside its span, logs its status and latency and counts it in the metrics.
+///
+/// The request ID comes from the `x-request-id` header when a proxy in front of the Frontend
+/// already set a valid one, otherwise it is generated. It is sent to the Backend with every
+/// backend API call made for the request, and back to the browser.
+///
+/// # Parameters
+/// - `routes_service`: The routes of the application.
+/// - `metrics`: The metrics counting every request.
+/// - `peer_address`: The address the connection came from, sent to the Backend in
+///   `x-forwarded-for` after those of any proxy in front of the Frontend.
+/// - `request`: The request of the browser.
+async fn trace_request<S>(
+    mut routes_service: S,
+    metrics: Arc<FrontendMetrics>,
+    peer_address: SocketAddr,
+    mut request: Request<Body>,
+) -> Result<Response<Body>, Infallible>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>,
+{
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|request_id| is_valid_request_id(request_id))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let request_id_value = HeaderValue::from_str(&request_id).ok();
+    if let Some(request_id_value) = &request_id_value {
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id_value.clone());
+    }
+
+    let forwarded_for = match request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(forwarded_for) => format!("{}, {}", forwarded_for, peer_address.ip()),
+        None => peer_address.ip().to_string(),
+    };
+
+    let request_span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        request_id = %request_id,
+    );
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let started_at = Instant::now();
+    // The Warp service is always ready, so it can be called without waiting for it
+    let mut response = with_request_id(
+        request_id,
+        with_forwarded_for(forwarded_for, routes_service.call(request)),
+    )
+    .instrument(request_span.clone())
+    .await?;
+
+    let latency = started_at.elapsed();
+    metrics.observe_request(&method, &path, response.status().as_u16(), latency);
+    request_span.in_scope(|| {
+        let status = response.status().as_u16();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        if response.status().is_server_error() {
+            tracing::error!(status, latency_ms, "Request failed");
+        } else {
+            tracing::info!(status, latency_ms, "Request answered");
+        }
+    });
+    if let Some(request_id_value) = request_id_value {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id_value);
+    }
+    Ok(response)
+}
+
+/// Whether a request ID received from outside is short and safe to write in the logs.
+fn is_valid_request_id(request_id: &str) -> bool {
+    !request_id.is_empty()
+        && request_id.len() <= MAX_REQUEST_ID_LENGTH
+        && request_id
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || "-_.".contains(character))
+}
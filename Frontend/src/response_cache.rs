@@ -0,0 +1,553 @@
+use questions_client::{HeaderMap, RawResponse};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Longest time an answer stays fresh, in seconds, whatever the backend or the configuration
+/// asks for: one year.
+pub const MAX_CACHE_LIFETIME_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Limits of the response cache.
+#[derive(Debug, Clone)]
+pub struct CacheSettings {
+    /// How long an answer stays fresh when the backend does not send `Cache-Control: max-age`.
+    /// Zero disables the cache.
+    pub ttl: Duration,
+    /// Maximum number of cached answers.
+    pub max_entries: usize,
+    /// Maximum total size in bytes of the cached bodies.
+    pub max_bytes: usize,
+}
+
+/// What the cache knows about a request.
+pub enum CacheLookup {
+    /// A fresh answer that can be sent without calling the backend.
+    Fresh(RawResponse),
+    /// An expired answer that the backend can confirm with `If-None-Match`.
+    Revalidate(String),
+    /// Nothing usable is cached.
+    Miss,
+}
+
+/// A cached backend answer.
+struct CachedResponse {
+    raw_response: RawResponse,
+    etag: Option<String>,
+    expires_at: Instant,
+    last_used: Instant,
+}
+
+/// Mutable state of the cache.
+struct CacheState {
+    entries: HashMap<String, CachedResponse>,
+    total_bytes: usize,
+    // Incremented by every invalidation, so answers fetched before it are not stored
+    generation: u64,
+}
+
+/// In-process cache of the backend answers to GET requests, keyed by path.
+///
+/// Answers are kept for the configured TTL, or for the `max-age` of the backend
+/// `Cache-Control` header, and the least recently used ones are evicted when the
+/// size limits are reached. Expired answers with an `ETag` are kept for revalidation.
+pub struct ResponseCache {
+    settings: CacheSettings,
+    state: Mutex<CacheState>,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache.
+    ///
+    /// # Parameters
+    /// - `settings`: The TTL and size limits of the cache.
+    pub fn new(settings: CacheSettings) -> Self {
+        ResponseCache {
+            settings,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                total_bytes: 0,
+                generation: 0,
+            }),
+        }
+    }
+
+    /// Locks the shared state, recovering it if a holder panicked.
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Looks up the cached answer of a request.
+    ///
+    /// # Parameters
+    /// - `cache_key`: The path and query of the request.
+    ///
+    /// # Returns
+    /// The lookup result and the generation to pass to `store` or `refresh`.
+    pub fn lookup(&self, cache_key: &str) -> (CacheLookup, u64) {
+        let mut state = self.lock();
+        let generation = state.generation;
+        let now = Instant::now();
+
+        let lookup = match state.entries.get_mut(cache_key) {
+            Some(cached) if now < cached.expires_at => {
+                cached.last_used = now;
+                CacheLookup::Fresh(cached.raw_response.clone())
+            }
+            Some(cached) => match &cached.etag {
+                Some(etag) => CacheLookup::Revalidate(etag.clone()),
+                None => {
+                    state.remove(cache_key);
+                    CacheLookup::Miss
+                }
+            },
+            None => CacheLookup::Miss,
+        };
+        (lookup, generation)
+    }
+
+    /// Stores a successful backend answer, if its headers allow it.
+    ///
+    /// # Parameters
+    /// - `cache_key`: The path and query of the request.
+    /// - `raw_response`: The backend answer.
+    /// - `generation`: The generation returned by `lookup` before calling the backend.
+    pub fn store(&self, cache_key: &str, raw_response: &RawResponse, generation: u64) {
+        if self.settings.ttl.is_zero() || !raw_response.status.is_success() {
+            return;
+        }
+        let Some(lifetime) = cache_lifetime(&raw_response.headers, self.settings.ttl) else {
+            return;
+        };
+        let etag = header_text(&raw_response.headers, "etag");
+        // An answer that must be revalidated is only useful with an ETag
+        if lifetime.is_zero() && etag.is_none() {
+            return;
+        }
+        let body_size = raw_response.body.len();
+        if body_size > self.settings.max_bytes || self.settings.max_entries == 0 {
+            return;
+        }
+
+        let mut state = self.lock();
+        if state.generation != generation {
+            return;
+        }
+        state.remove(cache_key);
+        while state.entries.len() >= self.settings.max_entries
+            || state.total_bytes + body_size > self.settings.max_bytes
+        {
+            if !state.evict_least_recently_used() {
+                break;
+            }
+        }
+        let now = Instant::now();
+        let Some(expires_at) = now.checked_add(lifetime) else {
+            return;
+        };
+        state.total_bytes += body_size;
+        state.entries.insert(
+            cache_key.to_string(),
+            CachedResponse {
+                raw_response: raw_response.clone(),
+                etag,
+                expires_at,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Marks an expired answer as fresh again after the backend answered `304 Not Modified`.
+    ///
+    /// # Parameters
+    /// - `cache_key`: The path and query of the request.
+    /// - `not_modified`: The `304` answer of the backend, whose headers give the new lifetime.
+    /// - `generation`: The generation returned by `lookup` before calling the backend.
+    ///
+    /// # Returns
+    /// The cached answer, or `None` when it was evicted or invalidated meanwhile.
+    pub fn refresh(
+        &self,
+        cache_key: &str,
+        not_modified: &RawResponse,
+        generation: u64,
+    ) -> Option<RawResponse> {
+        let mut state = self.lock();
+        if state.generation != generation {
+            return None;
+        }
+        let lifetime = cache_lifetime(&not_modified.headers, self.settings.ttl)?;
+        let cached = state.entries.get_mut(cache_key)?;
+        let now = Instant::now();
+        cached.expires_at = now.checked_add(lifetime)?;
+        cached.last_used = now;
+        Some(cached.raw_response.clone())
+    }
+
+    /// Drops every cached answer, after the questions were changed.
+    pub fn invalidate_all(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.total_bytes = 0;
+        state.generation += 1;
+    }
+}
+
+impl CacheState {
+    /// Removes an entry and its size from the totals.
+    fn remove(&mut self, cache_key: &str) {
+        if let Some(removed) = self.entries.remove(cache_key) {
+            self.total_bytes -= removed.raw_response.body.len();
+        }
+    }
+
+    /// Removes the entry used the longest time ago.
+    ///
+    /// # Returns
+    /// `false` when the cache is already empty.
+    fn evict_least_recently_used(&mut self) -> bool {
+        let oldest_key = self
+            .entries
+            .iter()
+            .min_by_key(|(_, cached)| cached.last_used)
+            .map(|(cache_key, _)| cache_key.clone());
+        match oldest_key {
+            Some(cache_key) => {
+                self.remove(&cache_key);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reads a header as text.
+fn header_text(headers: &HeaderMap, header_name: &str) -> Option<String> {
+    headers
+        .get(header_name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// How long an answer may be cached according to its `Cache-Control` header.
+///
+/// # Parameters
+/// - `headers`: The headers of the backend answer.
+/// - `default_ttl`: The lifetime used when the backend gives no `max-age`.
+///
+/// # Returns
+/// `None` when the answer must not be stored (`no-store`, `private`), zero when it must be
+/// revalidated before every use (`no-cache`), otherwise its lifetime, at most
+/// `MAX_CACHE_LIFETIME_SECS`.
+fn cache_lifetime(headers: &HeaderMap, default_ttl: Duration) -> Option<Duration> {
+    let Some(cache_control) = header_text(headers, "cache-control") else {
+        return Some(default_ttl);
+    };
+    let mut max_age = None;
+    let mut shared_max_age = None;
+    for directive in cache_control.split(',') {
+        let directive = directive.trim().to_ascii_lowercase();
+        match directive.split_once('=') {
+            None if directive == "no-store" || directive == "private" => return None,
+            None if directive == "no-cache" => return Some(Duration::ZERO),
+            Some(("max-age", seconds)) => max_age = seconds.trim_matches('"').parse().ok(),
+            Some(("s-maxage", seconds)) => shared_max_age = seconds.trim_matches('"').parse().ok(),
+            _ => {}
+        }
+    }
+    // The Frontend is a shared cache, so `s-maxage` takes precedence over `max-age`
+    Some(
+        shared_max_age
+            .or(max_age)
+            .map(|seconds: u64| Duration::from_secs(seconds.min(MAX_CACHE_LIFETIME_SECS)))
+            .unwrap_or(default_ttl),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cache_lifetime, CacheLookup, CacheSettings, ResponseCache, MAX_CACHE_LIFETIME_SECS,
+    };
+    use questions_client::{HeaderMap, HeaderValue, RawResponse, StatusCode};
+    use std::time::Duration;
+
+    const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+    /// A cache with the default TTL and the given size limits.
+    fn test_cache(max_entries: usize, max_bytes: usize) -> ResponseCache {
+        ResponseCache::new(CacheSettings {
+            ttl: DEFAULT_TTL,
+            max_entries,
+            max_bytes,
+        })
+    }
+
+    /// Headers made of the given names and values.
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    /// A `200 OK` answer with the given headers and body.
+    fn ok_response(pairs: &[(&'static str, &'static str)], body: &str) -> RawResponse {
+        RawResponse {
+            status: StatusCode::OK,
+            headers: headers(pairs),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// Stores an answer as if it was fetched after a lookup.
+    fn store(cache: &ResponseCache, cache_key: &str, raw_response: &RawResponse) {
+        let (_, generation) = cache.lookup(cache_key);
+        cache.store(cache_key, raw_response, generation);
+    }
+
+    /// The body of the fresh answer cached for a key, if any.
+    fn fresh_body(cache: &ResponseCache, cache_key: &str) -> Option<String> {
+        match cache.lookup(cache_key).0 {
+            CacheLookup::Fresh(raw_response) => Some(String::from_utf8(raw_response.body).unwrap()),
+            _ => None,
+        }
+    }
+
+    /// Waits long enough for the next use of an entry to be more recent.
+    fn tick() {
+        std::thread::sleep(Duration::from_millis(2));
+    }
+
+    #[test]
+    fn answers_without_cache_control_use_the_default_ttl() {
+        assert_eq!(
+            cache_lifetime(&HeaderMap::new(), DEFAULT_TTL),
+            Some(DEFAULT_TTL)
+        );
+        assert_eq!(
+            cache_lifetime(&headers(&[("cache-control", "public")]), DEFAULT_TTL),
+            Some(DEFAULT_TTL)
+        );
+    }
+
+    #[test]
+    fn private_and_no_store_answers_are_not_stored() {
+        for cache_control in [
+            "no-store",
+            "private",
+            "public, No-Store",
+            "max-age=60, private",
+        ] {
+            let response_headers = headers(&[("cache-control", cache_control)]);
+            assert_eq!(
+                cache_lifetime(&response_headers, DEFAULT_TTL),
+                None,
+                "{}",
+                cache_control
+            );
+        }
+
+        let cache = test_cache(10, 1024);
+        store(
+            &cache,
+            "/api/v1/questions",
+            &ok_response(&[("cache-control", "no-store"), ("etag", "\"1\"")], "[]"),
+        );
+        assert!(matches!(
+            cache.lookup("/api/v1/questions").0,
+            CacheLookup::Miss
+        ));
+    }
+
+    #[test]
+    fn no_cache_answers_are_revalidated_before_every_use() {
+        assert_eq!(
+            cache_lifetime(&headers(&[("cache-control", "no-cache")]), DEFAULT_TTL),
+            Some(Duration::ZERO)
+        );
+
+        let cache = test_cache(10, 1024);
+        // Without an ETag there is nothing to revalidate
+        store(
+            &cache,
+            "/api/v1/questions",
+            &ok_response(&[("cache-control", "no-cache")], "[]"),
+        );
+        assert!(matches!(
+            cache.lookup("/api/v1/questions").0,
+            CacheLookup::Miss
+        ));
+
+        store(
+            &cache,
+            "/api/v1/questions",
+            &ok_response(&[("cache-control", "no-cache"), ("etag", "\"1\"")], "[]"),
+        );
+        let (lookup, generation) = cache.lookup("/api/v1/questions");
+        assert!(matches!(lookup, CacheLookup::Revalidate(etag) if etag == "\"1\""));
+
+        let not_modified = RawResponse {
+            status: StatusCode::NOT_MODIFIED,
+            headers: headers(&[("cache-control", "max-age=60")]),
+            body: Vec::new(),
+        };
+        let refreshed = cache
+            .refresh("/api/v1/questions", &not_modified, generation)
+            .expect("The answer is still cached");
+        assert_eq!(refreshed.body, b"[]");
+        assert_eq!(
+            fresh_body(&cache, "/api/v1/questions").as_deref(),
+            Some("[]")
+        );
+    }
+
+    #[test]
+    fn s_maxage_takes_precedence_over_max_age() {
+        assert_eq!(
+            cache_lifetime(&headers(&[("cache-control", "max-age=10")]), DEFAULT_TTL),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            cache_lifetime(
+                &headers(&[("cache-control", "max-age=10, s-maxage=120")]),
+                DEFAULT_TTL
+            ),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            cache_lifetime(
+                &headers(&[("cache-control", "S-MAXAGE=\"5\", max-age=10")]),
+                DEFAULT_TTL
+            ),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            cache_lifetime(&headers(&[("cache-control", "max-age=soon")]), DEFAULT_TTL),
+            Some(DEFAULT_TTL)
+        );
+    }
+
+    #[test]
+    fn lifetimes_are_capped_at_one_year() {
+        let one_year = Duration::from_secs(MAX_CACHE_LIFETIME_SECS);
+        assert_eq!(
+            cache_lifetime(
+                &headers(&[("cache-control", "max-age=18446744073709551615")]),
+                DEFAULT_TTL
+            ),
+            Some(one_year)
+        );
+        assert_eq!(
+            cache_lifetime(
+                &headers(&[("cache-control", "s-maxage=99999999999")]),
+                DEFAULT_TTL
+            ),
+            Some(one_year)
+        );
+
+        let cache = test_cache(10, 1024);
+        store(
+            &cache,
+            "/api/v1/questions",
+            &ok_response(&[("cache-control", "max-age=18446744073709551615")], "[]"),
+        );
+        assert_eq!(
+            fresh_body(&cache, "/api/v1/questions").as_deref(),
+            Some("[]")
+        );
+    }
+
+    #[test]
+    fn only_successful_answers_are_stored() {
+        let cache = test_cache(10, 1024);
+        let not_found = RawResponse {
+            status: StatusCode::NOT_FOUND,
+            headers: HeaderMap::new(),
+            body: b"{}".to_vec(),
+        };
+        store(&cache, "/api/v1/questions/1", &not_found);
+        assert!(matches!(
+            cache.lookup("/api/v1/questions/1").0,
+            CacheLookup::Miss
+        ));
+    }
+
+    #[test]
+    fn the_least_recently_used_answer_is_evicted_past_the_entry_limit() {
+        let cache = test_cache(2, 1024);
+        store(&cache, "/a", &ok_response(&[], "a"));
+        tick();
+        store(&cache, "/b", &ok_response(&[], "b"));
+        tick();
+        assert!(fresh_body(&cache, "/a").is_some());
+        tick();
+        store(&cache, "/c", &ok_response(&[], "c"));
+
+        assert_eq!(fresh_body(&cache, "/a").as_deref(), Some("a"));
+        assert_eq!(fresh_body(&cache, "/b"), None);
+        assert_eq!(fresh_body(&cache, "/c").as_deref(), Some("c"));
+    }
+
+    #[test]
+    fn the_least_recently_used_answers_are_evicted_past_the_byte_limit() {
+        let cache = test_cache(10, 10);
+        store(&cache, "/a", &ok_response(&[], "aaaa"));
+        tick();
+        store(&cache, "/b", &ok_response(&[], "bbbb"));
+        tick();
+        store(&cache, "/c", &ok_response(&[], "cccc"));
+        assert_eq!(fresh_body(&cache, "/a"), None);
+        assert_eq!(fresh_body(&cache, "/b").as_deref(), Some("bbbb"));
+        assert_eq!(fresh_body(&cache, "/c").as_deref(), Some("cccc"));
+
+        // A body larger than the whole cache is not stored, and evicts nothing
+        store(&cache, "/d", &ok_response(&[], "ddddddddddd"));
+        assert_eq!(fresh_body(&cache, "/d"), None);
+        assert_eq!(fresh_body(&cache, "/b").as_deref(), Some("bbbb"));
+
+        // Replacing an answer frees the size of the previous one
+        store(&cache, "/b", &ok_response(&[], "bbbbbbb"));
+        assert_eq!(fresh_body(&cache, "/b").as_deref(), Some("bbbbbbb"));
+        assert_eq!(fresh_body(&cache, "/c"), None);
+    }
+
+    #[test]
+    fn answers_fetched_before_an_invalidation_are_dropped() {
+        let cache = test_cache(10, 1024);
+        store(&cache, "/a", &ok_response(&[("etag", "\"1\"")], "old"));
+
+        let (_, generation) = cache.lookup("/b");
+        cache.invalidate_all();
+        assert_eq!(fresh_body(&cache, "/a"), None);
+        cache.store("/b", &ok_response(&[], "stale"), generation);
+        assert_eq!(fresh_body(&cache, "/b"), None);
+
+        let not_modified = RawResponse {
+            status: StatusCode::NOT_MODIFIED,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        };
+        assert!(cache.refresh("/a", &not_modified, generation).is_none());
+
+        store(&cache, "/b", &ok_response(&[], "new"));
+        assert_eq!(fresh_body(&cache, "/b").as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn a_zero_ttl_disables_the_cache() {
+        let cache = ResponseCache::new(CacheSettings {
+            ttl: Duration::ZERO,
+            max_entries: 10,
+            max_bytes: 1024,
+        });
+        store(
+            &cache,
+            "/a",
+            &ok_response(&[("cache-control", "max-age=60")], "a"),
+        );
+        assert_eq!(fresh_body(&cache, "/a"), None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::convert::Infallible;
+use std::io::Write;
+
+use warp::http::header::{self, HeaderValue};
+use warp::http::StatusCode;
+use warp::hyper::body::{self, HttpBody};
+use warp::hyper::Body;
+use warp::reply::Response;
+use warp::Reply;
+
+/// Brotli quality, low enough for answers compressed on every request.
+const BROTLI_QUALITY: u32 = 4;
+
+/// Brotli window size, as a power of two.
+const BROTLI_WINDOW_BITS: u32 = 22;
+
+/// zstd compression level, the default of the `zstd` command.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The encodings an answer can be compressed with, from most to least preferred.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Brotli,
+    Zstd,
+    Gzip,
+}
+
+impl Encoding {
+    /// The name of the encoding in `Accept-Encoding` and `Content-Encoding`.
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    /// Compresses a whole body.
+    ///
+    /// # Parameters
+    /// - `data`: The uncompressed body.
+    fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut compressed = Vec::new();
+                let mut writer = brotli::CompressorWriter::new(
+                    &mut compressed,
+                    4096,
+                    BROTLI_QUALITY,
+                    BROTLI_WINDOW_BITS,
+                );
+                writer.write_all(data)?;
+                drop(writer);
+                Ok(compressed)
+            }
+            Encoding::Zstd => zstd::encode_all(data, ZSTD_LEVEL),
+            Encoding::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Whether an encoding is accepted by the browser, by name or through `*`, and not refused
+/// with `q=0`.
+///
+/// # Parameters
+/// - `accept_encoding`: The `Accept-Encoding` header of the request.
+/// - `encoding_name`: The name of the encoding, e.g. `gzip`.
+pub fn accepts_encoding(accept_encoding: &str, encoding_name: &str) -> bool {
+    accept_encoding.split(',').any(|offer| {
+        let mut offer_parts = offer.split(';');
+        let offered_name = offer_parts.next().unwrap_or("").trim();
+        // An encoding with `q=0` is explicitly refused
+        let refused = offer_parts.any(|parameter| {
+            parameter
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .is_some_and(|quality| quality <= 0.0)
+        });
+        (offered_name.eq_ignore_ascii_case(encoding_name) || offered_name == "*") && !refused
+    })
+}
+
+/// Picks the best encoding accepted by the browser, if any.
+///
+/// # Parameters
+/// - `accept_encoding`: The `Accept-Encoding` header of the request, if any.
+fn negotiate_encoding(accept_encoding: Option<&str>) -> Option<Encoding> {
+    let accept_encoding = accept_encoding?;
+    [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip]
+        .into_iter()
+        .find(|encoding| accepts_encoding(accept_encoding, encoding.name()))
+}
+
+/// Whether compressing an answer of this type is worth it: text formats are, images and
+/// event streams are not.
+///
+/// # Parameters
+/// - `content_type`: The `Content-Type` header of the answer.
+fn is_compressible(content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    media_type != "text/event-stream"
+        && (media_type.starts_with("text/")
+            || media_type.ends_with("json")
+            || media_type.ends_with("+xml")
+            || media_type == "application/javascript"
+            || media_type == "image/svg+xml")
+}
+
+/// Compresses an answer with the best encoding accepted by the browser.
+///
+/// Answers that are already encoded, such as the precompressed static files, that are
+/// streamed, that are shorter than the threshold or that compression wouldn't shrink are
+/// sent as they are.
+///
+/// # Parameters
+/// - `accept_encoding`: The `Accept-Encoding` header of the request.
+/// - `reply`: The answer of a route.
+/// - `min_bytes`: The smallest answer compressed, or `None` when compression is disabled.
+///
+/// # Returns
+/// The answer, compressed when it's worth it.
+pub async fn compress_reply(
+    accept_encoding: Option<String>,
+    reply: impl Reply,
+    min_bytes: Option<u16>,
+) -> Result<Response, Infallible> {
+    let response = reply.into_response();
+    let Some(min_bytes) = min_bytes else {
+        return Ok(response);
+    };
+    let headers = response.headers();
+    let compressible = !headers.contains_key(header::CONTENT_ENCODING)
+        && headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_compressible);
+    // Streamed bodies have no exact size and are never buffered here
+    let long_enough = response
+        .body()
+        .size_hint()
+        .exact()
+        .is_some_and(|body_length| body_length >= u64::from(min_bytes));
+    if !compressible || !long_enough {
+        return Ok(response);
+    }
+
+    let (mut parts, response_body) = response.into_parts();
+    let varies_on_encoding = parts
+        .headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.to_ascii_lowercase().contains("accept-encoding"));
+    if !varies_on_encoding {
+        parts
+            .headers
+            .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+    let Some(encoding) = negotiate_encoding(accept_encoding.as_deref()) else {
+        return Ok(Response::from_parts(parts, response_body));
+    };
+    let data = match body::to_bytes(response_body).await {
+        Ok(data) => data,
+        Err(error) => {
+            tracing::error!(%error, "Couldn't read the answer to compress");
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+    match encoding.compress(&data) {
+        Ok(compressed) if compressed.len() < data.len() => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(encoding.name()),
+            );
+            Ok(Response::from_parts(parts, Body::from(compressed)))
+        }
+        Ok(_) => Ok(Response::from_parts(parts, Body::from(data))),
+        Err(error) => {
+            tracing::warn!(%error, encoding = encoding.name(), "Couldn't compress the answer");
+            Ok(Response::from_parts(parts, Body::from(data)))
+        }
+    }
+}
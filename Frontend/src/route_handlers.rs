@@ -1,156 +1,698 @@
-use serde::{Deserialize, Serialize};
+use crate::response_cache::{CacheLookup, ResponseCache};
+use common::route_paths::{question_path, QUESTIONS_ROUTE};
+use common::{
+    ErrorCode, ErrorResponse, ListParameters, MessageResponse, NewQuestion, ReadinessCheck,
+    ReadinessReport,
+};
+use questions_client::{
+    CircuitBreakerStatus, ClientError, HeaderMap, HeaderValue, Method, QuestionsClient,
+    RawResponse, StatusCode as BackendStatusCode,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use warp::body::BodyDeserializeError;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::hyper::Body;
+use warp::reject::PayloadTooLarge;
 use warp::reject::Reject;
+use warp::reply::Reply;
 use warp::Rejection;
 
+/// Reasons why the Frontend could not obtain a usable answer from the backend API.
 #[derive(Debug)]
-pub struct RetrieveError;
+pub enum RetrieveError {
+    /// The connection to the backend API could not be established.
+    Connect(String),
+    /// The backend API did not answer in time.
+    Timeout(String),
+    /// The backend API answered with something that is not the expected JSON.
+    InvalidJson(String),
+    /// The circuit breaker is open, so the backend API was not called.
+    CircuitOpen {
+        retry_after_secs: u64,
+        message: String,
+    },
+    /// The backend API answered with an error status.
+    Backend {
+        status: u16,
+        code: ErrorCode,
+        message: String,
+    },
+}
 impl Reject for RetrieveError {}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NewQuestion {
-    question_title: String,
-    type_of_content: String,
-    type_of_question: Vec<String>,
+impl From<ClientError> for RetrieveError {
+    fn from(error: ClientError) -> Self {
+        match error {
+            ClientError::Timeout(error) => RetrieveError::Timeout(error.to_string()),
+            ClientError::Decode(message) => RetrieveError::InvalidJson(message),
+            ClientError::CircuitOpen { retry_after } => RetrieveError::CircuitOpen {
+                retry_after_secs: retry_after.as_secs_f64().ceil() as u64,
+                message: error.to_string(),
+            },
+            ClientError::NotFound(message) => RetrieveError::Backend {
+                status: 404,
+                code: ErrorCode::QuestionNotFound,
+                message,
+            },
+            ClientError::Api {
+                status,
+                code,
+                message,
+            } => RetrieveError::Backend {
+                status,
+                code,
+                message,
+            },
+            other => RetrieveError::Connect(other.to_string()),
+        }
+    }
 }
 
-/// Helper function for sending a GET call to the backend API and returning the response as a string.
+/// Headers that only concern a single connection and must not be forwarded by a proxy.
+/// `content-length` is recomputed by Warp from the forwarded body.
+const HOP_BY_HOP_HEADERS: [&str; 9] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "content-length",
+];
+
+/// Turns a client error into a rejection carrying the matching `RetrieveError`.
 ///
 /// # Parameters
-/// - `url`: The URL for the backend API endpoint.
+/// - `error`: The error returned by the questions client.
 ///
 /// # Returns
-/// A string-formatted JSON response or a Warp rejection.
-async fn fetch_from_backend_api(url: &str) -> Result<String, Rejection> {
-    let json_response_result = reqwest::get(url).await;
+/// A Warp rejection carrying a `RetrieveError`.
+pub fn reject_client_error(error: ClientError) -> Rejection {
+    warp::reject::custom(RetrieveError::from(error))
+}
 
-    if json_response_result.is_err() {
-        return Err(warp::reject::custom(RetrieveError));
+/// Returns the error message sent by the backend API, when the backend answered with an error.
+///
+/// # Parameters
+/// - `error`: The error returned by the questions client.
+pub fn backend_error_message(error: &ClientError) -> Option<&str> {
+    match error {
+        ClientError::NotFound(message) | ClientError::Api { message, .. } => Some(message),
+        _ => None,
     }
+}
 
-    let json_response = json_response_result.unwrap();
-
-    let json_data_result = json_response.text().await;
+/// Builds a JSON error reply whose HTTP status matches the error code.
+///
+/// # Parameters
+/// - `code`: The machine readable kind of the error.
+/// - `message`: The human readable description of the error.
+pub(crate) fn error_reply(code: ErrorCode, message: &str) -> warp::reply::Response {
+    let status = StatusCode::from_u16(code.http_status()).unwrap_or(StatusCode::BAD_GATEWAY);
+    warp::reply::with_status(
+        warp::reply::json(&ErrorResponse::new(code, message)),
+        status,
+    )
+    .into_response()
+}
 
-    if json_data_result.is_err() {
-        return Err(warp::reject::custom(RetrieveError));
+/// Copies the status, headers and body of a backend answer into the reply sent to the browser.
+///
+/// # Parameters
+/// - `raw_response`: The answer of the backend API.
+///
+/// # Returns
+/// The reply with the same status, end-to-end headers and body.
+fn forward_response(raw_response: RawResponse) -> warp::reply::Response {
+    let mut response_builder = Response::builder().status(raw_response.status.as_u16());
+    for (header_name, header_value) in raw_response.headers.iter() {
+        if !HOP_BY_HOP_HEADERS.contains(&header_name.as_str()) {
+            response_builder =
+                response_builder.header(header_name.as_str(), header_value.as_bytes());
+        }
     }
+    response_builder
+        .body(Body::from(raw_response.body))
+        .unwrap_or_else(|_| {
+            error_reply(
+                ErrorCode::InvalidBackendResponse,
+                "The backend API answered with invalid headers",
+            )
+        })
+}
 
-    let parsed_json_data = json_data_result.unwrap();
+/// Sends a GET request to the backend API through the response cache.
+///
+/// Fresh cached answers are sent without calling the backend, expired ones with an `ETag`
+/// are revalidated with `If-None-Match`. The `X-Cache` header of the reply tells which
+/// case happened: `HIT`, `REVALIDATED` or `MISS`.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
+/// - `path`: The path of the backend API endpoint.
+///
+/// # Returns
+/// The cached or forwarded answer, or a Warp rejection when the backend could not be reached.
+async fn cached_get_from_backend_api(
+    backend_client: &QuestionsClient,
+    response_cache: &ResponseCache,
+    path: &str,
+) -> Result<warp::reply::Response, Rejection> {
+    let (lookup, generation) = response_cache.lookup(path);
+    let mut conditional_headers = HeaderMap::new();
+    match lookup {
+        CacheLookup::Fresh(raw_response) => {
+            return Ok(with_cache_status(forward_response(raw_response), "HIT"))
+        }
+        CacheLookup::Revalidate(etag) => {
+            if let Ok(etag_value) = HeaderValue::from_str(&etag) {
+                conditional_headers.insert("if-none-match", etag_value);
+            }
+        }
+        CacheLookup::Miss => {}
+    }
 
-    Ok(parsed_json_data)
+    let raw_response = backend_client
+        .send_raw_with_headers(Method::GET, path, &conditional_headers, None::<&()>)
+        .await
+        .map_err(reject_client_error)?;
+    if raw_response.status == BackendStatusCode::NOT_MODIFIED {
+        if let Some(cached_response) = response_cache.refresh(path, &raw_response, generation) {
+            return Ok(with_cache_status(
+                forward_response(cached_response),
+                "REVALIDATED",
+            ));
+        }
+        // The cached answer disappeared meanwhile, so ask again without condition
+        let raw_response = backend_client
+            .send_raw(Method::GET, path, None::<&()>)
+            .await
+            .map_err(reject_client_error)?;
+        response_cache.store(path, &raw_response, generation);
+        return Ok(with_cache_status(forward_response(raw_response), "MISS"));
+    }
+    response_cache.store(path, &raw_response, generation);
+    Ok(with_cache_status(forward_response(raw_response), "MISS"))
 }
 
-/// Returns the JSON result from an HTTP call sent to the backend API.
+/// Sends a request that changes questions and empties the response cache once the backend accepted it.
 ///
 /// # Parameters
-/// - `url`: The URL of the backend API endpoint.
-/// - `method`: The HTTP method to employ for the request (such as GET, POST, or PATCH).
-/// - `body`: The optional body for the request, serialized as JSON.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
+/// - `method`: The HTTP method of the request.
+/// - `path`: The path of the backend API endpoint.
+/// - `headers`: Extra headers of the request, e.g. the `Content-Type` of a patch.
+/// - `body`: The optional JSON body of the request.
 ///
 /// # Returns
-/// A result containing the backend APIs JSON answer or a Warp refusal.
-async fn send_request_to_backend_api(
-    url: &str,
-    method: reqwest::Method,
+/// The forwarded answer, or a Warp rejection when the backend could not be reached.
+async fn proxy_change_to_backend_api(
+    backend_client: &QuestionsClient,
+    response_cache: &ResponseCache,
+    method: Method,
+    path: &str,
+    headers: &HeaderMap,
     body: Option<&impl Serialize>,
-) -> Result<serde_json::Value, Rejection> {
-    // Use the reqwest library to create a new HTTP client.
-    let http_client = reqwest::Client::new();
+) -> Result<warp::reply::Response, Rejection> {
+    let raw_response = backend_client
+        .send_raw_with_headers(method, path, headers, body)
+        .await
+        .map_err(reject_client_error)?;
+    if raw_response.status.is_success() {
+        response_cache.invalidate_all();
+    }
+    Ok(forward_response(raw_response))
+}
 
-    // Build the request using the supplied method and URL.
-    let mut request_builder = http_client.request(method, url);
+/// Adds the `X-Cache` header telling whether the reply came from the response cache.
+fn with_cache_status(
+    mut response: warp::reply::Response,
+    cache_status: &'static str,
+) -> warp::reply::Response {
+    response.headers_mut().insert(
+        header::HeaderName::from_static("x-cache"),
+        header::HeaderValue::from_static(cache_status),
+    );
+    response
+}
 
-    // If a body is provided, serialize it into JSON and include it in the request.
-    if let Some(body_data) = body {
-        request_builder = request_builder.json(body_data);
+/// Turns the rejections of the Frontend into JSON errors.
+///
+/// Connection failures and invalid answers of the backend become `502 Bad Gateway`,
+/// timeouts become `504 Gateway Timeout`, an open circuit breaker becomes
+/// `503 Service Unavailable` with `Retry-After`, and backend error statuses are kept.
+/// Other rejections, such as unknown paths, are left to Warp.
+///
+/// # Parameters
+/// - `rejection`: The rejection raised by a route.
+///
+/// # Returns
+/// A JSON error reply, or the original rejection.
+pub async fn handle_rejection(rejection: Rejection) -> Result<warp::reply::Response, Rejection> {
+    if let Some(retrieve_error) = rejection.find::<RetrieveError>() {
+        let reply = match retrieve_error {
+            RetrieveError::Connect(message) => error_reply(
+                ErrorCode::BackendUnavailable,
+                &format!("The backend API is unavailable: {}", message),
+            ),
+            RetrieveError::Timeout(message) => error_reply(
+                ErrorCode::BackendTimeout,
+                &format!("The backend API did not answer in time: {}", message),
+            ),
+            RetrieveError::InvalidJson(message) => error_reply(
+                ErrorCode::InvalidBackendResponse,
+                &format!("The backend API answered with invalid JSON: {}", message),
+            ),
+            RetrieveError::CircuitOpen {
+                retry_after_secs,
+                message,
+            } => warp::reply::with_header(
+                warp::reply::with_status(
+                    warp::reply::json(&ErrorResponse::new(
+                        ErrorCode::BackendUnavailable,
+                        message.as_str(),
+                    )),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                ),
+                "retry-after",
+                (*retry_after_secs).max(1).to_string(),
+            )
+            .into_response(),
+            RetrieveError::Backend {
+                status,
+                code,
+                message,
+            } => {
+                let status = StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY);
+                warp::reply::with_status(
+                    warp::reply::json(&ErrorResponse::new(*code, message.as_str())),
+                    status,
+                )
+                .into_response()
+            }
+        };
+        return Ok(reply);
     }
 
-    // Send the request, mapping any errors to a specific RetrieveError.
-    let json_response = request_builder
-        .send()
-        .await
-        .map_err(|_| warp::reject::custom(RetrieveError))?;
-    let json_data = json_response
-        .json::<serde_json::Value>()
-        .await
-        .map_err(|_| warp::reject::custom(RetrieveError))?;
+    if rejection.find::<PayloadTooLarge>().is_some() {
+        return Ok(error_reply(
+            ErrorCode::PayloadTooLarge,
+            "The request body is too large",
+        ));
+    }
+
+    if let Some(body_error) = rejection.find::<BodyDeserializeError>() {
+        return Ok(error_reply(
+            ErrorCode::InvalidInput,
+            &format!("Invalid request body: {}", body_error),
+        ));
+    }
+
+    Err(rejection)
+}
+
+/// Value of the `Deprecation` header of the legacy routes: the date they were deprecated,
+/// 2026-10-18, as an RFC 9745 Unix timestamp.
+const LEGACY_DEPRECATION_DATE: &str = "@1792281600";
+
+/// Marks the reply of a legacy route as deprecated, with a link to its successor.
+///
+/// # Parameters
+/// - `reply`: The reply of the versioned route.
+/// - `successor_path`: The versioned path that replaces the legacy one.
+fn deprecated_alias(reply: impl Reply, successor_path: &str) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    let headers = response.headers_mut();
+    headers.insert(
+        header::HeaderName::from_static("deprecation"),
+        header::HeaderValue::from_static(LEGACY_DEPRECATION_DATE),
+    );
+    if let Ok(link) =
+        header::HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor_path))
+    {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
 
-    // Return the parsed JSON data
-    Ok(json_data)
+/// The backend path of the questions collection, with the search text when there is one.
+///
+/// # Parameters
+/// - `list_parameters`: The query string of the request.
+fn questions_path(list_parameters: &ListParameters) -> String {
+    match &list_parameters.q {
+        Some(search_text) => {
+            let query: String =
+                reqwest::Url::parse_with_params("http://localhost/", [("q", search_text)])
+                    .ok()
+                    .and_then(|url| url.query().map(str::to_string))
+                    .unwrap_or_default();
+            format!("{}?{}", QUESTIONS_ROUTE, query)
+        }
+        None => QUESTIONS_ROUTE.to_string(),
+    }
 }
 
-/// This function makes a GET request to the backend API to retrieve all questions.
+/// Lists the questions, or searches them with `?q=`, unless a fresh answer is cached.
+///
+/// # Parameters
+/// - `list_parameters`: The query string of the request.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
 ///
 /// # Returns
-/// A string-formatted JSON response or a Warp rejection.
-pub async fn retrieve_all_questions() -> Result<String, Rejection> {
-    let backend_api_url = "http://localhost:1000/getAllQuestions";
-    fetch_from_backend_api(backend_api_url).await
+/// The backend answer with its status and headers, or a Warp rejection.
+pub async fn list_questions(
+    list_parameters: ListParameters,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    cached_get_from_backend_api(
+        &backend_client,
+        &response_cache,
+        &questions_path(&list_parameters),
+    )
+    .await
 }
 
-/// This function sends a GET request to the backend API to obtain a question by ID.
+/// Sends a GET request to the backend API to obtain a question by ID,
+/// unless a fresh answer is cached.
 ///
 /// # Parameters
 /// - `question_id`: The ID of the question to be retrieved.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
 ///
 /// # Returns
-/// A string-formatted JSON response or a Warp rejection.
-pub async fn retrieve_question_by_id(question_id: i32) -> Result<String, Rejection> {
-    let backend_api_url = format!("http://localhost:1000/getQuestionByID/{}", question_id);
-    fetch_from_backend_api(&backend_api_url).await
+/// The backend answer (the question, or the error with its status), or a Warp rejection.
+pub async fn get_question(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    cached_get_from_backend_api(
+        &backend_client,
+        &response_cache,
+        &question_path(question_id),
+    )
+    .await
 }
 
-/// This function makes a POST request to the backend API to add a new question.
+/// Sends a POST request to the backend API to add a new question.
 ///
 /// # Parameters
 /// - `new_question`: The question information must be supplied.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
 ///
 /// # Returns
-/// A JSON response or a Warp rejection.
-pub async fn add_new_question(new_question: NewQuestion) -> Result<impl warp::Reply, Rejection> {
-    let backend_api_url = "http://localhost:1000/addQuestion";
-    let json_data =
-        send_request_to_backend_api(backend_api_url, reqwest::Method::POST, Some(&new_question))
-            .await?;
-    Ok(warp::reply::json(&json_data))
+/// The backend answer with its status and headers, or a Warp rejection.
+pub async fn create_question(
+    new_question: NewQuestion,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    proxy_change_to_backend_api(
+        &backend_client,
+        &response_cache,
+        Method::POST,
+        QUESTIONS_ROUTE,
+        &HeaderMap::new(),
+        Some(&new_question),
+    )
+    .await
 }
 
-/// Sends a PATCH request to the backend API to update an existing inquiry based on its ID.
+/// Sends a PUT request to the backend API to replace every field of a question.
 ///
 /// # Parameters
-/// - `question_id`: The question ID that has to be modified.
-/// - `updated_question`: Updated question data.
+/// - `question_id`: The ID of the question to replace.
+/// - `replacement`: The complete new question, validated by the backend.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+///
+/// # Returns
+/// The backend answer with its status and headers, or a Warp rejection.
+pub async fn replace_question(
+    question_id: i32,
+    replacement: Value,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    proxy_change_to_backend_api(
+        &backend_client,
+        &response_cache,
+        Method::PUT,
+        &question_path(question_id),
+        &HeaderMap::new(),
+        Some(&replacement),
+    )
+    .await
+}
+
+/// Sends a PATCH request to the backend API to change some fields of a question.
+///
+/// The `Content-Type` of the request is forwarded, so the backend can tell a JSON Merge Patch
+/// from a JSON Patch.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to change.
+/// - `content_type`: The `Content-Type` of the request, if any.
+/// - `patch_body`: The raw patch.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+///
+/// # Returns
+/// The backend answer with its status and headers, a JSON error when the patch isn't JSON, or a Warp rejection.
+pub async fn patch_question(
+    question_id: i32,
+    content_type: Option<String>,
+    patch_body: Bytes,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    let question_patch: Value = match serde_json::from_slice(&patch_body) {
+        Ok(question_patch) => question_patch,
+        Err(error) => {
+            return Ok(error_reply(
+                ErrorCode::InvalidPatch,
+                &format!("Invalid patch: {}", error),
+            ))
+        }
+    };
+    let mut headers = HeaderMap::new();
+    if let Some(content_type) = content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        headers.insert(header::CONTENT_TYPE.as_str(), content_type);
+    }
+    proxy_change_to_backend_api(
+        &backend_client,
+        &response_cache,
+        Method::PATCH,
+        &question_path(question_id),
+        &headers,
+        Some(&question_patch),
+    )
+    .await
+}
+
+/// Sends a DELETE request to the backend API to delete a question.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question that will be erased.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
 ///
 /// # Returns
-/// A Warp reply containing the backend APIs JSON response, or a Warp rejection.
+/// The backend answer with its status and headers, or a Warp rejection.
+pub async fn delete_question(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<warp::reply::Response, Rejection> {
+    proxy_change_to_backend_api(
+        &backend_client,
+        &response_cache,
+        Method::DELETE,
+        &question_path(question_id),
+        &HeaderMap::new(),
+        None::<&()>,
+    )
+    .await
+}
+
+/// Deprecated alias of `list_questions` at `/allQuestions`.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
+pub async fn retrieve_all_questions(
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<impl warp::Reply, Rejection> {
+    let reply = list_questions(ListParameters::default(), backend_client, response_cache).await?;
+    Ok(deprecated_alias(reply, QUESTIONS_ROUTE))
+}
+
+/// Deprecated alias of `get_question` at `/getQuestionByID/:id`.
+///
+/// # Parameters
+/// - `question_id`: The ID of the question to be retrieved.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers.
+pub async fn retrieve_question_by_id(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<impl warp::Reply, Rejection> {
+    let reply = get_question(question_id, backend_client, response_cache).await?;
+    Ok(deprecated_alias(reply, &question_path(question_id)))
+}
+
+/// Deprecated alias of `create_question` at `/addQuestion`.
 ///
-/// # Errors
-/// If the backend API request fails, this method returns a 'RetrieveError'.
+/// # Parameters
+/// - `new_question`: The question information must be supplied.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+pub async fn add_new_question(
+    new_question: NewQuestion,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<impl warp::Reply, Rejection> {
+    let reply = create_question(new_question, backend_client, response_cache).await?;
+    Ok(deprecated_alias(reply, QUESTIONS_ROUTE))
+}
+
+/// Deprecated alias of `patch_question` at `/updateQuestion/:id`.
+///
+/// # Parameters
+/// - `question_id`: The question ID that has to be modified.
+/// - `question_changes`: Updated question data.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
 pub async fn update_question_by_id(
     question_id: i32,
-    updated_question: NewQuestion,
+    question_changes: Value,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
 ) -> Result<impl warp::Reply, Rejection> {
-    // Create the backend API URL using the question ID.
-    let backend_api_url = format!("http://localhost:1000/updateQuestion/{}", question_id);
-    let json_data = send_request_to_backend_api(
-        &backend_api_url,
-        reqwest::Method::PATCH,
-        Some(&updated_question),
+    let reply = proxy_change_to_backend_api(
+        &backend_client,
+        &response_cache,
+        Method::PATCH,
+        &question_path(question_id),
+        &HeaderMap::new(),
+        Some(&question_changes),
     )
     .await?;
-    Ok(warp::reply::json(&json_data))
+    Ok(deprecated_alias(reply, &question_path(question_id)))
 }
 
-/// To delete a question by ID, sends a DELETE request to the backend API.
+/// Deprecated alias of `delete_question` at `/deleteQuestion/:id`.
 ///
 /// # Parameters
 /// - `question_id`: The ID of the question that will be erased.
+/// - `backend_client`: The shared client of the backend API.
+/// - `response_cache`: The shared cache of backend answers, emptied on success.
+pub async fn delete_question_by_id(
+    question_id: i32,
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+) -> Result<impl warp::Reply, Rejection> {
+    let reply = delete_question(question_id, backend_client, response_cache).await?;
+    Ok(deprecated_alias(reply, &question_path(question_id)))
+}
+
+/// The state of the connection between the Frontend and the backend API.
+#[derive(Serialize)]
+struct BackendStatus<'a> {
+    backend_url: &'a str,
+    circuit_breaker: CircuitBreakerStatus,
+}
+
+/// Reports the circuit breaker of the backend API client, without calling the backend.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// The backend URL and the state of the circuit breaker as JSON.
+pub async fn report_backend_status(
+    backend_client: Arc<QuestionsClient>,
+) -> Result<impl warp::Reply, Rejection> {
+    Ok(warp::reply::json(&BackendStatus {
+        backend_url: backend_client.base_url(),
+        circuit_breaker: backend_client.circuit_status(),
+    }))
+}
+
+/// Longest time the readiness probe waits for the backend API, a bit more than the
+/// time the Backend gives its own checks.
+const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Answers as long as the Frontend serves requests, without calling the backend API.
 ///
 /// # Returns
-/// A JSON answer or Warp rejection.
-pub async fn delete_question_by_id(question_id: i32) -> Result<impl warp::Reply, Rejection> {
-    let backend_api_url = format!("http://localhost:1000/deleteQuestion/{}", question_id);
-    let json_data =
-        send_request_to_backend_api(&backend_api_url, reqwest::Method::DELETE, None::<&()>).await?;
-    Ok(warp::reply::json(&json_data))
+/// `200 OK` with a JSON message.
+pub async fn report_health() -> Result<impl warp::Reply, Rejection> {
+    Ok(warp::reply::json(&MessageResponse {
+        message: "alive".to_string(),
+    }))
+}
+
+/// Checks that the backend API is ready, by calling its own readiness probe.
+///
+/// # Parameters
+/// - `backend_client`: The shared client of the backend API.
+///
+/// # Returns
+/// `200 OK` with the report when the backend API is ready, `503 Service Unavailable` otherwise.
+pub async fn report_readiness(
+    backend_client: Arc<QuestionsClient>,
+) -> Result<impl warp::Reply, Rejection> {
+    let backend_check =
+        match tokio::time::timeout(READINESS_CHECK_TIMEOUT, backend_client.readiness()).await {
+            Ok(Ok(backend_report)) if backend_report.ready => ReadinessCheck::passed("backend"),
+            Ok(Ok(backend_report)) => {
+                let failed_checks: Vec<String> = backend_report
+                    .checks
+                    .iter()
+                    .filter(|check| !check.ready)
+                    .map(|check| match &check.detail {
+                        Some(detail) => format!("{}: {}", check.name, detail),
+                        None => check.name.clone(),
+                    })
+                    .collect();
+                ReadinessCheck::failed(
+                    "backend",
+                    format!("Backend is not ready ({})", failed_checks.join("; ")),
+                )
+            }
+            Ok(Err(error)) => ReadinessCheck::failed("backend", error.to_string()),
+            Err(_) => ReadinessCheck::failed(
+                "backend",
+                format!(
+                    "No answer within {} ms",
+                    READINESS_CHECK_TIMEOUT.as_millis()
+                ),
+            ),
+        };
+    let report = ReadinessReport::new(vec![backend_check]);
+    if !report.ready {
+        tracing::warn!(?report, "Frontend is not ready");
+    }
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&report), status))
 }
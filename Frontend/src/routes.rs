@@ -1,70 +1,353 @@
-use warp::{http::Response, Filter};
+use questions_client::QuestionsClient;
+use std::convert::Infallible;
+use std::sync::Arc;
+use warp::http::{header, HeaderValue};
+use warp::Filter;
 
+use crate::event_stream_proxy::{relay_question_events, EventStreams};
+use crate::metrics::{render_metrics, FrontendMetrics};
+use crate::page_handlers::{
+    render_edit_question_page, render_new_question_page, render_question_detail_page,
+    render_question_list_page, render_search_results_page, submit_delete_question_form,
+    submit_edit_question_form, submit_new_question_form,
+};
+use crate::presence::{open_presence_socket, PresenceParameters, QuestionPresence};
+use crate::response_cache::ResponseCache;
+use crate::response_compression::compress_reply;
 use crate::route_handlers::{
-    add_new_question, delete_question_by_id, retrieve_all_questions, retrieve_question_by_id,
+    add_new_question, create_question, delete_question, delete_question_by_id, get_question,
+    handle_rejection, list_questions, patch_question, replace_question, report_backend_status,
+    report_health, report_readiness, retrieve_all_questions, retrieve_question_by_id,
     update_question_by_id,
 };
+use crate::static_assets::{serve_entry_page, serve_static_asset, StaticAssets};
+use common::ListParameters;
 
-/// Utility function for formatting the API response.
-fn api_format_response(response_body: String) -> Result<impl warp::Reply, warp::Rejection> {
-    Ok(Response::builder()
-        .header("Content-Type", "application/json")
-        .body(response_body)
-        .unwrap())
+/// Create a filter that passes the shared metrics to the route handlers.
+fn with_metrics(
+    metrics: Arc<FrontendMetrics>,
+) -> impl Filter<Extract = (Arc<FrontendMetrics>,), Error = Infallible> + Clone {
+    warp::any().map(move || metrics.clone())
 }
 
-/// Create a route to serve static files (HTML, CSS, and JavaScript).
-fn static_file_routes(
-    path: &'static str,
-    file: &'static str,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    warp::path(path).and(warp::fs::file(file))
+/// Create a filter that passes the shared static assets server to the route handlers.
+fn with_static_assets(
+    static_assets: Arc<StaticAssets>,
+) -> impl Filter<Extract = (Arc<StaticAssets>,), Error = Infallible> + Clone {
+    warp::any().map(move || static_assets.clone())
+}
+
+/// Create a filter that refuses request bodies larger than the configured limit before reading them.
+fn with_body_limit(
+    max_body_bytes: u64,
+) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::body::content_length_limit(max_body_bytes)
+}
+
+/// Content Security Policy of the HTML pages: only the Frontend's own scripts, styles and
+/// forms, images from anywhere over HTTPS as question content may embed them, and no framing.
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; img-src 'self' https: data:; \
+     object-src 'none'; base-uri 'self'; form-action 'self'; frame-ancestors 'none'";
+
+/// Add the security headers to a reply: `X-Content-Type-Options` on every reply, and the
+/// Content Security Policy, frame and referrer policies on the HTML pages.
+///
+/// # Parameters
+/// - `reply`: The reply of a route, or of the rejection handler.
+fn with_security_headers(reply: impl warp::Reply) -> warp::reply::Response {
+    let mut response = reply.into_response();
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/html"));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    if is_html {
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(CONTENT_SECURITY_POLICY),
+        );
+        headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+        headers.insert(
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("same-origin"),
+        );
+    }
+    response
+}
+
+/// Create a filter that passes the shared backend API client to the route handlers.
+fn with_backend_client(
+    backend_client: Arc<QuestionsClient>,
+) -> impl Filter<Extract = (Arc<QuestionsClient>,), Error = Infallible> + Clone {
+    warp::any().map(move || backend_client.clone())
+}
+
+/// Create a filter that passes the shared set of relayed event streams to the route handlers.
+fn with_event_streams(
+    event_streams: Arc<EventStreams>,
+) -> impl Filter<Extract = (Arc<EventStreams>,), Error = Infallible> + Clone {
+    warp::any().map(move || event_streams.clone())
+}
+
+/// Create a filter that passes the shared presence of the update form to the route handlers.
+fn with_presence(
+    presence: Arc<QuestionPresence>,
+) -> impl Filter<Extract = (Arc<QuestionPresence>,), Error = Infallible> + Clone {
+    warp::any().map(move || presence.clone())
+}
+
+/// Create a filter that passes the shared response cache to the route handlers.
+fn with_response_cache(
+    response_cache: Arc<ResponseCache>,
+) -> impl Filter<Extract = (Arc<ResponseCache>,), Error = Infallible> + Clone {
+    warp::any().map(move || response_cache.clone())
 }
 
 /// Configure the routes to serve static files and API endpoints.
 ///
+/// # Parameters
+/// - `backend_client`: The backend API client shared by every route.
+/// - `response_cache`: The cache of backend answers shared by every route.
+/// - `static_assets`: The server of the embedded static files.
+/// - `metrics`: The metrics served at `/metrics`.
+/// - `event_streams`: The event streams relayed to the browsers.
+/// - `presence`: The presence of the clients of the update form.
+/// - `max_body_bytes`: The largest request body accepted.
+/// - `compression_threshold`: The smallest answer compressed, or `None` to never compress.
+///
 /// # Returns
 /// A combination filter that covers all routes.
-pub fn create_routes() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    // Serve static files
-    let html_route = warp::path::end().and(warp::fs::file("templates/index.html"));
-    let css_route = static_file_routes("index.css", "templates/index.css");
-    let js_route = static_file_routes("index.js", "templates/index.js");
+#[allow(clippy::too_many_arguments)]
+pub fn create_routes(
+    backend_client: Arc<QuestionsClient>,
+    response_cache: Arc<ResponseCache>,
+    static_assets: Arc<StaticAssets>,
+    metrics: Arc<FrontendMetrics>,
+    event_streams: Arc<EventStreams>,
+    presence: Arc<QuestionPresence>,
+    max_body_bytes: u64,
+    compression_threshold: Option<u16>,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    // Serve the static files embedded in the binary (HTML, CSS, and JavaScript)
+    let html_route = warp::path::end()
+        .and(warp::get().or(warp::head()).unify())
+        .and(warp::header::optional::<String>("accept-encoding"))
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_static_assets(static_assets.clone()))
+        .and_then(serve_entry_page);
+    let static_asset_route = warp::path::param::<String>()
+        .and(warp::path::end())
+        .and(warp::get().or(warp::head()).unify())
+        .and(warp::header::optional::<String>("accept-encoding"))
+        .and(warp::header::optional::<String>("if-none-match"))
+        .and(with_static_assets(static_assets))
+        .and_then(serve_static_asset);
 
-    // API routes for fetching questions
+    // Status route reporting the circuit breaker of the backend API client
+    let backend_status_route = warp::path!("backendStatus")
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(report_backend_status);
+
+    // Liveness and readiness probes, the latter checking the readiness of the backend API
+    let health_route = warp::path!("healthz")
+        .and(warp::get())
+        .and_then(report_health);
+    let readiness_route = warp::path!("readyz")
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(report_readiness);
+
+    // Metrics route in the Prometheus text format
+    let metrics_route = warp::path!("metrics")
+        .and(warp::get())
+        .and(with_metrics(metrics))
+        .and_then(render_metrics);
+
+    // Live stream of the changes of the questions, relayed from the backend API
+    let events_route = warp::path!("events")
+        .and(warp::get())
+        .and(warp::header::optional::<String>("last-event-id"))
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_event_streams(event_streams))
+        .and_then(relay_question_events);
+
+    // WebSocket telling the clients of the update form who views and who edits each question
+    let presence_route = warp::path!("presence")
+        .and(warp::ws())
+        .and(warp::query::<PresenceParameters>())
+        .and(warp::header::optional::<String>("origin"))
+        .and(warp::header::optional::<String>("host"))
+        .and(with_presence(presence))
+        .and_then(open_presence_socket);
+
+    // Versioned resource routes of the questions collection
+    let questions_collection_route = warp::path!("api" / "v1" / "questions")
+        .and(warp::get())
+        .and(warp::query::<ListParameters>())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(list_questions)
+        .or(warp::path!("api" / "v1" / "questions")
+            .and(warp::post())
+            .and(with_body_limit(max_body_bytes))
+            .and(warp::body::json())
+            .and(with_backend_client(backend_client.clone()))
+            .and(with_response_cache(response_cache.clone()))
+            .and_then(create_question));
+
+    // Versioned resource routes of a single question
+    let question_item_route = warp::path!("api" / "v1" / "questions" / i32)
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(get_question)
+        .or(warp::path!("api" / "v1" / "questions" / i32)
+            .and(warp::put())
+            .and(with_body_limit(max_body_bytes))
+            .and(warp::body::json())
+            .and(with_backend_client(backend_client.clone()))
+            .and(with_response_cache(response_cache.clone()))
+            .and_then(replace_question))
+        .or(warp::path!("api" / "v1" / "questions" / i32)
+            .and(warp::patch())
+            .and(warp::header::optional::<String>("content-type"))
+            .and(with_body_limit(max_body_bytes))
+            .and(warp::body::bytes())
+            .and(with_backend_client(backend_client.clone()))
+            .and(with_response_cache(response_cache.clone()))
+            .and_then(patch_question))
+        .or(warp::path!("api" / "v1" / "questions" / i32)
+            .and(warp::delete())
+            .and(with_backend_client(backend_client.clone()))
+            .and(with_response_cache(response_cache.clone()))
+            .and_then(delete_question));
+
+    // Deprecated legacy route for fetching questions
     let fetch_all_questions_route = warp::path("allQuestions")
-        .and_then(retrieve_all_questions)
-        .and_then(|response_body| async { api_format_response(response_body) });
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(retrieve_all_questions);
 
-    // API route for fetching questions by ID
+    // Deprecated legacy route for fetching questions by ID
     let fetch_question_by_id_route = warp::path!("getQuestionByID" / i32)
-        .and_then(retrieve_question_by_id)
-        .and_then(|response_body| async { api_format_response(response_body) });
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(retrieve_question_by_id);
 
-    // API route for adding a new question
+    // Deprecated legacy route for adding a new question
     let add_new_question_route = warp::path("addQuestion")
         .and(warp::post())
+        .and(with_body_limit(max_body_bytes))
         .and(warp::body::json())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
         .and_then(add_new_question);
 
-    // API route for updating an existing question
+    // Deprecated legacy route for updating an existing question
     let update_questions_route = warp::path!("updateQuestion" / i32)
         .and(warp::patch())
+        .and(with_body_limit(max_body_bytes))
         .and(warp::body::json())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
         .and_then(update_question_by_id);
 
-    // API route for deleting a question
+    // Deprecated legacy route for deleting a question
     let delete_question_route = warp::path!("deleteQuestion" / i32)
         .and(warp::delete())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
         .and_then(delete_question_by_id);
 
-    // Combine all routes
-    html_route
+    // Server-side rendered page listing all questions
+    let question_list_page_route = warp::path!("questions")
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(render_question_list_page);
+
+    // Server-side rendered search page
+    let search_results_page_route = warp::path!("questions" / "search")
+        .and(warp::get())
+        .and(warp::query())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(render_search_results_page);
+
+    // Server-side rendered form for adding a question
+    let new_question_page_route = warp::path!("questions" / "new")
+        .and(warp::get())
+        .and_then(render_new_question_page);
+
+    // Server-side rendered detail page of a question
+    let question_detail_page_route = warp::path!("questions" / i32)
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(render_question_detail_page);
+
+    // Server-side rendered form for updating a question
+    let edit_question_page_route = warp::path!("questions" / i32 / "edit")
+        .and(warp::get())
+        .and(with_backend_client(backend_client.clone()))
+        .and_then(render_edit_question_page);
+
+    // HTML form submissions, which only support GET and POST
+    let submit_new_question_route = warp::path!("questions")
+        .and(warp::post())
+        .and(with_body_limit(max_body_bytes))
+        .and(warp::body::form())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(submit_new_question_form);
+    let submit_edit_question_route = warp::path!("questions" / i32 / "edit")
+        .and(warp::post())
+        .and(with_body_limit(max_body_bytes))
+        .and(warp::body::form())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(submit_edit_question_form);
+    let submit_delete_question_route = warp::path!("questions" / i32 / "delete")
+        .and(warp::post())
+        .and(with_backend_client(backend_client.clone()))
+        .and(with_response_cache(response_cache.clone()))
+        .and_then(submit_delete_question_form);
+
+    let page_routes = question_list_page_route
+        .or(search_results_page_route)
+        .or(new_question_page_route)
+        .or(question_detail_page_route)
+        .or(edit_question_page_route)
+        .or(submit_new_question_route)
+        .or(submit_edit_question_route)
+        .or(submit_delete_question_route);
+
+    // Combine all routes, turning backend failures into JSON errors, with the security headers,
+    // and compress the answers as negotiated by `Accept-Encoding`
+    let all_routes = html_route
+        .or(questions_collection_route)
+        .or(question_item_route)
         .or(fetch_all_questions_route)
         .or(fetch_question_by_id_route)
         .or(add_new_question_route)
         .or(update_questions_route)
         .or(delete_question_route)
-        .or(css_route)
-        .or(js_route)
+        .or(backend_status_route)
+        .or(health_route)
+        .or(readiness_route)
+        .or(metrics_route)
+        .or(events_route)
+        .or(presence_route)
+        .or(page_routes)
+        .or(static_asset_route)
+        .recover(handle_rejection)
+        .map(with_security_headers);
+    warp::header::optional::<String>("accept-encoding")
+        .and(all_routes)
+        .and_then(move |accept_encoding, reply| {
+            compress_reply(accept_encoding, reply, compression_threshold)
+        })
 }
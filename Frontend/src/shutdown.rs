@@ -0,0 +1,186 @@
+use crate::event_stream_proxy::EventStreams;
+use crate::presence::QuestionPresence;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// An error that stopped the server.
+pub type ServerError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Future completing when the server must stop accepting connections.
+pub type ShutdownSignal = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Counts the requests being answered, so that the shutdown can tell how many it drained.
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    active: AtomicUsize,
+    served: AtomicU64,
+}
+
+impl InFlightRequests {
+    /// Counts a request among the active ones until the returned guard is dropped.
+    ///
+    /// # Parameters
+    /// - `self`: The shared counters.
+    ///
+    /// # Returns
+    /// The guard to keep while the request is being answered.
+    pub fn start(self: &Arc<Self>) -> InFlightGuard {
+        self.active.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(self.clone())
+    }
+
+    /// The number of requests being answered.
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// The number of requests answered since the server started.
+    pub fn served(&self) -> u64 {
+        self.served.load(Ordering::Relaxed)
+    }
+}
+
+/// Decrements the active requests when the request is answered or abandoned.
+pub struct InFlightGuard(Arc<InFlightRequests>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::Relaxed);
+        self.0.served.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// What happened while the server was shutting down.
+#[derive(Debug)]
+pub struct ShutdownSummary {
+    /// The signal that started the shutdown.
+    pub signal: &'static str,
+    /// Time between the signal and the end of the draining.
+    pub drain_duration: Duration,
+    /// Requests still being answered when the deadline passed, or a second signal came.
+    pub abandoned_requests: usize,
+    /// Requests answered since the server started.
+    pub served_requests: u64,
+}
+
+/// Runs a server until SIGTERM or SIGINT, then lets it stop accepting connections and waits
+/// for the requests being answered, at most for the drain timeout.
+///
+/// The relayed event streams and the presence sockets are ended when the draining starts, as
+/// they would otherwise never finish, and the draining lasts until the sockets are closed. A second signal while draining stops waiting at once.
+///
+/// # Parameters
+/// - `serve`: Starts the server, which stops accepting connections when the given signal completes.
+/// - `in_flight_requests`: The counters of the requests answered by the server.
+/// - `drain_timeout`: Longest time to wait for the requests being answered.
+/// - `event_streams`: The event streams relayed to the browsers.
+/// - `presence`: The presence whose sockets are closed.
+///
+/// # Returns
+/// The summary of the shutdown, or the error that stopped the server.
+pub async fn serve_until_drained<S, E>(
+    serve: impl FnOnce(ShutdownSignal) -> S,
+    in_flight_requests: Arc<InFlightRequests>,
+    drain_timeout: Duration,
+    event_streams: Arc<EventStreams>,
+    presence: Arc<QuestionPresence>,
+) -> Result<ShutdownSummary, ServerError>
+where
+    S: Future<Output = Result<(), E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let (shutdown_sender, mut shutdown_receiver) = oneshot::channel();
+    let draining_requests = in_flight_requests.clone();
+    let closing_presence = presence.clone();
+    let server = serve(Box::pin(async move {
+        let signal = wait_for_shutdown_signal().await;
+        tracing::info!(
+            signal,
+            active_requests = draining_requests.active(),
+            drain_timeout_secs = drain_timeout.as_secs(),
+            "Shutdown requested, draining the requests being answered"
+        );
+        event_streams.close();
+        presence.close();
+        let _ = shutdown_sender.send((signal, Instant::now()));
+    }));
+    // The presence sockets are upgraded out of the server, which doesn't wait for them
+    let server = async move {
+        let served = server.await;
+        if served.is_ok() {
+            closing_presence.sockets_closed().await;
+        }
+        served
+    };
+    tokio::pin!(server);
+
+    let ((signal, shutdown_started_at), drained) = tokio::select! {
+        served = &mut server => {
+            served?;
+            // With no connection left, the server may stop as soon as the signal is sent
+            match shutdown_receiver.try_recv() {
+                Ok(shutdown_started) => (shutdown_started, true),
+                Err(_) => return Err("The server stopped without a shutdown signal".into()),
+            }
+        }
+        Ok(shutdown_started) = &mut shutdown_receiver => (shutdown_started, false),
+    };
+    if !drained {
+        tokio::select! {
+            served = &mut server => served?,
+            _ = tokio::time::sleep(drain_timeout) => {
+                tracing::warn!("Drain timeout reached, abandoning the requests being answered");
+            }
+            _ = wait_for_shutdown_signal() => {
+                tracing::warn!("Second shutdown signal, abandoning the requests being answered");
+            }
+        }
+    }
+    Ok(ShutdownSummary {
+        signal,
+        drain_duration: shutdown_started_at.elapsed(),
+        abandoned_requests: in_flight_requests.active(),
+        served_requests: in_flight_requests.served(),
+    })
+}
+
+/// Waits for SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+///
+/// # Returns
+/// The name of the signal received.
+async fn wait_for_shutdown_signal() -> &'static str {
+    let interrupt = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "SIGINT",
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't listen for SIGINT");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate_signal) => {
+                terminate_signal.recv().await;
+                "SIGTERM"
+            }
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't listen for SIGTERM");
+                std::future::pending().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<&'static str>();
+
+    tokio::select! {
+        signal = interrupt => signal,
+        signal = terminate => signal,
+    }
+}
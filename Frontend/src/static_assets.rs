@@ -0,0 +1,220 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use warp::http::{header, Response, StatusCode};
+use warp::hyper::Body;
+use warp::Rejection;
+
+use crate::response_compression::accepts_encoding;
+
+/// A static asset compiled into the binary by `build.rs`.
+pub struct EmbeddedAsset {
+    /// The name of the file in `templates/`, e.g. `index.css`.
+    pub file_name: &'static str,
+    /// The name with the content hash, e.g. `index.0123456789abcdef.css`.
+    pub hashed_name: &'static str,
+    /// The `Content-Type` of the asset.
+    pub content_type: &'static str,
+    /// The first 16 hexadecimal digits of the SHA-256 of the asset, also used as `ETag`.
+    pub content_hash: &'static str,
+    /// The asset as is.
+    pub body: &'static [u8],
+    /// The asset compressed with gzip.
+    pub gzip_body: &'static [u8],
+    /// The asset compressed with brotli.
+    pub brotli_body: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+
+/// Cache policy of the URLs that contain the content hash, which never change.
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Cache policy of the URLs without hash, which browsers must revalidate with the `ETag`.
+const REVALIDATE_CACHE_CONTROL: &str = "no-cache";
+
+/// Serves the embedded static assets, or the files of an override directory during development.
+pub struct StaticAssets {
+    override_dir: Option<PathBuf>,
+}
+
+/// Finds the embedded asset of a file name, with or without content hash.
+///
+/// # Parameters
+/// - `requested_name`: The last segment of the requested path.
+///
+/// # Returns
+/// The asset and whether the hashed name was requested.
+fn find_embedded_asset(requested_name: &str) -> Option<(&'static EmbeddedAsset, bool)> {
+    EMBEDDED_ASSETS.iter().find_map(|asset| {
+        if asset.hashed_name == requested_name {
+            Some((asset, true))
+        } else if asset.file_name == requested_name {
+            Some((asset, false))
+        } else {
+            None
+        }
+    })
+}
+
+/// The URL of an embedded asset with its content hash, for links in the HTML pages.
+///
+/// # Parameters
+/// - `file_name`: The name of the file in `templates/`, e.g. `index.css`.
+pub fn asset_url(file_name: &str) -> String {
+    match find_embedded_asset(file_name) {
+        Some((asset, _)) => format!("/{}", asset.hashed_name),
+        None => format!("/{}", file_name),
+    }
+}
+
+/// The encodings of the precompressed variants, from most to least preferred.
+#[derive(Clone, Copy, PartialEq)]
+enum ContentEncoding {
+    Brotli,
+    Gzip,
+    Identity,
+}
+
+/// Picks the best encoding accepted by the browser.
+///
+/// # Parameters
+/// - `accept_encoding`: The `Accept-Encoding` header of the request, if any.
+fn negotiate_encoding(accept_encoding: Option<&str>) -> ContentEncoding {
+    let Some(accept_encoding) = accept_encoding else {
+        return ContentEncoding::Identity;
+    };
+    let accepts = |encoding_name: &str| accepts_encoding(accept_encoding, encoding_name);
+    if accepts("br") {
+        ContentEncoding::Brotli
+    } else if accepts("gzip") {
+        ContentEncoding::Gzip
+    } else {
+        ContentEncoding::Identity
+    }
+}
+
+impl StaticAssets {
+    /// Creates the static assets server.
+    ///
+    /// # Parameters
+    /// - `override_dir`: A directory whose files are served instead of the embedded ones,
+    ///   read again on every request so that edits show up without rebuilding.
+    pub fn new(override_dir: Option<PathBuf>) -> Self {
+        StaticAssets { override_dir }
+    }
+
+    /// Serves an asset by name.
+    ///
+    /// # Parameters
+    /// - `requested_name`: The last segment of the requested path, with or without hash.
+    /// - `accept_encoding`: The `Accept-Encoding` header of the request.
+    /// - `if_none_match`: The `If-None-Match` header of the request.
+    ///
+    /// # Returns
+    /// The asset, `304 Not Modified`, or a not found rejection.
+    pub async fn serve(
+        &self,
+        requested_name: &str,
+        accept_encoding: Option<&str>,
+        if_none_match: Option<&str>,
+    ) -> Result<warp::reply::Response, Rejection> {
+        let (asset, is_hashed_name) =
+            find_embedded_asset(requested_name).ok_or_else(warp::reject::not_found)?;
+
+        if let Some(override_dir) = &self.override_dir {
+            if let Ok(body) = tokio::fs::read(override_dir.join(asset.file_name)).await {
+                return Ok(Response::builder()
+                    .header(header::CONTENT_TYPE, asset.content_type)
+                    .header(header::CACHE_CONTROL, "no-store")
+                    .body(Body::from(body))
+                    .unwrap_or_default());
+            }
+        }
+
+        let cache_control = if is_hashed_name {
+            IMMUTABLE_CACHE_CONTROL
+        } else {
+            REVALIDATE_CACHE_CONTROL
+        };
+        let etag = format!("\"{}\"", asset.content_hash);
+        let response_builder = Response::builder()
+            .header(header::CACHE_CONTROL, cache_control)
+            .header(header::ETAG, &etag)
+            .header(header::VARY, "accept-encoding");
+
+        let etag_matches = if_none_match.is_some_and(|if_none_match| {
+            if_none_match
+                .split(',')
+                .any(|candidate| candidate.trim() == "*" || candidate.trim() == etag)
+        });
+        if etag_matches {
+            return Ok(response_builder
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Body::empty())
+                .unwrap_or_default());
+        }
+
+        // Only send a compressed variant when it is actually smaller
+        let (content_encoding, body) = match negotiate_encoding(accept_encoding) {
+            ContentEncoding::Brotli if asset.brotli_body.len() < asset.body.len() => {
+                (Some("br"), asset.brotli_body)
+            }
+            ContentEncoding::Gzip if asset.gzip_body.len() < asset.body.len() => {
+                (Some("gzip"), asset.gzip_body)
+            }
+            _ => (None, asset.body),
+        };
+        let response_builder = match content_encoding {
+            Some(content_encoding) => {
+                response_builder.header(header::CONTENT_ENCODING, content_encoding)
+            }
+            None => response_builder,
+        };
+        Ok(response_builder
+            .header(header::CONTENT_TYPE, asset.content_type)
+            .body(Body::from(body))
+            .unwrap_or_default())
+    }
+}
+
+/// Serves the entry page at `/`.
+///
+/// # Parameters
+/// - `accept_encoding`: The `Accept-Encoding` header of the request.
+/// - `if_none_match`: The `If-None-Match` header of the request.
+/// - `static_assets`: The shared static assets server.
+pub async fn serve_entry_page(
+    accept_encoding: Option<String>,
+    if_none_match: Option<String>,
+    static_assets: Arc<StaticAssets>,
+) -> Result<warp::reply::Response, Rejection> {
+    static_assets
+        .serve(
+            "index.html",
+            accept_encoding.as_deref(),
+            if_none_match.as_deref(),
+        )
+        .await
+}
+
+/// Serves a static asset by name, e.g. `/index.css` or `/index.0123456789abcdef.css`.
+///
+/// # Parameters
+/// - `requested_name`: The requested file name.
+/// - `accept_encoding`: The `Accept-Encoding` header of the request.
+/// - `if_none_match`: The `If-None-Match` header of the request.
+/// - `static_assets`: The shared static assets server.
+pub async fn serve_static_asset(
+    requested_name: String,
+    accept_encoding: Option<String>,
+    if_none_match: Option<String>,
+    static_assets: Arc<StaticAssets>,
+) -> Result<warp::reply::Response, Rejection> {
+    static_assets
+        .serve(
+            &requested_name,
+            accept_encoding.as_deref(),
+            if_none_match.as_deref(),
+        )
+        .await
+}
@@ -0,0 +1,187 @@
+use common::tls::{https_redirect_location, ReloadingTlsConfig};
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use warp::http::{header, Request, Response, StatusCode};
+use warp::hyper::server::accept::Accept;
+use warp::hyper::service::{make_service_fn, service_fn};
+use warp::hyper::{Body, Server};
+
+use crate::shutdown::ServerError;
+
+/// Longest time a browser may take to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after a failed accept, e.g. when the process ran out of file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// Connections whose handshake completed but that the server hasn't picked up yet.
+const PENDING_CONNECTIONS: usize = 64;
+
+/// A connection of a browser over TLS.
+pub struct TlsConnection {
+    stream: TlsStream<TcpStream>,
+    remote_addr: SocketAddr,
+}
+
+impl TlsConnection {
+    /// The address the connection came from.
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+}
+
+impl AsyncRead for TlsConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        buffer: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(context, buffer)
+    }
+}
+
+impl AsyncWrite for TlsConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(context, data)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(context)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(context)
+    }
+}
+
+/// The TLS connections accepted for the server, in the order their handshake completed.
+pub struct TlsIncoming {
+    connections: mpsc::Receiver<TlsConnection>,
+}
+
+impl Accept for TlsIncoming {
+    type Conn = TlsConnection;
+    type Error = io::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        context: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.connections
+            .poll_recv(context)
+            .map(|connection| connection.map(Ok))
+    }
+}
+
+/// Accepts the connections of the listener and performs their TLS handshakes, each in its
+/// own task so that a slow browser doesn't hold back the others.
+///
+/// The listener is closed once the server stops accepting connections.
+///
+/// # Parameters
+/// - `tcp_listener`: The listener bound to the configured address.
+/// - `tls_config`: The certificate, whose current version is used for every new connection.
+///
+/// # Returns
+/// The connections to serve, for `Server::builder`.
+pub fn tls_incoming(tcp_listener: TcpListener, tls_config: Arc<ReloadingTlsConfig>) -> TlsIncoming {
+    let (connection_sender, connections) = mpsc::channel(PENDING_CONNECTIONS);
+    tokio::spawn(async move {
+        loop {
+            let (tcp_stream, remote_addr) = tokio::select! {
+                accepted = tcp_listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(error) => {
+                        tracing::warn!(%error, "Couldn't accept a connection");
+                        tokio::time::sleep(ACCEPT_ERROR_DELAY).await;
+                        continue;
+                    }
+                },
+                _ = connection_sender.closed() => return,
+            };
+            let tls_acceptor = tls_config.acceptor();
+            let connection_sender = connection_sender.clone();
+            tokio::spawn(async move {
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls_acceptor.accept(tcp_stream))
+                    .await
+                {
+                    Ok(Ok(stream)) => {
+                        let _ = connection_sender
+                            .send(TlsConnection {
+                                stream,
+                                remote_addr,
+                            })
+                            .await;
+                    }
+                    Ok(Err(error)) => {
+                        tracing::debug!(%remote_addr, %error, "TLS handshake failed")
+                    }
+                    Err(_) => tracing::debug!(%remote_addr, "TLS handshake timed out"),
+                }
+            });
+        }
+    });
+    TlsIncoming { connections }
+}
+
+/// Serves a plain HTTP listener that redirects every request to the HTTPS server.
+///
+/// # Parameters
+/// - `bind_address`: The address the plain HTTP listener binds to.
+/// - `https_port`: The port of the HTTPS server.
+///
+/// # Returns
+/// An error when the listener could not be started or failed.
+pub async fn serve_https_redirect(
+    bind_address: SocketAddr,
+    https_port: u16,
+) -> Result<(), ServerError> {
+    let make_service = make_service_fn(move |_| async move {
+        Ok::<_, Infallible>(service_fn(move |request: Request<Body>| async move {
+            Ok::<_, Infallible>(redirect_to_https(&request, https_port))
+        }))
+    });
+    Server::try_bind(&bind_address)?.serve(make_service).await?;
+    Ok(())
+}
+
+/// Answers `308 Permanent Redirect` to the same host and path on the HTTPS server, keeping
+/// the method and body of the request.
+///
+/// # Parameters
+/// - `request`: The plain HTTP request.
+/// - `https_port`: The port of the HTTPS server.
+fn redirect_to_https(request: &Request<Body>, https_port: u16) -> Response<Body> {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|path_and_query| path_and_query.as_str())
+        .unwrap_or("/");
+    let response = match https_redirect_location(host, path_and_query, https_port) {
+        Some(location) => Response::builder()
+            .status(StatusCode::PERMANENT_REDIRECT)
+            .header(header::LOCATION, location)
+            .body(Body::empty()),
+        None => Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(Body::from("The Host header is missing or invalid")),
+    };
+    response.unwrap_or_else(|_| Response::new(Body::empty()))
+}
@@ -1,29 +1,44 @@
+/**
+ *  This asynchronous method reads the JSON answer of a response.
+ *  If the status is not successful, it throws an error with the message sent by the server.
+ *
+ *  @param {Response} jsonResponse - The response returned by fetch.
+ *  @returns {Promise<Object>} - The JSON answer.
+ */
+async function readJsonResponse(jsonResponse) {
+    const responseBody = await jsonResponse.json().catch(() => ({}));
+
+    if (!jsonResponse.ok) {
+        const errorMessage = responseBody.error || `Request failed with status ${jsonResponse.status}`;
+        const responseError = new Error(errorMessage);
+        responseError.status = jsonResponse.status;
+        throw responseError;
+    }
+
+    return responseBody;
+}
+
 /**
  *  This asynchronous method does a GET request to the specified URL and provides a JSON response.
- *  If the request fails, it returns an error with a message.
+ *  If the request fails, it returns an error with the message sent by the server.
  *
  *  @param {string} url - The GET request should be sent to this URL.
  *  @returns {Promise<Object>} - The backend API returned a JSON answer.
  */
 async function fetchJsonFormat(url) {
     const jsonResponse = await fetch(url);
-
-    if (!jsonResponse.ok) {
-        throw new Error('Error occurred  in network response');
-    }
-
-    return await jsonResponse.json();
+    return await readJsonResponse(jsonResponse);
 }
 
 /**
  *  This asynchronous function retrieves questions from the backend API via a GET call to the
- *  '/allQuestions' endpoint.
+ *  '/api/v1/questions' endpoint.
  *  If the request is successful, it evaluates the JSON response and executes the displayAllQuestions method to show the questions.
  *  If the request fails, it sends an error message to the console.
  */
 async function fetchAllQuestions() {
     try {
-        const allQuestions = await fetchJsonFormat('/allQuestions');
+        const allQuestions = await fetchJsonFormat('/api/v1/questions');
         displayAllQuestions(allQuestions);
     } catch (error) {
         console.error('Failed to fetch questions from database:', error);
@@ -32,7 +47,7 @@ async function fetchAllQuestions() {
 
 /**
  *  This asynchronous function retrieves a question by its ID from the backend API via a GET call to the
- *  '/getQuestionByID/:id' endpoint.
+ *  '/api/v1/questions/:id' endpoint.
  *  If the request is successful, it evaluates the JSON response and executes the displayQuestionById method to show the question details.
  *  If the request fails, it sends an error message to the console.
  */
@@ -45,25 +60,43 @@ async function fetchQuestionById(event) {
     }
 
     try {
-        const questionDetails = await fetchJsonFormat(`/getQuestionByID/${questionId}`);
+        const questionDetails = await fetchJsonFormat(`/api/v1/questions/${questionId}`);
         displayQuestionById(questionDetails);
     } catch (error) {
+        // Show the error sent by the server, such as a missing question or an unavailable backend.
         console.error('Failed to fetch question:', error);
+        displayQuestionById({error: error.message});
     }
 }
 
+/**
+ * Function that escapes text so it can be placed inside HTML without being interpreted as markup.
+ *
+ * @param {*} value - The value to escape.
+ * @returns {string} - The escaped text.
+ */
+function escapeHtml(value) {
+    return String(value)
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;')
+        .replace(/'/g, '&#39;');
+}
+
 /**
  * Function that generates HTML content for a query.
+ * Plain text fields are escaped, and the content is shown only in the form sanitized by the backend.
  *
  * @param {Object} question - The question object contains its details.
  * @returns {string} - HTML content for the question.
  */
 function htmlFormat(question) {
     return `
-        <div class="id">Question ID: ${question.question_id}</div>
-        <div class="title">Question Title: ${question.question_title}</div>
-        <div class="content-type">Type of Content: ${question.type_of_content}</div>
-        <div class="question-types">Type of Question: ${question.type_of_question.join(', ')}</div>
+        <div class="id">Question ID: ${escapeHtml(question.question_id)}</div>
+        <div class="title">Question Title: ${escapeHtml(question.question_title)}</div>
+        <div class="content-type">Type of Content: <div class="rendered-content">${question.type_of_content_html}</div></div>
+        <div class="question-types">Type of Question: ${escapeHtml(question.type_of_question.join(', '))}</div>
     `;
 }
 
@@ -97,7 +130,7 @@ function displayQuestionById(questionDetail) {
     const questionDetailElement = document.getElementById('questionDetail');
 
     if (questionDetail.error) {
-        questionDetailElement.innerHTML = `<div class="error">${questionDetail.error}</div>`;
+        questionDetailElement.innerHTML = `<div class="error">${escapeHtml(questionDetail.error)}</div>`;
     } else {
         questionDetailElement.innerHTML = htmlFormat(questionDetail);
     }
@@ -127,7 +160,7 @@ async function addNewQuestion(event) {
 
     try {
         // To add the new question, send a POST request to the backend.
-        const json_response = await fetch('/addQuestion', {
+        const json_response = await fetch('/api/v1/questions', {
             method: 'POST',
             headers: {
                 'Content-Type': 'application/json'
@@ -135,13 +168,8 @@ async function addNewQuestion(event) {
             body: JSON.stringify(newQuestion)
         });
 
-        // Check if the json_response is not okay, and throw an error if it is.
-        if (!json_response.ok) {
-            throw new Error('Error occurred in network json_response');
-        }
-
-        // Parse the server's JSON answer.
-        const response_result = await json_response.json();
+        // Parse the server's JSON answer, throwing the server's error if the status is not okay.
+        const response_result = await readJsonResponse(json_response);
 
         // Send a message to the user from the server.
         alert(response_result.message);
@@ -155,7 +183,7 @@ async function addNewQuestion(event) {
 
         // If the request fails, log the error in the console and notify the user.
         console.error('Failed to add a new question:', error);
-        alert('Failed to add a new question. Please try again!');
+        alert(`Failed to add a new question: ${error.message}`);
     }
 }
 
@@ -181,28 +209,28 @@ async function updateQuestion(event) {
     };
 
     try {
-        const json_response = await fetch(`/updateQuestion/${questionId}`, {
+        const json_response = await fetch(`/api/v1/questions/${questionId}`, {
             method: 'PATCH',
             headers: {
-                'Content-Type': 'application/json'
+                'Content-Type': 'application/merge-patch+json'
             },
             body: JSON.stringify(updatedQuestion)
         });
 
-        if (!json_response.ok) {
-            throw new Error('Error occurred  in network response');
-        }
-
-        const response_result = await json_response.json();
+        const response_result = await readJsonResponse(json_response);
         alert(response_result.message);
 
-        // Reset form inputs.
+        // Give the edit lock back and reset form inputs, keeping the name shown to others.
+        sendPresence({ action: 'release' });
+        const presenceName = document.getElementById('presenceName').value;
         document.getElementById('updateQuestionForm').reset();
+        document.getElementById('presenceName').value = presenceName;
+        announceUpdatedQuestion();
         await fetchAllQuestions(); // Refresh the list of questions
     } catch (error) {
         // If the request fails, log the error in the console and notify the user.
         console.error('Failed to update question:', error);
-        alert('Failed to update question. Please try again.');
+        alert(`Failed to update question: ${error.message}`);
     }
 }
 
@@ -217,15 +245,11 @@ async function deleteQuestionById(event) {
     const questionId = document.getElementById('deleteQuestionId').value;
 
     try {
-        const json_response = await fetch(`/deleteQuestion/${questionId}`, {
+        const json_response = await fetch(`/api/v1/questions/${questionId}`, {
             method: 'DELETE'
         });
 
-        if (!json_response.ok) {
-            throw new Error('Error occurred  in network response');
-        }
-
-        const json_result = await json_response.json();
+        const json_result = await readJsonResponse(json_response);
         alert(json_result.message);
 
         // Reset form inputs.
@@ -234,7 +258,163 @@ async function deleteQuestionById(event) {
     } catch (error) {
         // If the request fails, log the error in the console and notify the user.
         console.error('Failed to delete the question:', error);
-        alert('Failed to delete the question. Please try again.');
+        alert(`Failed to delete the question: ${error.message}`);
+    }
+}
+
+/**
+ * Keeps the list of questions up to date with the changes made by anyone, by listening to the
+ * '/events' stream of server-sent events.
+ * The browser reconnects by itself with the ID of the last event received, and gets the events it missed.
+ * A 'resync' event means some were lost, so the whole list is fetched again, as it is for any change.
+ */
+function listenForQuestionChanges() {
+    if (!window.EventSource) {
+        return;
+    }
+
+    // The events of a batch of changes, such as several questions added at once, trigger a single refresh.
+    let pendingRefresh = null;
+    const refreshSoon = () => {
+        clearTimeout(pendingRefresh);
+        pendingRefresh = setTimeout(fetchAllQuestions, 100);
+    };
+
+    const questionEvents = new EventSource('/events');
+    ['created', 'updated', 'deleted', 'resync'].forEach(eventName => {
+        questionEvents.addEventListener(eventName, refreshSoon);
+    });
+    questionEvents.onerror = () => console.warn('Lost the stream of question changes, reconnecting');
+}
+
+// The '/presence' WebSocket, the session it is known by, and the question entered in the update form.
+let presenceSocket = null;
+let presenceSessionId = null;
+let presenceQuestionId = null;
+let presenceLockTtlMs = 60000;
+let lastLockRenewal = 0;
+let presenceReconnectDelay = 1000;
+
+/**
+ * Sends an announcement on the presence socket, when it is open.
+ *
+ * @param {Object} message - The announcement, such as {action: 'view', question_id: 7}.
+ */
+function sendPresence(message) {
+    if (presenceSocket && presenceSocket.readyState === WebSocket.OPEN) {
+        presenceSocket.send(JSON.stringify(message));
+    }
+}
+
+/**
+ * Shows who else views the question of the update form, and who holds its edit lock.
+ *
+ * @param {Object|null} snapshot - The presence of the question, or null to hide it.
+ */
+function displayPresence(snapshot) {
+    const presenceElement = document.getElementById('updatePresence');
+    if (!snapshot || snapshot.question_id !== presenceQuestionId) {
+        presenceElement.hidden = true;
+        return;
+    }
+
+    const lines = [];
+    const lock = snapshot.lock;
+    const lockedByOther = lock !== null && lock.session_id !== presenceSessionId;
+    if (lockedByOther) {
+        lines.push(`${lock.name} is editing this question, your changes may overwrite theirs (lock expires in ${lock.expires_in_secs} s).`);
+    } else if (lock !== null) {
+        lines.push('You are editing this question, the others viewing it are told so.');
+    }
+    const others = snapshot.viewers.filter(viewer => viewer.session_id !== presenceSessionId);
+    if (others.length > 0) {
+        lines.push(`Also viewing: ${others.map(viewer => viewer.name).join(', ')}.`);
+    }
+
+    // Set as text, since the names are chosen by the other people
+    presenceElement.textContent = lines.join(' ');
+    presenceElement.classList.toggle('presence-warning', lockedByOther);
+    presenceElement.hidden = lines.length === 0;
+}
+
+/**
+ * Tells the others which question is entered in the update form, or that none is.
+ */
+function announceUpdatedQuestion() {
+    const questionId = Number.parseInt(document.getElementById('updateQuestionId').value, 10);
+    const updateSectionActive = document.querySelector('#updateQuestionSection').classList.contains('active');
+    presenceQuestionId = updateSectionActive && Number.isInteger(questionId) ? questionId : null;
+    lastLockRenewal = 0;
+    displayPresence(null);
+    if (presenceQuestionId === null) {
+        sendPresence({ action: 'leave' });
+    } else {
+        sendPresence({ action: 'view', question_id: presenceQuestionId });
+    }
+}
+
+/**
+ * Takes the edit lock of the question while its fields are being changed, renewing it before it expires.
+ */
+function renewEditLock() {
+    const now = Date.now();
+    if (presenceQuestionId === null || now - lastLockRenewal < presenceLockTtlMs / 3) {
+        return;
+    }
+    lastLockRenewal = now;
+    sendPresence({ action: 'edit', question_id: presenceQuestionId });
+}
+
+/**
+ * Opens the '/presence' WebSocket, telling the others updating the same question who is there
+ * and who is editing, and opens it again whenever it closes.
+ */
+function connectPresence() {
+    if (!window.WebSocket) {
+        return;
+    }
+
+    const scheme = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+    const name = encodeURIComponent(document.getElementById('presenceName').value.trim());
+    const socket = new WebSocket(`${scheme}//${window.location.host}/presence?name=${name}`);
+    presenceSocket = socket;
+
+    socket.onopen = () => {
+        presenceReconnectDelay = 1000;
+        announceUpdatedQuestion();
+    };
+    socket.onmessage = messageEvent => {
+        const message = JSON.parse(messageEvent.data);
+        if (message.kind === 'welcome') {
+            presenceSessionId = message.session_id;
+            presenceLockTtlMs = message.lock_ttl_secs * 1000;
+        } else if (message.kind === 'presence') {
+            displayPresence(message);
+        } else if (message.kind === 'error') {
+            console.warn('Presence message refused:', message.error);
+        }
+    };
+    socket.onclose = () => {
+        // A socket replaced after a change of name is not opened again
+        if (presenceSocket !== socket) {
+            return;
+        }
+        presenceSocket = null;
+        displayPresence(null);
+        setTimeout(connectPresence, presenceReconnectDelay);
+        presenceReconnectDelay = Math.min(presenceReconnectDelay * 2, 30000);
+    };
+}
+
+/**
+ * Opens the presence socket again under the new name, and remembers it for the next visits.
+ */
+function changePresenceName() {
+    localStorage.setItem('presenceName', document.getElementById('presenceName').value.trim());
+    const previousSocket = presenceSocket;
+    connectPresence();
+    if (previousSocket) {
+        previousSocket.close();
     }
 }
 
@@ -252,6 +432,9 @@ function handlePageNavigation(event) {
     // Take the 'active' class out of all sections.
     const targetSection = document.querySelector(event.target.getAttribute('href'));
     targetSection.classList.add('active');
+
+    // Only the update form is shown to the others
+    announceUpdatedQuestion();
 }
 
 // Configure event listeners for the navigation links.
@@ -262,6 +445,9 @@ document.querySelectorAll('.nav-link').forEach(link => {
 // Display the default section when the page loads.
 document.addEventListener('DOMContentLoaded', () => {
     fetchAllQuestions().then(() => "Could not find questions in database!"); // Fetch all questions on page load
+    listenForQuestionChanges(); // Refresh the list whenever a question changes
+    document.getElementById('presenceName').value = localStorage.getItem('presenceName') || '';
+    connectPresence(); // Tell who else updates the same question
     document.querySelector('#fetchAllQuestionsSection').classList.add('active'); // Show the default section
 });
 
@@ -275,5 +461,12 @@ document.getElementById('addNewQuestionForm').addEventListener('submit', addNewQ
 // Add an event listener for the update question form
 document.getElementById('updateQuestionForm').addEventListener('submit', updateQuestion);
 
+// Tell the others which question is being updated, and take its edit lock while its fields change
+document.getElementById('updateQuestionId').addEventListener('change', announceUpdatedQuestion);
+['updateQuestionTitle', 'updateTypeOfContent', 'updateTypeOfQuestion'].forEach(inputId => {
+    document.getElementById(inputId).addEventListener('input', renewEditLock);
+});
+document.getElementById('presenceName').addEventListener('change', changePresenceName);
+
 // Add an event listener for the delete question form
 document.getElementById('deleteQuestionForm').addEventListener('submit', deleteQuestionById);
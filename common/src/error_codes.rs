@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Machine readable kinds of errors returned by the backend API.
+///
+/// Serialized in snake case, e.g. `"question_not_found"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// No question has the requested ID.
+    QuestionNotFound,
+    /// No webhook has the requested ID.
+    WebhookNotFound,
+    /// No webhook delivery in the dead-letter list has the requested ID.
+    DeliveryNotFound,
+    /// The request body or query string is not valid.
+    InvalidInput,
+    /// A merge patch or JSON Patch cannot be applied, or would leave an invalid question.
+    InvalidPatch,
+    /// The request body is larger than the server accepts.
+    PayloadTooLarge,
+    /// The `Content-Type` of the request body is not one the route accepts.
+    UnsupportedMediaType,
+    /// The route requires an admin API key in `X-API-Key`, and none or an unknown one was sent.
+    Unauthorized,
+    /// A browser page of another site tried to open a WebSocket of the Frontend.
+    ForbiddenOrigin,
+    /// The client used up its request budget; the `Retry-After` header tells when to retry.
+    RateLimited,
+    /// The database or the server failed while handling the request.
+    InternalError,
+    /// The Frontend could not reach the backend API.
+    BackendUnavailable,
+    /// The backend API did not answer the Frontend in time.
+    BackendTimeout,
+    /// The backend API answered the Frontend with something that is not the expected JSON.
+    InvalidBackendResponse,
+    /// The error is not one of the known kinds, e.g. an answer from an older backend.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    /// The HTTP status code that goes with this kind of error.
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorCode::QuestionNotFound
+            | ErrorCode::WebhookNotFound
+            | ErrorCode::DeliveryNotFound => 404,
+            ErrorCode::InvalidInput | ErrorCode::InvalidPatch => 400,
+            ErrorCode::Unauthorized => 401,
+            ErrorCode::ForbiddenOrigin => 403,
+            ErrorCode::PayloadTooLarge => 413,
+            ErrorCode::UnsupportedMediaType => 415,
+            ErrorCode::RateLimited => 429,
+            ErrorCode::InternalError | ErrorCode::Unknown => 500,
+            ErrorCode::BackendUnavailable | ErrorCode::InvalidBackendResponse => 502,
+            ErrorCode::BackendTimeout => 504,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = match self {
+            ErrorCode::QuestionNotFound => "question_not_found",
+            ErrorCode::WebhookNotFound => "webhook_not_found",
+            ErrorCode::DeliveryNotFound => "delivery_not_found",
+            ErrorCode::InvalidInput => "invalid_input",
+            ErrorCode::InvalidPatch => "invalid_patch",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::UnsupportedMediaType => "unsupported_media_type",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::ForbiddenOrigin => "forbidden_origin",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::InternalError => "internal_error",
+            ErrorCode::BackendUnavailable => "backend_unavailable",
+            ErrorCode::BackendTimeout => "backend_timeout",
+            ErrorCode::InvalidBackendResponse => "invalid_backend_response",
+            ErrorCode::Unknown => "unknown",
+        };
+        formatter.write_str(code)
+    }
+}
@@ -0,0 +1,22 @@
+//! Types shared by the Backend, the Frontend and the questions client,
+//! so the API schema cannot drift between them.
+
+/// Machine readable error codes of the backend API.
+mod error_codes;
+
+/// Request and response types of the backend API.
+mod models;
+
+/// Paths of the backend API routes.
+pub mod route_paths;
+
+/// HTTPS support of the servers.
+#[cfg(feature = "tls")]
+pub mod tls;
+
+pub use error_codes::ErrorCode;
+pub use models::{
+    ErrorResponse, ListParameters, MessageResponse, NewQuestion, PatchOperation, Question,
+    QuestionEvent, QuestionEventKind, ReadinessCheck, ReadinessReport, SearchParameters,
+    API_KEY_HEADER, JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE, REQUEST_ID_HEADER,
+};
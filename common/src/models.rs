@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error_codes::ErrorCode;
+
+/// Represents a single question in the database
+/// Each question includes an ID, title, content type and a list of question categories
+/// `type_of_content` holds raw Markdown, and `type_of_content_html` its sanitized rendering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Question {
+    pub question_id: i32,
+    pub question_title: String,
+    pub type_of_content: String,
+    pub type_of_question: Vec<String>,
+    #[cfg_attr(feature = "sqlx", sqlx(skip))]
+    #[serde(default)]
+    pub type_of_content_html: String,
+}
+
+/// The fields sent to the backend API to add or update a question.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NewQuestion {
+    pub question_title: String,
+    pub type_of_content: String,
+    pub type_of_question: Vec<String>,
+}
+
+/// What happened to a question.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionEventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl QuestionEventKind {
+    /// The name of the kind, also the name of the server-sent event.
+    pub fn name(self) -> &'static str {
+        match self {
+            QuestionEventKind::Created => "created",
+            QuestionEventKind::Updated => "updated",
+            QuestionEventKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A change of the questions, streamed by the events route as a server-sent event named
+/// after its `kind`, with this as JSON data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct QuestionEvent {
+    /// Whether the question was added, replaced or updated, or deleted.
+    pub kind: QuestionEventKind,
+    /// The ID of the question.
+    pub question_id: i32,
+}
+
+/// Header carrying the ID of a request from the Frontend to the Backend, so that the logs of
+/// both servers for a single user action can be matched.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header carrying an API key, which gets its own rate limit budget when the Backend knows it.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// `Content-Type` of an RFC 7396 JSON Merge Patch: the fields to change, `null` clearing a field.
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// `Content-Type` of an RFC 6902 JSON Patch: an array of `PatchOperation`.
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// One operation of an RFC 6902 JSON Patch, applied to the fields of a `NewQuestion`.
+///
+/// `path` is a JSON Pointer such as `/question_title`, `/type_of_question/0`, or
+/// `/type_of_question/-` to append a type. `move` and `copy` are not supported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PatchOperation {
+    /// Adds a value, inserting it when the path points into `type_of_question`.
+    Add { path: String, value: Value },
+    /// Removes a value; removing `type_of_content` or `type_of_question` clears it.
+    Remove { path: String },
+    /// Replaces an existing value.
+    Replace { path: String, value: Value },
+    /// Fails the whole patch unless the value at the path equals `value`.
+    Test { path: String, value: Value },
+}
+
+/// The answer of the backend API when an operation succeeds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+/// The answer of the backend API when an operation fails.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorResponse {
+    /// Human readable description of the error.
+    pub error: String,
+    /// Machine readable kind of the error.
+    #[serde(default)]
+    pub code: ErrorCode,
+}
+
+impl ErrorResponse {
+    /// Creates an error answer with the given code and message.
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        ErrorResponse {
+            error: error.into(),
+            code,
+        }
+    }
+}
+
+/// Query string of the search endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct SearchParameters {
+    /// The text to look for.
+    pub q: String,
+}
+
+/// Query string of the questions collection.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams))]
+#[cfg_attr(feature = "openapi", into_params(parameter_in = Query))]
+pub struct ListParameters {
+    /// When given, only the questions whose title, content or types contain this text,
+    /// or whose ID equals it, are listed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+}
+
+/// The answer of `/readyz`: whether the server can take traffic, with the result of each check.
+///
+/// It is answered with `200 OK` when every check passed and `503 Service Unavailable` otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    /// Whether every check passed.
+    pub ready: bool,
+    /// The checks of the dependencies of the server.
+    pub checks: Vec<ReadinessCheck>,
+}
+
+impl ReadinessReport {
+    /// Creates the report of a set of checks, ready when all of them passed.
+    pub fn new(checks: Vec<ReadinessCheck>) -> Self {
+        ReadinessReport {
+            ready: checks.iter().all(|check| check.ready),
+            checks,
+        }
+    }
+}
+
+/// The result of one readiness check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessCheck {
+    /// What was checked, e.g. `database`.
+    pub name: String,
+    /// Whether the check passed.
+    pub ready: bool,
+    /// Why the check failed, or what it found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ReadinessCheck {
+    /// A check that passed.
+    pub fn passed(name: &str) -> Self {
+        ReadinessCheck {
+            name: name.to_string(),
+            ready: true,
+            detail: None,
+        }
+    }
+
+    /// A check that failed, with the reason.
+    pub fn failed(name: &str, detail: impl Into<String>) -> Self {
+        ReadinessCheck {
+            name: name.to_string(),
+            ready: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
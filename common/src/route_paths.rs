@@ -0,0 +1,63 @@
+//! Paths of the backend API routes.
+//!
+//! The `*_ROUTE` constants use the Axum `:id` syntax and are what the Backend registers,
+//! and the functions build the matching concrete paths for clients.
+//!
+//! `/api/v1/questions` is the versioned resource layout. The older RPC-style routes are
+//! deprecated aliases, answered with a `Deprecation` header pointing at their successor.
+
+/// Collection of questions: GET to list or search with `?q=`, POST to add.
+pub const QUESTIONS_ROUTE: &str = "/api/v1/questions";
+
+/// A single question: GET, PUT to replace, PATCH to change some fields, DELETE.
+pub const QUESTION_ROUTE: &str = "/api/v1/questions/:id";
+
+/// Deprecated alias of GET `QUESTIONS_ROUTE`.
+pub const GET_ALL_QUESTIONS_ROUTE: &str = "/getAllQuestions";
+
+/// Deprecated alias of GET `QUESTION_ROUTE`.
+pub const GET_QUESTION_BY_ID_ROUTE: &str = "/getQuestionByID/:id";
+
+/// Deprecated alias of GET `QUESTIONS_ROUTE` with `?q=`.
+pub const SEARCH_QUESTIONS_ROUTE: &str = "/searchQuestions";
+
+/// Deprecated alias of DELETE `QUESTION_ROUTE`.
+pub const DELETE_QUESTION_ROUTE: &str = "/deleteQuestion/:id";
+
+/// Deprecated alias of POST `QUESTIONS_ROUTE`.
+pub const ADD_QUESTION_ROUTE: &str = "/addQuestion";
+
+/// Deprecated alias of PATCH `QUESTION_ROUTE`.
+pub const UPDATE_QUESTION_ROUTE: &str = "/updateQuestion/:id";
+
+/// Stream of the changes of the questions as server-sent events, each with an ID so that a
+/// client reconnecting with `Last-Event-ID` gets the events it missed.
+pub const EVENTS_ROUTE: &str = "/events";
+
+/// Webhooks posting the question events to other tools: GET to list, POST to register.
+/// Every webhook route requires an admin API key.
+pub const WEBHOOKS_ROUTE: &str = "/api/v1/webhooks";
+
+/// A single webhook: DELETE to unregister it, with its deliveries.
+pub const WEBHOOK_ROUTE: &str = "/api/v1/webhooks/:id";
+
+/// Delivery log of a webhook, newest first.
+pub const WEBHOOK_DELIVERIES_ROUTE: &str = "/api/v1/webhooks/:id/deliveries";
+
+/// Deliveries of every webhook that failed every attempt.
+pub const DEAD_LETTERS_ROUTE: &str = "/api/v1/webhooks/dead-letters";
+
+/// POST to deliver a dead letter again, with a fresh set of attempts.
+pub const DEAD_LETTER_REDELIVERY_ROUTE: &str = "/api/v1/webhooks/dead-letters/:id/redeliver";
+
+/// Liveness probe: answers `200 OK` as long as the process serves requests.
+pub const HEALTH_ROUTE: &str = "/healthz";
+
+/// Readiness probe: answers a `ReadinessReport`, `200 OK` when the dependencies are usable
+/// and `503 Service Unavailable` otherwise.
+pub const READINESS_ROUTE: &str = "/readyz";
+
+/// Path of the question with the given ID.
+pub fn question_path(question_id: i32) -> String {
+    QUESTION_ROUTE.replace(":id", &question_id.to_string())
+}
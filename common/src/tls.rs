@@ -0,0 +1,351 @@
+//! HTTPS support shared by the Backend and the Frontend: loading the certificate and key
+//! from PEM files, reloading them when the files change, and redirecting HTTP to HTTPS.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use rustls::crypto::ring;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+
+/// How often the certificate files are checked for changes.
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The PEM files a server presents and, for mutual TLS, trusts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TlsFiles {
+    /// The certificate chain, leaf first.
+    pub cert_path: PathBuf,
+    /// The private key of the leaf certificate.
+    pub key_path: PathBuf,
+    /// The certificate authorities that sign the client certificates; when set, clients
+    /// without a valid certificate are refused.
+    pub client_ca_path: Option<PathBuf>,
+}
+
+impl TlsFiles {
+    /// Every file of the set, to watch for changes.
+    fn paths(&self) -> Vec<&Path> {
+        let mut paths = vec![self.cert_path.as_path(), self.key_path.as_path()];
+        paths.extend(self.client_ca_path.as_deref());
+        paths
+    }
+}
+
+/// Why the TLS configuration couldn't be loaded.
+#[derive(Debug)]
+pub enum TlsError {
+    /// A PEM file couldn't be read or holds nothing usable.
+    Pem { path: PathBuf, message: String },
+    /// rustls refused the certificate, the key or the client certificate authorities.
+    Config(String),
+}
+
+impl fmt::Display for TlsError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlsError::Pem { path, message } => {
+                write!(formatter, "Couldn't load {}: {}", path.display(), message)
+            }
+            TlsError::Config(message) => {
+                write!(formatter, "Invalid TLS configuration: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TlsError {}
+
+/// Reads every certificate of a PEM file.
+fn load_certificates(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let pem_error = |message: String| TlsError::Pem {
+        path: path.to_path_buf(),
+        message,
+    };
+    let certificates = CertificateDer::pem_file_iter(path)
+        .map_err(|error| pem_error(error.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| pem_error(error.to_string()))?;
+    if certificates.is_empty() {
+        return Err(pem_error("no certificate found".to_string()));
+    }
+    Ok(certificates)
+}
+
+/// Reads the first private key of a PEM file, in the PKCS#8, PKCS#1 or SEC1 format.
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+    PrivateKeyDer::from_pem_file(path).map_err(|error| TlsError::Pem {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })
+}
+
+/// Builds the rustls configuration of a server, offering HTTP/2 and HTTP/1.1.
+///
+/// # Parameters
+/// - `tls_files`: The certificate, key and optional client certificate authorities.
+pub fn load_server_config(tls_files: &TlsFiles) -> Result<ServerConfig, TlsError> {
+    let certificates = load_certificates(&tls_files.cert_path)?;
+    let private_key = load_private_key(&tls_files.key_path)?;
+    let config_builder = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|error| TlsError::Config(error.to_string()))?;
+    let config_builder = match &tls_files.client_ca_path {
+        Some(client_ca_path) => {
+            let mut client_roots = RootCertStore::empty();
+            for certificate in load_certificates(client_ca_path)? {
+                client_roots
+                    .add(certificate)
+                    .map_err(|error| TlsError::Config(error.to_string()))?;
+            }
+            let client_verifier = WebPkiClientVerifier::builder_with_provider(
+                Arc::new(client_roots),
+                Arc::new(ring::default_provider()),
+            )
+            .build()
+            .map_err(|error| TlsError::Config(error.to_string()))?;
+            config_builder.with_client_cert_verifier(client_verifier)
+        }
+        None => config_builder.with_no_client_auth(),
+    };
+    let mut server_config = config_builder
+        .with_single_cert(certificates, private_key)
+        .map_err(|error| TlsError::Config(error.to_string()))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(server_config)
+}
+
+/// The TLS configuration of a server, replaced when its files change so that certificates
+/// can be renewed without a restart.
+///
+/// Connections already established keep the configuration they were accepted with.
+pub struct ReloadingTlsConfig {
+    tls_files: TlsFiles,
+    server_config: RwLock<Arc<ServerConfig>>,
+}
+
+impl ReloadingTlsConfig {
+    /// Loads the configuration and starts checking its files for changes in the background.
+    ///
+    /// # Parameters
+    /// - `tls_files`: The certificate, key and optional client certificate authorities.
+    ///
+    /// # Returns
+    /// The shared configuration, or why it couldn't be loaded.
+    pub fn load(tls_files: TlsFiles) -> Result<Arc<Self>, TlsError> {
+        let server_config = load_server_config(&tls_files)?;
+        let tls_config = Arc::new(ReloadingTlsConfig {
+            tls_files,
+            server_config: RwLock::new(Arc::new(server_config)),
+        });
+        tokio::spawn(watch_tls_files(Arc::downgrade(&tls_config)));
+        Ok(tls_config)
+    }
+
+    /// An acceptor performing the handshakes with the current configuration.
+    pub fn acceptor(&self) -> TlsAcceptor {
+        let server_config = self
+            .server_config
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        TlsAcceptor::from(server_config)
+    }
+
+    /// The modification times of the files, `None` for those that can't be read.
+    fn modification_times(&self) -> Vec<Option<SystemTime>> {
+        self.tls_files
+            .paths()
+            .into_iter()
+            .map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Loads the files again, keeping the current configuration when they are invalid, e.g.
+    /// when the certificate was replaced but not yet the key.
+    fn reload(&self) {
+        match load_server_config(&self.tls_files) {
+            Ok(server_config) => {
+                *self
+                    .server_config
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = Arc::new(server_config);
+                tracing::info!(
+                    cert_path = %self.tls_files.cert_path.display(),
+                    "TLS certificate reloaded"
+                );
+            }
+            Err(error) => {
+                tracing::warn!(%error, "Couldn't reload the TLS certificate, keeping the current one")
+            }
+        }
+    }
+}
+
+/// Reloads the configuration whenever the modification time of one of its files changes,
+/// until the configuration is dropped.
+async fn watch_tls_files(tls_config: std::sync::Weak<ReloadingTlsConfig>) {
+    let mut known_times = match tls_config.upgrade() {
+        Some(tls_config) => tls_config.modification_times(),
+        None => return,
+    };
+    loop {
+        tokio::time::sleep(RELOAD_CHECK_INTERVAL).await;
+        let Some(tls_config) = tls_config.upgrade() else {
+            return;
+        };
+        let modification_times = tls_config.modification_times();
+        if modification_times != known_times {
+            known_times = modification_times;
+            tls_config.reload();
+        }
+    }
+}
+
+/// The `Location` of the redirect of a plain HTTP request to the HTTPS server.
+///
+/// # Parameters
+/// - `host`: The `Host` header of the request, possibly with a port.
+/// - `path_and_query`: The path of the request, with its query string.
+/// - `https_port`: The port of the HTTPS server, left out of the URL when it's 443.
+///
+/// # Returns
+/// The HTTPS URL, or `None` when the host is missing or malformed.
+pub fn https_redirect_location(
+    host: Option<&str>,
+    path_and_query: &str,
+    https_port: u16,
+) -> Option<String> {
+    let host = host?.trim();
+    // Strip the port, keeping the brackets of an IPv6 address
+    let host_name = match host.rsplit_once(':') {
+        Some((host_name, port)) if !port.contains(']') => {
+            if !port.chars().all(|character| character.is_ascii_digit()) {
+                return None;
+            }
+            host_name
+        }
+        _ => host,
+    };
+    let valid_host = match host_name
+        .strip_prefix('[')
+        .and_then(|ip_literal| ip_literal.strip_suffix(']'))
+    {
+        Some(ip_literal) => {
+            !ip_literal.is_empty()
+                && ip_literal
+                    .chars()
+                    .all(|character| character.is_ascii_hexdigit() || ":.".contains(character))
+        }
+        None => {
+            !host_name.is_empty()
+                && host_name
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || "-.".contains(character))
+        }
+    };
+    if !valid_host {
+        return None;
+    }
+    let path_and_query = if path_and_query.starts_with('/') {
+        path_and_query
+    } else {
+        "/"
+    };
+    Some(match https_port {
+        443 => format!("https://{}{}", host_name, path_and_query),
+        port => format!("https://{}:{}{}", host_name, port, path_and_query),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::https_redirect_location;
+
+    /// The redirect of `/questions?page=2` to an HTTPS server on the given port.
+    fn location(host: &str, https_port: u16) -> Option<String> {
+        https_redirect_location(Some(host), "/questions?page=2", https_port)
+    }
+
+    #[test]
+    fn the_port_of_the_host_is_replaced_by_the_https_port() {
+        assert_eq!(
+            location("questions.example.com:8080", 8443).as_deref(),
+            Some("https://questions.example.com:8443/questions?page=2")
+        );
+        assert_eq!(
+            location("localhost", 2443).as_deref(),
+            Some("https://localhost:2443/questions?page=2")
+        );
+        assert_eq!(
+            location(" 192.0.2.7:80 ", 8443).as_deref(),
+            Some("https://192.0.2.7:8443/questions?page=2")
+        );
+    }
+
+    #[test]
+    fn the_default_https_port_is_left_out() {
+        assert_eq!(
+            location("questions.example.com:80", 443).as_deref(),
+            Some("https://questions.example.com/questions?page=2")
+        );
+    }
+
+    #[test]
+    fn ipv6_addresses_keep_their_brackets() {
+        assert_eq!(
+            location("[::1]:8080", 8443).as_deref(),
+            Some("https://[::1]:8443/questions?page=2")
+        );
+        assert_eq!(
+            location("[::1]", 443).as_deref(),
+            Some("https://[::1]/questions?page=2")
+        );
+        assert_eq!(
+            location("[2001:db8::7]", 8443).as_deref(),
+            Some("https://[2001:db8::7]:8443/questions?page=2")
+        );
+    }
+
+    #[test]
+    fn missing_and_malformed_hosts_are_refused() {
+        assert_eq!(https_redirect_location(None, "/", 8443), None);
+        for host in [
+            "",
+            " ",
+            ":8080",
+            "::1",
+            "[]",
+            "[::1",
+            "[evil.example.com]",
+            "example.com:http",
+            "evil.example.com/phishing",
+            "user@example.com",
+            "example.com\\evil",
+            "example.com?",
+        ] {
+            assert_eq!(location(host, 8443), None, "{:?} should be refused", host);
+        }
+    }
+
+    #[test]
+    fn paths_not_starting_with_a_slash_redirect_to_the_root() {
+        for path_and_query in ["", "questions", "*", "https://evil.example.com/"] {
+            assert_eq!(
+                https_redirect_location(Some("example.com"), path_and_query, 8443).as_deref(),
+                Some("https://example.com:8443/"),
+                "{:?}",
+                path_and_query
+            );
+        }
+    }
+}